@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Harness settings, stored in `~/.config/doctown/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Python interpreter used to run the services
+    pub python: String,
+    pub embedding: ServiceConfig,
+    pub documenter: ServiceConfig,
+    pub doctown_main: ServiceConfig,
+    pub database: ServiceConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    pub name: String,
+    pub port: u16,
+    /// URL polled for the status panel
+    pub endpoint: String,
+    /// Working directory, relative to the project root
+    pub dir: String,
+}
+
+impl ServiceConfig {
+    fn new(name: &str, port: u16, endpoint: &str, dir: &str) -> Self {
+        ServiceConfig {
+            name: name.to_string(),
+            port,
+            endpoint: endpoint.to_string(),
+            dir: dir.to_string(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            python: "python".to_string(),
+            embedding: ServiceConfig::new(
+                "Embedding Service",
+                18115,
+                "http://localhost:18115/health",
+                "python/embedding",
+            ),
+            documenter: ServiceConfig::new(
+                "Documenter Service",
+                18116,
+                "http://localhost:18116/health",
+                "python/documenter",
+            ),
+            doctown_main: ServiceConfig::new("Doctown Main", 3000, "http://localhost:3000/health", "."),
+            database: ServiceConfig::new("Database", 5432, "http://localhost:5432", "."),
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/doctown/config.toml`, falling back to `~/.config`
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("doctown").join("config.toml"))
+    }
+
+    /// Load the saved config, or the defaults if none has been saved
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid config {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = Self::path().ok_or("No home directory to save the config in")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+
+        let text = toml::to_string_pretty(self).map_err(|e| format!("Failed to encode config: {}", e))?;
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    pub fn services(&self) -> [&ServiceConfig; 4] {
+        [&self.embedding, &self.documenter, &self.doctown_main, &self.database]
+    }
+
+    fn services_mut(&mut self) -> [&mut ServiceConfig; 4] {
+        [
+            &mut self.embedding,
+            &mut self.documenter,
+            &mut self.doctown_main,
+            &mut self.database,
+        ]
+    }
+
+    /// Editable settings as (label, value) pairs, in display order
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![("Python".to_string(), self.python.clone())];
+        for service in self.services() {
+            fields.push((format!("{} name", service.name), service.name.clone()));
+            fields.push((format!("{} port", service.name), service.port.to_string()));
+            fields.push((format!("{} endpoint", service.name), service.endpoint.clone()));
+            fields.push((format!("{} directory", service.name), service.dir.clone()));
+        }
+        fields
+    }
+
+    /// Set the field at `index` in `fields()` order
+    pub fn set_field(&mut self, index: usize, value: String) -> Result<(), String> {
+        if index == 0 {
+            self.python = value;
+            return Ok(());
+        }
+
+        let (service, field) = ((index - 1) / 4, (index - 1) % 4);
+        let service = self
+            .services_mut()
+            .into_iter()
+            .nth(service)
+            .ok_or_else(|| format!("No setting at index {}", index))?;
+        match field {
+            0 => service.name = value,
+            1 => {
+                service.port = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid port: {}", value))?
+            }
+            2 => service.endpoint = value,
+            _ => service.dir = value,
+        }
+        Ok(())
+    }
+}
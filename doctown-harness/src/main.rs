@@ -1,14 +1,26 @@
+mod config;
+
+use config::Config;
 use minui::prelude::*;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::process::{Child, Command};
+use std::process::{Child, Command, Stdio};
 use std::path::PathBuf;
 
+/// Lines of output kept per service process
+const LOG_CAPACITY: usize = 1000;
+
+/// Most recent output lines of a service, oldest first
+type LogBuffer = Arc<Mutex<VecDeque<String>>>;
+
 #[derive(Debug, Clone)]
 enum MenuItem {
     Launch,
     Restart,
     LaunchService,
+    Logs,
     Configuration,
     Quit,
 }
@@ -19,6 +31,7 @@ impl MenuItem {
             MenuItem::Launch => "Launch",
             MenuItem::Restart => "Restart",
             MenuItem::LaunchService => "Launch Service",
+            MenuItem::Logs => "Service Logs",
             MenuItem::Configuration => "Configuration",
             MenuItem::Quit => "Quit",
         }
@@ -72,7 +85,7 @@ impl ServiceStatus {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum ServiceType {
     PythonEmbedding,
     PythonDocumenter,
@@ -87,12 +100,15 @@ struct Service {
     status: ServiceStatus,
     endpoint: String,
     service_type: ServiceType,
+    /// Found running when we went to launch it; the harness won't stop it
+    adopted: bool,
 }
 
-#[allow(dead_code)]
 struct ServiceProcess {
     child: Child,
     service_type: ServiceType,
+    /// Captured stdout and stderr
+    logs: LogBuffer,
 }
 
 impl Service {
@@ -103,6 +119,7 @@ impl Service {
             status: ServiceStatus::Offline,
             endpoint: endpoint.to_string(),
             service_type,
+            adopted: false,
         }
     }
 }
@@ -111,6 +128,8 @@ impl Service {
 enum MenuMode {
     Main,
     ServiceSubmenu,
+    Logs,
+    Configuration,
 }
 
 struct MenuState {
@@ -121,15 +140,34 @@ struct MenuState {
     processes: Arc<Mutex<Vec<ServiceProcess>>>,
     running: bool,
     mode: MenuMode,
+    config: Config,
+    /// Text being typed into the selected configuration field
+    editing: Option<String>,
+    /// Last error or notice, shown in the status panel
+    message: Option<String>,
+}
+
+fn services_from_config(config: &Config) -> Vec<Service> {
+    let types = [
+        ServiceType::PythonEmbedding,
+        ServiceType::PythonDocumenter,
+        ServiceType::RustMain,
+        ServiceType::Database,
+    ];
+    config
+        .services()
+        .iter()
+        .zip(types)
+        .map(|(service, service_type)| Service::new(&service.name, service.port, &service.endpoint, service_type))
+        .collect()
 }
 
 fn main() -> minui::Result<()> {
-    let services = Arc::new(Mutex::new(vec![
-        Service::new("Embedding Service", 18115, "http://localhost:18115/health", ServiceType::PythonEmbedding),
-        Service::new("Documenter Service", 18116, "http://localhost:18116/health", ServiceType::PythonDocumenter),
-        Service::new("Doctown Main", 3000, "http://localhost:3000/health", ServiceType::RustMain),
-        Service::new("Database", 5432, "http://localhost:5432", ServiceType::Database),
-    ]));
+    let (config, message) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+    let services = Arc::new(Mutex::new(services_from_config(&config)));
 
     let processes = Arc::new(Mutex::new(Vec::<ServiceProcess>::new()));
 
@@ -139,6 +177,7 @@ fn main() -> minui::Result<()> {
             MenuItem::Launch,
             MenuItem::Restart,
             MenuItem::LaunchService,
+            MenuItem::Logs,
             MenuItem::Configuration,
             MenuItem::Quit,
         ],
@@ -153,6 +192,9 @@ fn main() -> minui::Result<()> {
         processes: Arc::clone(&processes),
         running: true,
         mode: MenuMode::Main,
+        config,
+        editing: None,
+        message,
     };
 
     // Spawn background task for status polling
@@ -171,10 +213,15 @@ fn main() -> minui::Result<()> {
 
     app.run(
         |state, event| {
+            if state.editing.is_some() {
+                handle_edit_event(&event, state);
+                return state.running;
+            }
+
             match event {
                 Event::Character('q') | Event::Escape => match state.mode {
                     MenuMode::Main => state.running = false,
-                    MenuMode::ServiceSubmenu => {
+                    MenuMode::ServiceSubmenu | MenuMode::Logs | MenuMode::Configuration => {
                         state.mode = MenuMode::Main;
                         state.selected = 0;
                     }
@@ -188,6 +235,8 @@ fn main() -> minui::Result<()> {
                     let max_items = match state.mode {
                         MenuMode::Main => state.items.len(),
                         MenuMode::ServiceSubmenu => state.sub_items.len(),
+                        MenuMode::Logs => state.services.lock().unwrap().len() + 1,
+                        MenuMode::Configuration => config_items(&state.config).len(),
                     };
                     if state.selected < max_items - 1 {
                         state.selected += 1;
@@ -202,6 +251,14 @@ fn main() -> minui::Result<()> {
                         let selected_item = state.sub_items[state.selected].clone();
                         handle_submenu_selection(&selected_item, state);
                     }
+                    MenuMode::Logs => {
+                        // Only the Back row does anything; the log follows the selection
+                        if state.selected == state.services.lock().unwrap().len() {
+                            state.mode = MenuMode::Main;
+                            state.selected = 0;
+                        }
+                    }
+                    MenuMode::Configuration => handle_config_selection(state),
                 },
                 _ => {}
             }
@@ -233,6 +290,8 @@ fn main() -> minui::Result<()> {
             let menu_title = match state.mode {
                 MenuMode::Main => "MAIN MENU",
                 MenuMode::ServiceSubmenu => "LAUNCH SERVICE",
+                MenuMode::Logs => "SERVICE LOGS",
+                MenuMode::Configuration => "CONFIGURATION",
             };
             menu_panel = menu_panel.add_child(
                 Label::new(menu_title)
@@ -249,6 +308,25 @@ fn main() -> minui::Result<()> {
                     .iter()
                     .map(|i| i.label().to_string())
                     .collect(),
+                MenuMode::Logs => {
+                    let mut items: Vec<String> = state
+                        .services
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .map(|s| s.name.clone())
+                        .collect();
+                    items.push("← Back to Main Menu".to_string());
+                    items
+                }
+                MenuMode::Configuration => {
+                    let mut items = config_items(&state.config);
+                    if let (Some(buffer), Some(item)) = (&state.editing, items.get_mut(state.selected)) {
+                        let label = item.split(':').next().unwrap_or_default().to_string();
+                        *item = format!("{}: {}_", label, buffer);
+                    }
+                    items
+                }
             };
 
             for (idx, item_label) in items_to_display.iter().enumerate() {
@@ -274,8 +352,40 @@ fn main() -> minui::Result<()> {
             // Add spacing
             menu_panel = menu_panel.add_child(Label::new(""));
 
+            // Tail of the selected service's output
+            if let MenuMode::Logs = state.mode {
+                let service_type = state
+                    .services
+                    .lock()
+                    .unwrap()
+                    .get(state.selected)
+                    .map(|s| s.service_type);
+                if let Some(service_type) = service_type {
+                    let max_lines = (height as usize).saturating_sub(24).max(5);
+                    let lines = service_logs(&state.processes, service_type, max_lines);
+                    if lines.is_empty() {
+                        menu_panel = menu_panel.add_child(
+                            Label::new("(no output captured)").with_text_color(Color::DarkGray),
+                        );
+                    }
+                    for line in lines {
+                        menu_panel = menu_panel.add_child(
+                            Label::new(&line)
+                                .with_text_color(Color::White)
+                                .with_alignment(Alignment::Left),
+                        );
+                    }
+                    menu_panel = menu_panel.add_child(Label::new(""));
+                }
+            }
+
             // Footer with controls
-            let footer = Label::new("↑/↓: Navigate  |  Enter: Select  |  Q/Esc: Back/Quit")
+            let controls = if state.editing.is_some() {
+                "Type to edit  |  Enter: Apply  |  Esc: Cancel"
+            } else {
+                "↑/↓: Navigate  |  Enter: Select  |  Q/Esc: Back/Quit"
+            };
+            let footer = Label::new(controls)
                 .with_text_color(Color::DarkGray)
                 .with_alignment(Alignment::Center);
 
@@ -299,10 +409,11 @@ fn main() -> minui::Result<()> {
             let services = state.services.lock().unwrap();
             for service in services.iter() {
                 let status_line = format!(
-                    "{} {} :{}",
+                    "{} {} :{}{}",
                     service.status.label(),
                     service.name,
-                    service.port
+                    service.port,
+                    if service.adopted { " (external)" } else { "" }
                 );
 
                 status_panel = status_panel.add_child(
@@ -319,6 +430,15 @@ fn main() -> minui::Result<()> {
                     .with_alignment(Alignment::Center),
             );
 
+            if let Some(message) = &state.message {
+                status_panel = status_panel.add_child(Label::new(""));
+                status_panel = status_panel.add_child(
+                    Label::new(message)
+                        .with_text_color(Color::Yellow)
+                        .with_alignment(Alignment::Left),
+                );
+            }
+
             // Draw both panels directly
             menu_panel.draw(window)?;
             status_panel.draw(window)?;
@@ -335,7 +455,7 @@ async fn check_services_status(services: &Arc<Mutex<Vec<Service>>>) {
 
     for service in services_guard.iter_mut() {
         // Simple TCP port check for database, HTTP check for others
-        let is_online = if service.port == 5432 {
+        let is_online = if matches!(service.service_type, ServiceType::Database) {
             check_tcp_port(service.port).await
         } else {
             check_http_endpoint(&service.endpoint).await
@@ -346,6 +466,9 @@ async fn check_services_status(services: &Arc<Mutex<Vec<Service>>>) {
         } else {
             ServiceStatus::Offline
         };
+        if !is_online {
+            service.adopted = false;
+        }
     }
 }
 
@@ -378,31 +501,28 @@ fn get_project_root() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(".."))
 }
 
-fn launch_service(service_type: ServiceType, processes: &Arc<Mutex<Vec<ServiceProcess>>>) -> std::result::Result<(), String> {
+fn launch_service(service_type: ServiceType, config: &Config, processes: &Arc<Mutex<Vec<ServiceProcess>>>) -> std::result::Result<(), String> {
     let project_root = get_project_root();
 
-    let child = match service_type {
+    let (child, logs) = match service_type {
         ServiceType::PythonEmbedding => {
-            let python_path = project_root.join("python").join("embedding");
-            launch_in_terminal(
-                "Embedding Service",
-                &["python", "server.py"],
+            let python_path = project_root.join(&config.embedding.dir);
+            spawn_service(
+                &[config.python.as_str(), "server.py"],
                 &python_path,
             ).map_err(|e| format!("Failed to launch embedding service: {}", e))?
         }
         ServiceType::PythonDocumenter => {
-            let python_path = project_root.join("python").join("documenter");
-            launch_in_terminal(
-                "Documenter Service",
-                &["python", "server.py"],
+            let python_path = project_root.join(&config.documenter.dir);
+            spawn_service(
+                &[config.python.as_str(), "server.py"],
                 &python_path,
             ).map_err(|e| format!("Failed to launch documenter service: {}", e))?
         }
         ServiceType::RustMain => {
-            launch_in_terminal(
-                "Doctown Main",
+            spawn_service(
                 &["cargo", "run", "--release"],
-                &project_root,
+                &project_root.join(&config.doctown_main.dir),
             ).map_err(|e| format!("Failed to launch Doctown main: {}", e))?
         }
         ServiceType::Database => {
@@ -414,83 +534,121 @@ fn launch_service(service_type: ServiceType, processes: &Arc<Mutex<Vec<ServicePr
     procs.push(ServiceProcess {
         child,
         service_type: service_type.clone(),
+        logs,
     });
 
     Ok(())
 }
 
-fn launch_in_terminal(title: &str, command_args: &[&str], working_dir: &PathBuf) -> std::io::Result<Child> {
-    // Build the command string to execute
-    let command_str = command_args.join(" ");
-    
-    // Try x-terminal-emulator first (Debian/Ubuntu default)
-    let result = Command::new("x-terminal-emulator")
-        .arg("-T")
-        .arg(title)
-        .arg("-e")
-        .arg("bash")
-        .arg("-c")
-        .arg(format!("cd '{}' && {} ; echo 'Press Enter to close...'; read", working_dir.display(), command_str))
-        .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn();
-    
-    if result.is_ok() {
-        return result;
+/// Run a service command in `working_dir`, capturing its output
+fn spawn_service(command_args: &[&str], working_dir: &PathBuf) -> std::io::Result<(Child, LogBuffer)> {
+    let (program, args) = command_args.split_first().expect("empty service command");
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(working_dir)
+        // Python block-buffers piped output; we want lines as they happen
+        .env("PYTHONUNBUFFERED", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let logs: LogBuffer = Arc::new(Mutex::new(VecDeque::with_capacity(LOG_CAPACITY)));
+    if let Some(stdout) = child.stdout.take() {
+        capture_output(stdout, Arc::clone(&logs));
     }
-    
-    // Try xterm (widely available)
-    let result = Command::new("xterm")
-        .arg("-title")
-        .arg(title)
-        .arg("-hold")
-        .arg("-e")
-        .arg("bash")
-        .arg("-c")
-        .arg(format!("cd '{}' && {}", working_dir.display(), command_str))
-        .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn();
-    
-    if result.is_ok() {
-        return result;
+    if let Some(stderr) = child.stderr.take() {
+        capture_output(stderr, Arc::clone(&logs));
     }
-    
-    // Try konsole (KDE)
-    let result = Command::new("konsole")
-        .arg("--title")
-        .arg(title)
-        .arg("-e")
-        .arg("bash")
-        .arg("-c")
-        .arg(format!("cd '{}' && {} ; exec bash", working_dir.display(), command_str))
-        .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn();
-    
-    if result.is_ok() {
-        return result;
-    }
-    
-    // Last resort: try gnome-terminal with proper environment
-    Command::new("sh")
-        .arg("-c")
-        .arg(format!("gnome-terminal --title='{}' -- bash -c \"cd '{}' && {} ; exec bash\"", title, working_dir.display(), command_str))
-        .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn()
+
+    Ok((child, logs))
+}
+
+/// Copy lines from `output` into `logs` until the process closes it
+fn capture_output(output: impl Read + Send + 'static, logs: LogBuffer) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let mut logs = logs.lock().unwrap();
+            if logs.len() == LOG_CAPACITY {
+                logs.pop_front();
+            }
+            logs.push_back(line);
+        }
+    });
 }
 
-fn stop_all_services(processes: &Arc<Mutex<Vec<ServiceProcess>>>) {
+/// Last `max_lines` lines of the most recently launched process of a service
+fn service_logs(processes: &Arc<Mutex<Vec<ServiceProcess>>>, service_type: ServiceType, max_lines: usize) -> Vec<String> {
+    let procs = processes.lock().unwrap();
+    let Some(process) = procs.iter().rev().find(|p| p.service_type == service_type) else {
+        return Vec::new();
+    };
+
+    let logs = process.logs.lock().unwrap();
+    logs.iter().skip(logs.len().saturating_sub(max_lines)).cloned().collect()
+}
+
+/// Stop every process the harness spawned (adopted services are left
+/// alone), returning the services that were stopped
+fn stop_all_services(processes: &Arc<Mutex<Vec<ServiceProcess>>>) -> Vec<ServiceType> {
     let mut procs = processes.lock().unwrap();
+    let mut stopped = Vec::new();
     for proc in procs.iter_mut() {
         let _ = proc.child.kill();
+        stopped.push(proc.service_type);
     }
     procs.clear();
+    stopped
+}
+
+/// Whether a process the harness spawned for this service is still running
+fn owns_running_process(processes: &Arc<Mutex<Vec<ServiceProcess>>>, service_type: ServiceType) -> bool {
+    processes
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .any(|p| p.service_type == service_type && matches!(p.child.try_wait(), Ok(None)))
+}
+
+/// Launch a service unless it is already up; one that is up without the
+/// harness having started it is adopted instead
+fn launch_if_needed(service_type: ServiceType, state: &mut MenuState) -> std::result::Result<(), String> {
+    if owns_running_process(&state.processes, service_type) {
+        state.message = Some(format!("{} is already running", service_name(state, service_type)));
+        return Ok(());
+    }
+
+    {
+        let mut services = state.services.lock().unwrap();
+        if let Some(service) = services
+            .iter_mut()
+            .find(|s| s.service_type == service_type && s.status == ServiceStatus::Online)
+        {
+            service.adopted = true;
+            state.message = Some(format!(
+                "{} is already running on :{}; using it",
+                service.name, service.port
+            ));
+            return Ok(());
+        }
+    }
+
+    launch_service(service_type, &state.config, &state.processes)
+}
+
+fn service_name(state: &MenuState, service_type: ServiceType) -> String {
+    state
+        .services
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|s| s.service_type == service_type)
+        .map(|s| s.name.clone())
+        .unwrap_or_default()
 }
 
 fn handle_selection(item: &MenuItem, state: &mut MenuState) {
@@ -499,18 +657,24 @@ fn handle_selection(item: &MenuItem, state: &mut MenuState) {
             // Launch all services
             let services = state.services.lock().unwrap().clone();
             for service in services.iter() {
-                if let Err(e) = launch_service(service.service_type.clone(), &state.processes) {
+                if let Err(e) = launch_if_needed(service.service_type, state) {
                     eprintln!("Failed to launch {}: {}", service.name, e);
                 }
             }
         }
         MenuItem::Restart => {
             // Restart all services
-            stop_all_services(&state.processes);
+            let stopped = stop_all_services(&state.processes);
             std::thread::sleep(Duration::from_secs(1));
             let services = state.services.lock().unwrap().clone();
             for service in services.iter() {
-                if let Err(e) = launch_service(service.service_type.clone(), &state.processes) {
+                // Our own services may still look online until the next poll
+                let result = if stopped.contains(&service.service_type) {
+                    launch_service(service.service_type, &state.config, &state.processes)
+                } else {
+                    launch_if_needed(service.service_type, state)
+                };
+                if let Err(e) = result {
                     eprintln!("Failed to restart {}: {}", service.name, e);
                 }
             }
@@ -519,8 +683,13 @@ fn handle_selection(item: &MenuItem, state: &mut MenuState) {
             state.mode = MenuMode::ServiceSubmenu;
             state.selected = 0;
         }
+        MenuItem::Logs => {
+            state.mode = MenuMode::Logs;
+            state.selected = 0;
+        }
         MenuItem::Configuration => {
-            // TODO: Show configuration screen
+            state.mode = MenuMode::Configuration;
+            state.selected = 0;
         }
         MenuItem::Quit => {
             stop_all_services(&state.processes);
@@ -532,22 +701,22 @@ fn handle_selection(item: &MenuItem, state: &mut MenuState) {
 fn handle_submenu_selection(item: &SubMenuItem, state: &mut MenuState) {
     match item {
         SubMenuItem::EmbeddingService => {
-            if let Err(e) = launch_service(ServiceType::PythonEmbedding, &state.processes) {
+            if let Err(e) = launch_if_needed(ServiceType::PythonEmbedding, state) {
                 eprintln!("Failed to launch embedding service: {}", e);
             }
         }
         SubMenuItem::DocumenterService => {
-            if let Err(e) = launch_service(ServiceType::PythonDocumenter, &state.processes) {
+            if let Err(e) = launch_if_needed(ServiceType::PythonDocumenter, state) {
                 eprintln!("Failed to launch documenter service: {}", e);
             }
         }
         SubMenuItem::DoctownMain => {
-            if let Err(e) = launch_service(ServiceType::RustMain, &state.processes) {
+            if let Err(e) = launch_if_needed(ServiceType::RustMain, state) {
                 eprintln!("Failed to launch Doctown main: {}", e);
             }
         }
         SubMenuItem::Database => {
-            if let Err(e) = launch_service(ServiceType::Database, &state.processes) {
+            if let Err(e) = launch_if_needed(ServiceType::Database, state) {
                 eprintln!("Failed to launch database: {}", e);
             }
         }
@@ -557,3 +726,54 @@ fn handle_submenu_selection(item: &SubMenuItem, state: &mut MenuState) {
         }
     }
 }
+
+/// Configuration screen rows: every setting, then Save and Back
+fn config_items(config: &Config) -> Vec<String> {
+    let mut items: Vec<String> = config
+        .fields()
+        .into_iter()
+        .map(|(label, value)| format!("{}: {}", label, value))
+        .collect();
+    items.push("Save".to_string());
+    items.push("← Back to Main Menu".to_string());
+    items
+}
+
+fn handle_config_selection(state: &mut MenuState) {
+    let field_count = state.config.fields().len();
+
+    if state.selected < field_count {
+        // Start editing from the current value
+        state.editing = Some(state.config.fields()[state.selected].1.clone());
+    } else if state.selected == field_count {
+        state.message = Some(match state.config.save() {
+            Ok(path) => format!("Saved to {}", path.display()),
+            Err(e) => e,
+        });
+        *state.services.lock().unwrap() = services_from_config(&state.config);
+    } else {
+        state.mode = MenuMode::Main;
+        state.selected = 0;
+    }
+}
+
+fn handle_edit_event(event: &Event, state: &mut MenuState) {
+    let Some(buffer) = state.editing.as_mut() else {
+        return;
+    };
+
+    match event {
+        Event::Character(c) => buffer.push(*c),
+        Event::Backspace => {
+            buffer.pop();
+        }
+        Event::Escape => state.editing = None,
+        Event::Enter => {
+            let value = state.editing.take().unwrap_or_default();
+            if let Err(e) = state.config.set_field(state.selected, value) {
+                state.message = Some(e);
+            }
+        }
+        _ => {}
+    }
+}
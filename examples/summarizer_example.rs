@@ -126,7 +126,7 @@ async fn process_file(path: &Path) -> Result<Vec<Chunk>> {
 }
 
 async fn example_batch() -> Result<()> {
-    let chunks = vec![
+    let chunks = [
         "fn add(a: i32, b: i32) -> i32 { a + b }",
         "fn multiply(a: i32, b: i32) -> i32 { a * b }",
         "fn divide(a: i32, b: i32) -> Option<i32> { if b != 0 { Some(a / b) } else { None } }",
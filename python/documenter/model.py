@@ -5,7 +5,10 @@ Uses the model registry to load and configure different LLMs
 with appropriate parameters and settings.
 """
 
-from transformers import AutoModelForCausalLM, AutoTokenizer, BitsAndBytesConfig
+from threading import Thread
+from typing import Iterator
+
+from transformers import AutoModelForCausalLM, AutoTokenizer, BitsAndBytesConfig, TextIteratorStreamer
 import torch
 from registry import get_model_config, list_available_models
 
@@ -95,17 +98,12 @@ class DocumenterModel:
                 return f"{system_message}\n\n{user_message}"
             return user_message
 
-    def summarize(self, text: str, instructions: str = "", system_prompt: str = "") -> str:
+    def _build_inputs(self, text: str, instructions: str = "", system_prompt: str = ""):
         """
-        Generate a summary of the given text.
-
-        Args:
-            text: The text/code to summarize
-            instructions: Specific instructions for this summary
-            system_prompt: Optional system-level prompt
+        Build the prompt for a summary and tokenize it.
 
         Returns:
-            Generated summary as a string
+            (prompt, inputs) where inputs are on the model's device
         """
         # Build the user message
         if instructions:
@@ -124,19 +122,38 @@ class DocumenterModel:
             max_length=4096  # Most models support at least 4k context
         ).to(self.model.device)
 
+        return prompt, inputs
+
+    def _generation_kwargs(self) -> dict:
+        """Model-specific parameters for generate()."""
+        return {
+            "max_new_tokens": self.config.max_new_tokens,
+            "temperature": self.config.temperature,
+            "do_sample": self.config.do_sample,
+            "top_p": self.config.top_p,
+            "top_k": self.config.top_k,
+            "repetition_penalty": self.config.repetition_penalty,
+            "eos_token_id": self.config.eos_token_id or self.tokenizer.eos_token_id,
+            "pad_token_id": self.config.pad_token_id or self.tokenizer.pad_token_id,
+        }
+
+    def summarize(self, text: str, instructions: str = "", system_prompt: str = "") -> str:
+        """
+        Generate a summary of the given text.
+
+        Args:
+            text: The text/code to summarize
+            instructions: Specific instructions for this summary
+            system_prompt: Optional system-level prompt
+
+        Returns:
+            Generated summary as a string
+        """
+        prompt, inputs = self._build_inputs(text, instructions, system_prompt)
+
         # Generate with model-specific parameters
         with torch.no_grad():
-            outputs = self.model.generate(
-                **inputs,
-                max_new_tokens=self.config.max_new_tokens,
-                temperature=self.config.temperature,
-                do_sample=self.config.do_sample,
-                top_p=self.config.top_p,
-                top_k=self.config.top_k,
-                repetition_penalty=self.config.repetition_penalty,
-                eos_token_id=self.config.eos_token_id or self.tokenizer.eos_token_id,
-                pad_token_id=self.config.pad_token_id or self.tokenizer.pad_token_id,
-            )
+            outputs = self.model.generate(**inputs, **self._generation_kwargs())
 
         # Decode output
         full_output = self.tokenizer.decode(outputs[0], skip_special_tokens=True)
@@ -155,3 +172,34 @@ class DocumenterModel:
             return full_output.replace(prompt, "").strip()
 
         return full_output.strip()
+
+    def summarize_stream(self, text: str, instructions: str = "", system_prompt: str = "") -> Iterator[str]:
+        """
+        Generate a summary of the given text, yielding it piece by piece.
+
+        Generation runs in a background thread; the streamer skips the
+        prompt, so only newly generated text is yielded.
+
+        Args:
+            text: The text/code to summarize
+            instructions: Specific instructions for this summary
+            system_prompt: Optional system-level prompt
+
+        Yields:
+            Decoded pieces of the summary as they are generated
+        """
+        _, inputs = self._build_inputs(text, instructions, system_prompt)
+        streamer = TextIteratorStreamer(self.tokenizer, skip_prompt=True, skip_special_tokens=True)
+
+        def generate():
+            with torch.no_grad():
+                self.model.generate(**inputs, **self._generation_kwargs(), streamer=streamer)
+
+        thread = Thread(target=generate, daemon=True)
+        thread.start()
+        try:
+            for piece in streamer:
+                if piece:
+                    yield piece
+        finally:
+            thread.join()
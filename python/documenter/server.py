@@ -5,8 +5,12 @@ Provides HTTP endpoints for summarizing code chunks using
 local language models with zero cloud dependencies.
 """
 
+import asyncio
 import os
+from threading import Lock
+from typing import Iterator
 from fastapi import FastAPI, HTTPException
+from fastapi.responses import StreamingResponse
 from pydantic import BaseModel, Field
 import uvicorn
 from model import DocumenterModel
@@ -25,6 +29,10 @@ app = FastAPI(
 # Global model instance (loaded on startup)
 model: DocumenterModel | None = None
 
+# Models requested by name, loaded on first use and kept for later requests
+loaded_models: dict[str, DocumenterModel] = {}
+_load_lock = Lock()
+
 
 @app.on_event("startup")
 async def load_model():
@@ -35,6 +43,7 @@ async def load_model():
 
     try:
         model = DocumenterModel(model_name=MODEL_NAME)
+        loaded_models[MODEL_NAME] = model
         print("[server] Model loaded and ready")
     except Exception as e:
         print(f"[server] ERROR: Failed to load model: {e}")
@@ -46,6 +55,47 @@ class SummarizeRequest(BaseModel):
     text: str = Field(..., description="The code or text to summarize")
     instructions: str | None = Field(None, description="Optional custom instructions")
     system_prompt: str | None = Field(None, description="Optional system prompt override")
+    model: str | None = Field(None, description="Registry model to use instead of the server's default")
+
+
+def resolve_model(name: str | None) -> DocumenterModel:
+    """
+    Pick the model for a request.
+
+    Without a name the server's default model is used. A registry name
+    loads that model on first use and caches it. Loading blocks, so async
+    handlers should call this through `asyncio.to_thread`.
+
+    Raises:
+        HTTPException: 503 if the default model isn't loaded yet, 400 if
+            the name isn't in the registry, 500 if loading it fails
+    """
+    if name is None or name == MODEL_NAME:
+        if model is None:
+            raise HTTPException(
+                status_code=503,
+                detail="Model not loaded. Server may still be initializing."
+            )
+        return model
+
+    if name not in list_available_models():
+        raise HTTPException(
+            status_code=400,
+            detail=f"Unknown model '{name}'. Available models: {', '.join(list_available_models())}"
+        )
+
+    with _load_lock:
+        if name not in loaded_models:
+            print(f"[server] Loading requested model: {name}")
+            try:
+                loaded_models[name] = DocumenterModel(model_name=name)
+            except Exception as e:
+                print(f"[server] ERROR: Failed to load model {name}: {e}")
+                raise HTTPException(
+                    status_code=500,
+                    detail=f"Failed to load model '{name}': {e}"
+                )
+        return loaded_models[name]
 
 
 class SummarizeResponse(BaseModel):
@@ -98,28 +148,25 @@ async def summarize(req: SummarizeRequest):
     Generate a summary of the provided text/code.
 
     Args:
-        req: SummarizeRequest with text and optional instructions
+        req: SummarizeRequest with text, optional instructions and model
 
     Returns:
         SummarizeResponse with the generated summary
 
     Raises:
-        HTTPException: If model is not loaded or generation fails
+        HTTPException: If model is not loaded, unknown, or generation fails
     """
-    if model is None:
-        raise HTTPException(
-            status_code=503,
-            detail="Model not loaded. Server may still be initializing."
-        )
-
     if not req.text or not req.text.strip():
         raise HTTPException(
             status_code=400,
             detail="Text field cannot be empty"
         )
 
+    # Loading a model on first use must not stall the event loop
+    documenter = await asyncio.to_thread(resolve_model, req.model)
+
     try:
-        summary = model.summarize(
+        summary = documenter.summarize(
             text=req.text,
             instructions=req.instructions or "",
             system_prompt=req.system_prompt or ""
@@ -135,6 +182,53 @@ async def summarize(req: SummarizeRequest):
         )
 
 
+def _sse_event(data: str) -> str:
+    """Format one server-sent event; newlines in data become extra data lines."""
+    lines = data.split("\n")
+    return "".join(f"data: {line}\n" for line in lines) + "\n"
+
+
+def _stream_events(pieces: Iterator[str]) -> Iterator[str]:
+    """Wrap generated pieces as SSE events, ending with a [DONE] event."""
+    try:
+        for piece in pieces:
+            yield _sse_event(piece)
+    except Exception as e:
+        # Headers are already sent, so the error can only end the stream
+        print(f"[server] Error during streaming summarization: {e}")
+        return
+    yield _sse_event("[DONE]")
+
+
+@app.post("/summarize/stream")
+def summarize_stream(req: SummarizeRequest):
+    """
+    Generate a summary of the provided text/code as server-sent events.
+
+    Each event's data is one piece of the summary as the model generates
+    it; a final `[DONE]` event marks the end. A stream that ends without
+    `[DONE]` was cut short by an error.
+
+    Raises:
+        HTTPException: If model is not loaded or unknown, or the text is empty
+    """
+    if not req.text or not req.text.strip():
+        raise HTTPException(
+            status_code=400,
+            detail="Text field cannot be empty"
+        )
+
+    documenter = resolve_model(req.model)
+
+    pieces = documenter.summarize_stream(
+        text=req.text,
+        instructions=req.instructions or "",
+        system_prompt=req.system_prompt or ""
+    )
+
+    return StreamingResponse(_stream_events(pieces), media_type="text/event-stream")
+
+
 if __name__ == "__main__":
     print(f"[server] Available models: {', '.join(list_available_models())}")
     print(f"[server] To use a different model, set DOCUMENTER_MODEL environment variable")
@@ -0,0 +1,98 @@
+"""
+Tests for the documenter API that run without loading a real model.
+
+`model.DocumenterModel` is replaced with a stub before the server is
+imported, so transformers and torch aren't needed.
+
+Run with: pytest test_api.py
+"""
+
+import sys
+import types
+
+import pytest
+
+
+class StubModel:
+    """Stands in for DocumenterModel; echoes which model handled a request."""
+
+    # Model names whose loading raises, to simulate a failed download
+    broken: set[str] = set()
+
+    def __init__(self, model_name: str = "qwen3-1.7b"):
+        if model_name in StubModel.broken:
+            raise RuntimeError("weights not found")
+        self.model_name = model_name
+
+    def summarize(self, text: str, instructions: str = "", system_prompt: str = "") -> str:
+        return f"{self.model_name}: {text}"
+
+    def summarize_stream(self, text: str, instructions: str = "", system_prompt: str = ""):
+        yield f"{self.model_name}:"
+        yield f" {text}"
+
+
+stub_module = types.ModuleType("model")
+stub_module.DocumenterModel = StubModel
+sys.modules["model"] = stub_module
+
+from fastapi.testclient import TestClient  # noqa: E402
+
+import server  # noqa: E402
+from registry import list_available_models  # noqa: E402
+
+
+@pytest.fixture
+def client():
+    server.loaded_models.clear()
+    StubModel.broken.clear()
+    with TestClient(server.app) as client:
+        yield client
+
+
+def other_model() -> str:
+    return next(name for name in list_available_models() if name != server.MODEL_NAME)
+
+
+def test_summarize_uses_default_model(client):
+    response = client.post("/summarize", json={"text": "code"})
+    assert response.status_code == 200
+    assert response.json()["summary"] == f"{server.MODEL_NAME}: code"
+
+
+def test_summarize_uses_requested_model(client):
+    name = other_model()
+    response = client.post("/summarize", json={"text": "code", "model": name})
+    assert response.status_code == 200
+    assert response.json()["summary"] == f"{name}: code"
+    assert name in server.loaded_models
+
+
+def test_summarize_rejects_unknown_model(client):
+    response = client.post("/summarize", json={"text": "code", "model": "no-such-model"})
+    assert response.status_code == 400
+    assert "no-such-model" in response.json()["detail"]
+
+
+def test_summarize_reports_model_load_failure(client):
+    name = other_model()
+    StubModel.broken.add(name)
+
+    response = client.post("/summarize", json={"text": "code", "model": name})
+
+    assert response.status_code == 500
+    assert response.json()["detail"] == f"Failed to load model '{name}': weights not found"
+    assert name not in server.loaded_models
+
+
+def test_stream_uses_requested_model(client):
+    name = other_model()
+    response = client.post("/summarize/stream", json={"text": "code", "model": name})
+    assert response.status_code == 200
+    assert response.headers["content-type"].startswith("text/event-stream")
+    assert response.text == f"data: {name}:\n\ndata:  code\n\ndata: [DONE]\n\n"
+
+
+def test_stream_rejects_unknown_model(client):
+    response = client.post("/summarize/stream", json={"text": "code", "model": "no-such-model"})
+    assert response.status_code == 400
@@ -61,6 +61,20 @@ def calculate_fibonacci(n):
         print(f"Error: {response.text}\n")
         return False
 
+def test_unknown_model():
+    """Test that an unknown model name is rejected, not ignored."""
+    print("Testing /summarize with an unknown model...")
+
+    payload = {
+        "text": "def f(): pass",
+        "model": "no-such-model"
+    }
+    response = requests.post(f"{BASE_URL}/summarize", json=payload, timeout=60)
+    print(f"Status: {response.status_code}")
+    print(f"Response: {response.text}\n")
+
+    return response.status_code == 400 and "no-such-model" in response.json()["detail"]
+
 def main():
     """Run all tests."""
     print("=" * 60)
@@ -71,6 +85,7 @@ def main():
         ("Health Check", test_health),
         ("Models List", test_models),
         ("Summarization", test_summarize),
+        ("Unknown Model", test_unknown_model),
     ]
 
     results = []
@@ -0,0 +1,39 @@
+use super::{Chunk, ChunkId};
+use std::collections::HashMap;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Drop chunks whose text matches an earlier chunk's, ignoring whitespace
+/// differences
+///
+/// Runs of whitespace are collapsed and leading/trailing whitespace is
+/// trimmed before comparing, so reindented copies of a license header
+/// count as duplicates. Kept chunks keep their ids and order. The mapping
+/// pairs each dropped chunk's id with the id of the kept chunk it
+/// duplicates, so its embedding can be restored from the kept one.
+pub fn dedupe_chunks(chunks: Vec<Chunk>) -> (Vec<Chunk>, Vec<(ChunkId, ChunkId)>) {
+    // Hash of normalized text -> (normalized text, id) of the kept chunks
+    let mut seen: HashMap<u64, Vec<(String, ChunkId)>> = HashMap::new();
+    let mut kept = Vec::with_capacity(chunks.len());
+    let mut duplicates = Vec::new();
+
+    for chunk in chunks {
+        let normalized = normalize_whitespace(&chunk.text);
+        let candidates = seen.entry(xxh3_64(normalized.as_bytes())).or_default();
+
+        // Compare the text too, so a hash collision can't drop a chunk
+        match candidates.iter().find(|(text, _)| *text == normalized) {
+            Some(&(_, original)) => duplicates.push((chunk.id, original)),
+            None => {
+                candidates.push((normalized, chunk.id));
+                kept.push(chunk);
+            }
+        }
+    }
+
+    (kept, duplicates)
+}
+
+/// Collapse whitespace runs to a single space and trim the ends
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,61 @@
+/// Estimates how many model tokens a piece of text will consume
+///
+/// The chunker uses this to keep chunks within the embedding model's
+/// context, so estimates should err on the high side.
+pub trait TokenEstimator: Send + Sync {
+    /// Estimated token count for `text` (at least 1)
+    fn estimate(&self, text: &str) -> usize;
+}
+
+/// Character-count heuristic: 1 token ≈ 4 bytes
+///
+/// Cheap and dependency-free, but undercounts code with many short symbols
+/// and non-Latin text such as CJK.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicEstimator;
+
+impl TokenEstimator for HeuristicEstimator {
+    fn estimate(&self, text: &str) -> usize {
+        // Average token is ~4 characters for English text
+        // Add 1 to avoid zero-token estimates for very short text
+        (text.len() / 4).max(1)
+    }
+}
+
+/// Token estimate used by the chunker by default (`HeuristicEstimator`)
+///
+/// Handy for pre-sizing batches or showing token counts without picking an
+/// estimator.
+pub fn estimate_tokens(text: &str) -> usize {
+    HeuristicEstimator.estimate(text)
+}
+
+/// Exact token counts from a BPE tokenizer via `tiktoken-rs`
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenEstimator {
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenEstimator {
+    /// Tokenizer used by OpenAI's `text-embedding-3-*` and GPT-4 models
+    pub fn cl100k_base() -> anyhow::Result<Self> {
+        Ok(Self {
+            bpe: tiktoken_rs::cl100k_base()?,
+        })
+    }
+
+    /// Tokenizer for a named OpenAI model (e.g., "gpt-4o")
+    pub fn for_model(model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            bpe: tiktoken_rs::get_bpe_from_model(model)?,
+        })
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl TokenEstimator for TiktokenEstimator {
+    fn estimate(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len().max(1)
+    }
+}
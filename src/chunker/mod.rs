@@ -1,9 +1,19 @@
+mod dedupe;
+mod estimator;
 mod splitter;
 
 #[cfg(test)]
 mod tests;
 
-pub use splitter::{Chunk, ChunkMetadata, chunk_semantic_units};
+pub use dedupe::dedupe_chunks;
+#[cfg(feature = "tiktoken")]
+pub use estimator::TiktokenEstimator;
+pub use estimator::{HeuristicEstimator, TokenEstimator, estimate_tokens};
+pub use splitter::{
+    Chunk, ChunkMetadata, ChunkOptions, chunk_parse_result, chunk_parse_result_opts,
+    chunk_parse_results, chunk_parse_results_opts, chunk_semantic_units,
+    chunk_semantic_units_heuristic, chunk_semantic_units_opts,
+};
 
 /// Unique identifier for a chunk
 pub type ChunkId = u32;
@@ -1,8 +1,13 @@
-use crate::parser::{SemanticKind, SemanticUnit};
+use super::{ChunkId, DEFAULT_MAX_TOKENS, HeuristicEstimator, MIN_MERGE_THRESHOLD, TokenEstimator};
+use crate::parser::{ParseResult, SemanticKind, SemanticUnit};
+use serde::Serialize;
 
 /// A chunk of text ready for embedding/indexing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Chunk {
+    /// Identifier unique across a run (0 until assigned by
+    /// `chunk_parse_results`)
+    pub id: ChunkId,
     /// The text content of this chunk
     pub text: String,
     /// Metadata about the chunk
@@ -10,7 +15,7 @@ pub struct Chunk {
 }
 
 /// Metadata for a chunk
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ChunkMetadata {
     /// Estimated token count
     pub token_count: usize,
@@ -22,6 +27,37 @@ pub struct ChunkMetadata {
     pub kinds: Vec<SemanticKind>,
     /// Number of semantic units merged into this chunk
     pub unit_count: usize,
+    /// Virtual path of the source file (empty if unknown)
+    pub file_path: String,
+    /// 1-based first line of the chunk in the source (0 if unknown)
+    pub start_line: u32,
+    /// 1-based last line of the chunk in the source (0 if unknown)
+    pub end_line: u32,
+}
+
+/// Settings for `chunk_semantic_units_opts`
+#[derive(Clone)]
+pub struct ChunkOptions<'a> {
+    /// Target maximum tokens per chunk
+    pub max_tokens: usize,
+    /// Text inserted between units merged into one chunk
+    pub join_separator: String,
+    /// Tokens of trailing units to repeat at the start of the next chunk
+    /// (0 disables overlap)
+    pub overlap: usize,
+    /// Token counter used for all size decisions
+    pub estimator: &'a dyn TokenEstimator,
+}
+
+impl Default for ChunkOptions<'_> {
+    fn default() -> Self {
+        Self {
+            max_tokens: DEFAULT_MAX_TOKENS,
+            join_separator: "\n\n".to_string(),
+            overlap: 0,
+            estimator: &HeuristicEstimator,
+        }
+    }
 }
 
 /// Chunk semantic units according to the rules:
@@ -30,61 +66,246 @@ pub struct ChunkMetadata {
 /// - Aim for <2k tokens per chunk (configurable)
 /// - Preserve unit boundaries if possible
 /// - Fallback to newline splitting for oversized units
-pub fn chunk_semantic_units(units: Vec<SemanticUnit>, max_tokens: usize) -> Vec<Chunk> {
+/// - Fold chunks below `MIN_MERGE_THRESHOLD` into their predecessor
+///
+/// Token counts come from `estimator`.
+pub fn chunk_semantic_units(
+    units: Vec<SemanticUnit>,
+    max_tokens: usize,
+    estimator: &dyn TokenEstimator,
+) -> Vec<Chunk> {
+    chunk_semantic_units_opts(
+        units,
+        &ChunkOptions {
+            max_tokens,
+            estimator,
+            ..Default::default()
+        },
+    )
+}
+
+/// Chunk semantic units with explicit options
+///
+/// With a nonzero `overlap`, whole units from the end of each chunk are
+/// repeated at the start of the next one, up to `overlap` tokens. Units
+/// split by line are never overlapped.
+pub fn chunk_semantic_units_opts(units: Vec<SemanticUnit>, options: &ChunkOptions) -> Vec<Chunk> {
     if units.is_empty() {
         return vec![];
     }
 
+    let max_tokens = options.max_tokens;
+    let estimator = options.estimator;
+    let separator = options.join_separator.as_str();
+
     let mut chunks = Vec::new();
-    let mut current_batch: Vec<SemanticUnit> = Vec::new();
+    let mut current_batch: Vec<(SemanticUnit, usize)> = Vec::new();
     let mut current_tokens = 0;
+    // Leading units of the batch repeated from the previous chunk
+    let mut carried = 0;
 
     for unit in units {
-        let unit_tokens = estimate_tokens(&unit.text);
+        let unit_tokens = estimator.estimate(&unit.text);
 
         // If this unit alone exceeds max_tokens, split it separately
         if unit_tokens > max_tokens {
             // Flush current batch first
-            if !current_batch.is_empty() {
-                chunks.push(create_chunk_from_units(current_batch, current_tokens));
-                current_batch = Vec::new();
-                current_tokens = 0;
+            if current_batch.len() > carried {
+                chunks.push(create_chunk_from_units(
+                    current_batch,
+                    current_tokens,
+                    separator,
+                ));
             }
+            current_batch = Vec::new();
+            current_tokens = 0;
+            carried = 0;
 
             // Split the huge unit
-            chunks.extend(split_large_unit(unit, max_tokens));
+            chunks.extend(split_large_unit(unit, max_tokens, estimator));
             continue;
         }
 
         // Check if adding this unit would exceed the limit
-        if current_tokens + unit_tokens > max_tokens && !current_batch.is_empty() {
-            // Flush current batch
-            chunks.push(create_chunk_from_units(current_batch, current_tokens));
-            current_batch = Vec::new();
+        if current_tokens + unit_tokens > max_tokens && current_batch.len() > carried {
+            // Flush current batch, keeping its tail for overlap
+            let tail = overlap_tail(&current_batch, options.overlap);
+            chunks.push(create_chunk_from_units(
+                current_batch,
+                current_tokens,
+                separator,
+            ));
+            current_tokens = tail.iter().map(|(_, tokens)| tokens).sum();
+            carried = tail.len();
+            current_batch = tail;
+        }
+
+        // Drop overlap that leaves no room for the new unit
+        if current_tokens + unit_tokens > max_tokens {
+            current_batch.clear();
             current_tokens = 0;
+            carried = 0;
         }
 
         // Add unit to current batch
         current_tokens += unit_tokens;
-        current_batch.push(unit);
+        current_batch.push((unit, unit_tokens));
     }
 
     // Flush remaining batch
-    if !current_batch.is_empty() {
-        chunks.push(create_chunk_from_units(current_batch, current_tokens));
+    if current_batch.len() > carried {
+        chunks.push(create_chunk_from_units(
+            current_batch,
+            current_tokens,
+            separator,
+        ));
+    }
+
+    // Overlapping chunks already share text, so merging would duplicate it
+    if options.overlap > 0 {
+        return chunks;
+    }
+    merge_small_chunks(chunks, max_tokens, separator)
+}
+
+/// Trailing units of a batch that fit within `overlap` tokens
+fn overlap_tail(batch: &[(SemanticUnit, usize)], overlap: usize) -> Vec<(SemanticUnit, usize)> {
+    let mut tokens = 0;
+    let count = batch
+        .iter()
+        .rev()
+        .take_while(|(_, unit_tokens)| {
+            tokens += unit_tokens;
+            tokens <= overlap
+        })
+        .count();
+
+    batch[batch.len() - count..].to_vec()
+}
+
+/// Chunk a file's semantic units, tagging each chunk with the file's path
+/// and the line range it covers
+pub fn chunk_parse_result(
+    result: ParseResult,
+    max_tokens: usize,
+    estimator: &dyn TokenEstimator,
+) -> Vec<Chunk> {
+    chunk_parse_result_opts(
+        result,
+        &ChunkOptions {
+            max_tokens,
+            estimator,
+            ..Default::default()
+        },
+    )
+}
+
+/// `chunk_parse_result` with full control over chunking behavior
+pub fn chunk_parse_result_opts(mut result: ParseResult, options: &ChunkOptions) -> Vec<Chunk> {
+    let units = std::mem::take(&mut result.semantic_units);
+    let mut chunks = chunk_semantic_units_opts(units, options);
+
+    for chunk in &mut chunks {
+        let meta = &mut chunk.metadata;
+        // end_offset is exclusive; the last byte decides the last line
+        let last_byte = meta.end_offset.saturating_sub(1).max(meta.start_offset);
+        meta.file_path = result.metadata.path.clone();
+        meta.start_line = result.line_at(meta.start_offset) as u32;
+        meta.end_line = result.line_at(last_byte) as u32;
     }
 
     chunks
 }
 
+/// Chunk many files, numbering chunks in input order
+///
+/// Produces the same chunks as calling `chunk_parse_result` on each file in
+/// turn, with `id`s assigned sequentially from 0. With the `rayon` feature
+/// (on by default) files are chunked in parallel.
+pub fn chunk_parse_results(
+    results: Vec<ParseResult>,
+    max_tokens: usize,
+    estimator: &dyn TokenEstimator,
+) -> Vec<Chunk> {
+    chunk_parse_results_opts(
+        results,
+        &ChunkOptions {
+            max_tokens,
+            estimator,
+            ..Default::default()
+        },
+    )
+}
+
+/// `chunk_parse_results` with full control over chunking behavior
+pub fn chunk_parse_results_opts(results: Vec<ParseResult>, options: &ChunkOptions) -> Vec<Chunk> {
+    let chunk = |result| chunk_parse_result_opts(result, options);
+
+    #[cfg(feature = "rayon")]
+    let per_file: Vec<Vec<Chunk>> = {
+        use rayon::prelude::*;
+        results.into_par_iter().map(chunk).collect()
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    let per_file: Vec<Vec<Chunk>> = results.into_iter().map(chunk).collect();
+
+    let mut chunks: Vec<Chunk> = per_file.into_iter().flatten().collect();
+    for (id, chunk) in chunks.iter_mut().enumerate() {
+        chunk.id = id as ChunkId;
+    }
+    chunks
+}
+
+/// Chunk semantic units using the character-count heuristic
+pub fn chunk_semantic_units_heuristic(units: Vec<SemanticUnit>, max_tokens: usize) -> Vec<Chunk> {
+    chunk_semantic_units(units, max_tokens, &HeuristicEstimator)
+}
+
+/// Merge chunks below `MIN_MERGE_THRESHOLD` into the previous chunk when
+/// the combined size still fits in `max_tokens`
+///
+/// Catches small leftovers such as the tail of a split unit or a short
+/// unit that follows one.
+fn merge_small_chunks(chunks: Vec<Chunk>, max_tokens: usize, separator: &str) -> Vec<Chunk> {
+    let mut merged: Vec<Chunk> = Vec::with_capacity(chunks.len());
+
+    for chunk in chunks {
+        if let Some(prev) = merged.last_mut()
+            && chunk.metadata.token_count < MIN_MERGE_THRESHOLD
+            && prev.metadata.token_count + chunk.metadata.token_count <= max_tokens
+        {
+            prev.text.push_str(separator);
+            prev.text.push_str(&chunk.text);
+            prev.metadata.token_count += chunk.metadata.token_count;
+            prev.metadata.end_offset = chunk.metadata.end_offset;
+            prev.metadata.unit_count += chunk.metadata.unit_count;
+            for kind in chunk.metadata.kinds {
+                if !prev.metadata.kinds.contains(&kind) {
+                    prev.metadata.kinds.push(kind);
+                }
+            }
+            continue;
+        }
+
+        merged.push(chunk);
+    }
+
+    merged
+}
+
 /// Create a chunk from a batch of semantic units
-fn create_chunk_from_units(units: Vec<SemanticUnit>, token_count: usize) -> Chunk {
-    let start_offset = units.first().map(|u| u.start_offset).unwrap_or(0);
-    let end_offset = units.last().map(|u| u.end_offset).unwrap_or(0);
+fn create_chunk_from_units(
+    units: Vec<(SemanticUnit, usize)>,
+    token_count: usize,
+    separator: &str,
+) -> Chunk {
+    let start_offset = units.first().map(|(u, _)| u.start_offset).unwrap_or(0);
+    let end_offset = units.last().map(|(u, _)| u.end_offset).unwrap_or(0);
 
     let kinds: Vec<SemanticKind> = units
         .iter()
-        .map(|u| u.kind)
+        .map(|(u, _)| u.kind)
         .collect::<std::collections::HashSet<_>>()
         .into_iter()
         .collect();
@@ -92,11 +313,12 @@ fn create_chunk_from_units(units: Vec<SemanticUnit>, token_count: usize) -> Chun
     let unit_count = units.len();
     let text = units
         .into_iter()
-        .map(|u| u.text)
+        .map(|(u, _)| u.text)
         .collect::<Vec<_>>()
-        .join("\n\n");
+        .join(separator);
 
     Chunk {
+        id: 0,
         text,
         metadata: ChunkMetadata {
             token_count,
@@ -104,20 +326,34 @@ fn create_chunk_from_units(units: Vec<SemanticUnit>, token_count: usize) -> Chun
             end_offset,
             kinds,
             unit_count,
+            file_path: String::new(),
+            start_line: 0,
+            end_line: 0,
         },
     }
 }
 
 /// Split a large semantic unit that exceeds max_tokens
 /// Falls back to newline-based splitting
-fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
-    let lines: Vec<&str> = unit.text.lines().collect();
+fn split_large_unit(
+    unit: SemanticUnit,
+    max_tokens: usize,
+    estimator: &dyn TokenEstimator,
+) -> Vec<Chunk> {
+    if unit.kind == SemanticKind::Function {
+        return split_function_unit(unit, max_tokens, estimator);
+    }
+
     let mut chunks = Vec::new();
     let mut current_lines = Vec::new();
     let mut current_tokens = 0;
+    // Start of the pending lines in the source
+    let mut current_start = unit.start_offset;
 
-    for line in lines {
-        let line_tokens = estimate_tokens(line);
+    for line in unit.text.lines() {
+        let line_tokens = estimator.estimate(line);
+        // Lines borrow from unit.text, so the pointer gives their offset
+        let line_start = unit.start_offset + (line.as_ptr() as usize - unit.text.as_ptr() as usize);
 
         // If a single line is too big, we have to include it anyway
         if line_tokens > max_tokens {
@@ -128,7 +364,7 @@ fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
                     text,
                     current_tokens,
                     unit.kind,
-                    unit.start_offset,
+                    current_start,
                 ));
                 current_lines.clear();
                 current_tokens = 0;
@@ -139,7 +375,7 @@ fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
                 line.to_string(),
                 line_tokens,
                 unit.kind,
-                unit.start_offset,
+                line_start,
             ));
             continue;
         }
@@ -151,12 +387,15 @@ fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
                 text,
                 current_tokens,
                 unit.kind,
-                unit.start_offset,
+                current_start,
             ));
             current_lines.clear();
             current_tokens = 0;
         }
 
+        if current_lines.is_empty() {
+            current_start = line_start;
+        }
         current_tokens += line_tokens;
         current_lines.push(line);
     }
@@ -168,13 +407,85 @@ fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
             text,
             current_tokens,
             unit.kind,
-            unit.start_offset,
+            current_start,
         ));
     }
 
     // If we somehow ended up with no chunks, create one from the whole unit
     if chunks.is_empty() {
-        let token_count = estimate_tokens(&unit.text);
+        let token_count = estimator.estimate(&unit.text);
+        chunks.push(create_single_chunk(
+            unit.text,
+            token_count,
+            unit.kind,
+            unit.start_offset,
+        ));
+    }
+
+    chunks
+}
+
+/// A line of an oversized function with the data needed to pick split points
+struct CodeLine<'a> {
+    text: &'a str,
+    /// Byte offset in the source
+    start: usize,
+    tokens: usize,
+    /// Brace nesting after this line, relative to the start of the unit
+    depth: i64,
+}
+
+/// Split an oversized function, preferring to cut where braces are balanced
+/// (the shallowest nesting available) and at blank lines
+///
+/// Each cut is chosen among the lines pending when the limit is hit,
+/// ignoring cuts that would leave a chunk under half of `max_tokens` unless
+/// nothing else is available. Code without braces (e.g., Python) is cut at
+/// blank lines where possible.
+fn split_function_unit(
+    unit: SemanticUnit,
+    max_tokens: usize,
+    estimator: &dyn TokenEstimator,
+) -> Vec<Chunk> {
+    let mut depth = 0;
+    let lines: Vec<CodeLine> = unit
+        .text
+        .lines()
+        .map(|text| {
+            depth += brace_delta(text);
+            CodeLine {
+                text,
+                // Lines borrow from unit.text, so the pointer gives their offset
+                start: unit.start_offset + (text.as_ptr() as usize - unit.text.as_ptr() as usize),
+                tokens: estimator.estimate(text),
+                depth,
+            }
+        })
+        .collect();
+
+    let mut chunks = Vec::new();
+    let mut pending = 0..0;
+    let mut pending_tokens = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        // Cut until the new line fits (or nothing is left to cut)
+        while pending_tokens + line.tokens > max_tokens && !pending.is_empty() {
+            let cut = best_cut(&lines[pending.clone()], max_tokens) + pending.start;
+            chunks.push(code_lines_chunk(&lines[pending.start..cut], unit.kind));
+            pending.start = cut;
+            pending_tokens = lines[pending.clone()].iter().map(|l| l.tokens).sum();
+        }
+
+        pending.end = i + 1;
+        pending_tokens += line.tokens;
+    }
+
+    if !pending.is_empty() {
+        chunks.push(code_lines_chunk(&lines[pending], unit.kind));
+    }
+
+    if chunks.is_empty() {
+        let token_count = estimator.estimate(&unit.text);
         chunks.push(create_single_chunk(
             unit.text,
             token_count,
@@ -186,6 +497,59 @@ fn split_large_unit(unit: SemanticUnit, max_tokens: usize) -> Vec<Chunk> {
     chunks
 }
 
+/// Number of lines to take from `lines` for the next chunk (at least 1)
+fn best_cut(lines: &[CodeLine], max_tokens: usize) -> usize {
+    let mut candidates = Vec::with_capacity(lines.len());
+    let mut tokens = 0;
+    for (i, line) in lines.iter().enumerate() {
+        tokens += line.tokens;
+        let blank_follows = lines.get(i + 1).is_some_and(|l| l.text.trim().is_empty());
+        let at_blank = line.text.trim().is_empty() || blank_follows;
+        candidates.push((i + 1, tokens, line.depth, at_blank));
+    }
+
+    let large_enough = candidates
+        .iter()
+        .any(|&(_, tokens, _, _)| tokens >= max_tokens / 2);
+
+    candidates
+        .into_iter()
+        .filter(|&(_, tokens, _, _)| !large_enough || tokens >= max_tokens / 2)
+        // Shallowest depth, then blank lines, then the largest chunk
+        .max_by_key(|&(len, _, depth, at_blank)| (-depth, at_blank, len))
+        .map_or(1, |(len, _, _, _)| len)
+}
+
+/// Change in `{}` nesting over a line, ignoring string literals and `//`
+/// comments
+fn brace_delta(line: &str) -> i64 {
+    let mut delta = 0;
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '/' if !in_string && chars.peek() == Some(&'/') => break,
+            '{' if !in_string => delta += 1,
+            '}' if !in_string => delta -= 1,
+            _ => {}
+        }
+    }
+
+    delta
+}
+
+/// Create a chunk from consecutive lines of a split function
+fn code_lines_chunk(lines: &[CodeLine], kind: SemanticKind) -> Chunk {
+    let text = lines.iter().map(|l| l.text).collect::<Vec<_>>().join("\n");
+    let tokens = lines.iter().map(|l| l.tokens).sum();
+    create_single_chunk(text, tokens, kind, lines[0].start)
+}
+
 /// Create a single chunk with the given properties
 fn create_single_chunk(
     text: String,
@@ -196,6 +560,7 @@ fn create_single_chunk(
     let end_offset = start_offset + text.len();
 
     Chunk {
+        id: 0,
         text,
         metadata: ChunkMetadata {
             token_count,
@@ -203,19 +568,13 @@ fn create_single_chunk(
             end_offset,
             kinds: vec![kind],
             unit_count: 1,
+            file_path: String::new(),
+            start_line: 0,
+            end_line: 0,
         },
     }
 }
 
-/// Estimate token count for a piece of text
-/// Uses a simple heuristic: 1 token H 4 characters
-/// This is a rough approximation suitable for most text
-fn estimate_tokens(text: &str) -> usize {
-    // Average token is ~4 characters for English text
-    // Add 1 to avoid zero-token estimates for very short text
-    (text.len() / 4).max(1)
-}
-
 #[cfg(test)]
 mod chunk_tests {
     use super::*;
@@ -231,14 +590,14 @@ mod chunk_tests {
 
     #[test]
     fn test_empty_units() {
-        let chunks = chunk_semantic_units(vec![], 2000);
+        let chunks = chunk_semantic_units_heuristic(vec![], 2000);
         assert_eq!(chunks.len(), 0);
     }
 
     #[test]
     fn test_single_small_unit() {
         let units = vec![make_unit("fn main() {}", SemanticKind::Function)];
-        let chunks = chunk_semantic_units(units, 2000);
+        let chunks = chunk_semantic_units_heuristic(units, 2000);
 
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].metadata.unit_count, 1);
@@ -252,7 +611,7 @@ mod chunk_tests {
             make_unit("fn baz() {}", SemanticKind::Function),
         ];
 
-        let chunks = chunk_semantic_units(units, 2000);
+        let chunks = chunk_semantic_units_heuristic(units, 2000);
 
         // All should merge into one chunk since they're small
         assert_eq!(chunks.len(), 1);
@@ -268,7 +627,7 @@ mod chunk_tests {
             make_unit("fn small() {}", SemanticKind::Function),
         ];
 
-        let chunks = chunk_semantic_units(units, 2000);
+        let chunks = chunk_semantic_units_heuristic(units, 2000);
 
         // Should split into 2 chunks at the unit boundary
         assert_eq!(chunks.len(), 2);
@@ -281,7 +640,7 @@ mod chunk_tests {
         let huge_text = lines.join("\n");
         let units = vec![make_unit(&huge_text, SemanticKind::Blob)];
 
-        let chunks = chunk_semantic_units(units, 2000);
+        let chunks = chunk_semantic_units_heuristic(units, 2000);
 
         // Should split into multiple chunks
         assert!(chunks.len() > 1);
@@ -289,9 +648,9 @@ mod chunk_tests {
 
     #[test]
     fn test_token_estimation() {
-        assert_eq!(estimate_tokens(""), 1); // Minimum of 1
-        assert_eq!(estimate_tokens("test"), 1); // 4 chars = 1 token
-        assert_eq!(estimate_tokens("test test"), 2); // 9 chars H 2 tokens
-        assert_eq!(estimate_tokens(&"x".repeat(8000)), 2000); // 8000 chars = 2000 tokens
+        assert_eq!(HeuristicEstimator.estimate(""), 1); // Minimum of 1
+        assert_eq!(HeuristicEstimator.estimate("test"), 1); // 4 chars = 1 token
+        assert_eq!(HeuristicEstimator.estimate("test test"), 2); // 9 chars H 2 tokens
+        assert_eq!(HeuristicEstimator.estimate(&"x".repeat(8000)), 2000); // 8000 chars = 2000 tokens
     }
 }
@@ -1,5 +1,5 @@
 use super::*;
-use crate::parser::{SemanticKind, SemanticUnit};
+use crate::parser::{Parser, RustParser, SemanticKind, SemanticUnit};
 
 fn make_test_unit(text: &str, kind: SemanticKind, start: usize) -> SemanticUnit {
     SemanticUnit {
@@ -18,7 +18,7 @@ fn test_chunker_preserves_boundaries() {
         make_test_unit("// Function C\nfn c() {}", SemanticKind::Function, 200),
     ];
 
-    let chunks = chunk_semantic_units(units, DEFAULT_MAX_TOKENS);
+    let chunks = chunk_semantic_units_heuristic(units, DEFAULT_MAX_TOKENS);
 
     // Should merge all small functions into one chunk
     assert_eq!(chunks.len(), 1);
@@ -34,7 +34,7 @@ fn test_chunker_splits_large_content() {
     let large_content = lines.join("\n");
     let units = vec![make_test_unit(&large_content, SemanticKind::Blob, 0)];
 
-    let chunks = chunk_semantic_units(units, DEFAULT_MAX_TOKENS);
+    let chunks = chunk_semantic_units_heuristic(units, DEFAULT_MAX_TOKENS);
 
     // Should split into multiple chunks
     assert!(chunks.len() >= 2, "Large content should be split");
@@ -57,7 +57,7 @@ fn test_chunker_respects_max_tokens() {
         make_test_unit(&medium_text, SemanticKind::Function, 6000),
     ];
 
-    let chunks = chunk_semantic_units(units, DEFAULT_MAX_TOKENS);
+    let chunks = chunk_semantic_units_heuristic(units, DEFAULT_MAX_TOKENS);
 
     // Should create 2 separate chunks since combining would exceed limit
     assert_eq!(chunks.len(), 2);
@@ -72,7 +72,7 @@ fn test_chunker_tracks_metadata() {
         make_test_unit("struct Bar {}", SemanticKind::Class, 50),
     ];
 
-    let chunks = chunk_semantic_units(units.clone(), DEFAULT_MAX_TOKENS);
+    let chunks = chunk_semantic_units_heuristic(units.clone(), DEFAULT_MAX_TOKENS);
 
     assert_eq!(chunks.len(), 1);
     assert_eq!(chunks[0].metadata.unit_count, 2);
@@ -89,7 +89,7 @@ fn test_chunker_newline_fallback() {
 
     let units = vec![make_test_unit(&large_text, SemanticKind::Comment, 0)];
 
-    let chunks = chunk_semantic_units(units, 500); // Lower limit to force splitting
+    let chunks = chunk_semantic_units_heuristic(units, 500); // Lower limit to force splitting
 
     // Should split by newlines
     assert!(chunks.len() > 1, "Should split large unit by newlines");
@@ -99,3 +99,302 @@ fn test_chunker_newline_fallback() {
         assert!(chunk.metadata.kinds.contains(&SemanticKind::Comment));
     }
 }
+
+#[test]
+fn test_chunker_merges_small_trailing_chunk() {
+    // 3000 tokens over three lines: splits into ~2000 + ~1000 token chunks
+    let big_text = vec!["x".repeat(4000); 3].join("\n");
+    let small_text = "y".repeat(40); // 10 tokens
+    let units = vec![
+        make_test_unit(&big_text, SemanticKind::Function, 0),
+        make_test_unit(&small_text, SemanticKind::Comment, big_text.len()),
+    ];
+
+    let chunks = chunk_semantic_units_heuristic(units, DEFAULT_MAX_TOKENS);
+
+    // The 10-token unit is folded into the tail of the split unit
+    assert_eq!(chunks.len(), 2);
+    let last = &chunks[1];
+    assert!(last.text.ends_with(&small_text));
+    assert_eq!(last.metadata.token_count, 1010);
+    assert_eq!(last.metadata.unit_count, 2);
+    assert_eq!(last.metadata.end_offset, big_text.len() + small_text.len());
+    assert!(last.metadata.kinds.contains(&SemanticKind::Function));
+    assert!(last.metadata.kinds.contains(&SemanticKind::Comment));
+}
+
+#[test]
+fn test_chunk_parse_result_records_file_and_lines() {
+    let source = "fn alpha() {\n    one();\n}\n\nfn beta() {\n    two();\n}\n";
+    let result = RustParser.parse("src/lib.rs", source.as_bytes());
+
+    // Each function is ~6 tokens, so a limit of 8 keeps them apart
+    let chunks = chunk_parse_result(result, 8, &HeuristicEstimator);
+
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.iter().all(|c| c.metadata.file_path == "src/lib.rs"));
+    assert_eq!(
+        (chunks[0].metadata.start_line, chunks[0].metadata.end_line),
+        (1, 3)
+    );
+    assert_eq!(
+        (chunks[1].metadata.start_line, chunks[1].metadata.end_line),
+        (5, 7)
+    );
+}
+
+#[test]
+fn test_split_unit_chunks_have_own_offsets() {
+    let text = vec!["x".repeat(40); 4].join("\n"); // 10 tokens per line
+    let units = vec![make_test_unit(&text, SemanticKind::Blob, 100)];
+
+    let chunks = chunk_semantic_units_heuristic(units, 20);
+
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].metadata.start_offset, 100);
+    assert_eq!(chunks[1].metadata.start_offset, 100 + 82);
+    assert_eq!(chunks[1].metadata.end_offset, 100 + text.len());
+}
+
+#[test]
+fn test_chunker_default_separator() {
+    let units = vec![
+        make_test_unit("fn a() {}", SemanticKind::Function, 0),
+        make_test_unit("fn b() {}", SemanticKind::Function, 10),
+    ];
+
+    let chunks = chunk_semantic_units_opts(units, &ChunkOptions::default());
+
+    assert_eq!(chunks[0].text, "fn a() {}\n\nfn b() {}");
+}
+
+#[test]
+fn test_chunker_custom_separator() {
+    let units = vec![
+        make_test_unit("fn a() {}", SemanticKind::Function, 0),
+        make_test_unit("fn b() {}", SemanticKind::Function, 10),
+    ];
+    let options = ChunkOptions {
+        join_separator: "\n".to_string(),
+        ..Default::default()
+    };
+
+    let chunks = chunk_semantic_units_opts(units, &options);
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].text, "fn a() {}\nfn b() {}");
+}
+
+#[test]
+fn test_chunker_overlap_repeats_trailing_units() {
+    // 10 tokens each; 25-token chunks hold two units
+    let units: Vec<_> = ["a", "b", "c", "d"]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| make_test_unit(&s.repeat(40), SemanticKind::Function, i * 40))
+        .collect();
+    let options = ChunkOptions {
+        max_tokens: 25,
+        overlap: 10,
+        ..Default::default()
+    };
+
+    let chunks = chunk_semantic_units_opts(units, &options);
+
+    // [a b] [b c] [c d]
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks[1].text.starts_with(&"b".repeat(40)));
+    assert!(chunks[2].text.starts_with(&"c".repeat(40)));
+    assert_eq!(chunks[1].metadata.start_offset, 40);
+    assert!(chunks.iter().all(|c| c.metadata.token_count <= 25));
+}
+
+#[test]
+fn test_oversized_function_splits_at_balanced_braces() {
+    let mut source = String::from("fn process(items: &[Item]) {\n");
+    for i in 0..4 {
+        source.push_str(&format!(
+            "    for item in items.iter().filter(|x| x.group == {i}) {{\n"
+        ));
+        source.push_str("        if item.is_ready_for_processing_now() {\n");
+        source.push_str("            handle_the_item_with_care(item, &mut state);\n");
+        source.push_str("            record_progress_for_item(item, &mut stats);\n");
+        source.push_str("        }\n");
+        source.push_str("    }\n");
+    }
+    source.push_str("}\n");
+    let units = vec![make_test_unit(&source, SemanticKind::Function, 0)];
+
+    // Each loop is ~50 tokens, so a chunk fits two of them but not three
+    let chunks = chunk_semantic_units_heuristic(units, 120);
+
+    assert!(chunks.len() > 1);
+    let mut depth = 0i64;
+    for chunk in &chunks[..chunks.len() - 1] {
+        depth += chunk.text.matches('{').count() as i64;
+        depth -= chunk.text.matches('}').count() as i64;
+        // Every cut lands at the function body level, between loops
+        assert_eq!(depth, 1, "cut inside a nested block:\n{}", chunk.text);
+    }
+}
+
+#[test]
+fn test_oversized_function_prefers_blank_lines() {
+    // Brace-free code (as in Python) falls back to blank lines
+    let body = "    value = compute_something_interesting(value)\n".repeat(4);
+    let source = format!("def run(value):\n{body}\n{body}\n{body}    return value\n");
+    let units = vec![make_test_unit(&source, SemanticKind::Function, 0)];
+
+    let chunks = chunk_semantic_units_heuristic(units, 60);
+
+    assert_eq!(chunks.len(), 3);
+    for chunk in &chunks[..2] {
+        // Cut right after the blank line separating the groups
+        assert!(
+            chunk.text.ends_with("(value)\n"),
+            "unexpected cut:\n{}",
+            chunk.text
+        );
+    }
+}
+
+/// Counts whitespace-separated words, to exercise custom estimators
+struct WordEstimator;
+
+impl TokenEstimator for WordEstimator {
+    fn estimate(&self, text: &str) -> usize {
+        text.split_whitespace().count().max(1)
+    }
+}
+
+fn text_chunk(id: ChunkId, text: &str) -> Chunk {
+    Chunk {
+        id,
+        text: text.to_string(),
+        metadata: ChunkMetadata {
+            token_count: estimate_tokens(text),
+            start_offset: 0,
+            end_offset: text.len(),
+            kinds: vec![SemanticKind::Unknown],
+            unit_count: 1,
+            file_path: String::new(),
+            start_line: 0,
+            end_line: 0,
+        },
+    }
+}
+
+#[test]
+fn test_dedupe_chunks_ignores_whitespace() {
+    let chunks = vec![
+        text_chunk(0, "// Licensed under MIT\n// Copyright"),
+        text_chunk(1, "fn a() {}"),
+        text_chunk(2, "  // Licensed under  MIT\r\n    // Copyright\n"),
+        text_chunk(3, "fn b() {}"),
+        text_chunk(4, "fn a() {}"),
+    ];
+
+    let (kept, duplicates) = dedupe_chunks(chunks);
+
+    let ids: Vec<ChunkId> = kept.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![0, 1, 3]);
+    assert_eq!(duplicates, vec![(2, 0), (4, 1)]);
+}
+
+#[test]
+fn test_dedupe_chunks_keeps_distinct_text() {
+    let chunks = vec![text_chunk(0, "a b"), text_chunk(1, "ab"), text_chunk(2, "")];
+
+    let (kept, duplicates) = dedupe_chunks(chunks);
+
+    assert_eq!(kept.len(), 3);
+    assert!(duplicates.is_empty());
+}
+
+#[test]
+fn test_estimate_tokens_matches_default_estimator() {
+    for text in ["", "fn main() {}", &"x".repeat(8000)] {
+        assert_eq!(estimate_tokens(text), HeuristicEstimator.estimate(text));
+    }
+}
+
+#[test]
+fn test_chunker_uses_custom_estimator() {
+    // 3 words each; the heuristic would fit both in one 5-token chunk
+    let units = vec![
+        make_test_unit("a b c", SemanticKind::Function, 0),
+        make_test_unit("d e f", SemanticKind::Function, 10),
+    ];
+
+    let heuristic = chunk_semantic_units(units.clone(), 5, &HeuristicEstimator);
+    assert_eq!(heuristic.len(), 1);
+
+    let chunks = chunk_semantic_units(units, 5, &WordEstimator);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].metadata.token_count, 3);
+}
+
+#[cfg(feature = "tiktoken")]
+#[test]
+fn test_tiktoken_estimator_counts_tokens() {
+    let estimator = TiktokenEstimator::cl100k_base().unwrap();
+    assert_eq!(estimator.estimate("hello world"), 2);
+    assert_eq!(estimator.estimate(""), 1);
+}
+
+#[test]
+fn test_chunk_parse_results_matches_sequential() {
+    let files: Vec<(String, String)> = (0..8)
+        .map(|i| {
+            let body = format!("    step_{i}();\n").repeat(20 * (i + 1));
+            (format!("src/f{i}.rs"), format!("fn f{i}() {{\n{body}}}\n"))
+        })
+        .collect();
+    let parse = || {
+        files
+            .iter()
+            .map(|(path, source)| RustParser.parse(path, source.as_bytes()))
+            .collect::<Vec<_>>()
+    };
+
+    let sequential: Vec<Chunk> = parse()
+        .into_iter()
+        .flat_map(|result| chunk_parse_result(result, 100, &HeuristicEstimator))
+        .collect();
+    let combined = chunk_parse_results(parse(), 100, &HeuristicEstimator);
+
+    assert!(combined.len() > files.len());
+    assert_eq!(combined.len(), sequential.len());
+    for (i, (chunk, expected)) in combined.iter().zip(&sequential).enumerate() {
+        assert_eq!(chunk.id, i as ChunkId);
+        assert_eq!(chunk.text, expected.text);
+        assert_eq!(chunk.metadata, expected.metadata);
+    }
+}
+
+#[test]
+fn test_chunk_parse_results_opts_applies_overlap() {
+    let source = (0..6)
+        .map(|i| format!("fn f{i}() {{\n    step_{i}();\n}}\n"))
+        .collect::<String>();
+    let options = ChunkOptions {
+        max_tokens: 20,
+        overlap: 10,
+        ..ChunkOptions::default()
+    };
+
+    let chunks = chunk_parse_results_opts(
+        vec![RustParser.parse("src/f.rs", source.as_bytes())],
+        &options,
+    );
+
+    assert!(chunks.len() > 1);
+    for (i, chunk) in chunks.iter().enumerate() {
+        assert_eq!(chunk.id, i as ChunkId);
+        assert_eq!(chunk.metadata.file_path, "src/f.rs");
+    }
+    // Each chunk after the first starts with the previous chunk's last unit
+    for pair in chunks.windows(2) {
+        assert!(pair[1].metadata.start_offset < pair[0].metadata.end_offset);
+    }
+}
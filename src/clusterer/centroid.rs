@@ -3,14 +3,39 @@ pub fn compute_centroid(vectors: &[&[f32]]) -> Vec<f32> {
     let mut out = vec![0.0; dim];
 
     for v in vectors {
-        for i in 0..dim {
-            out[i] += v[i];
+        for (o, x) in out.iter_mut().zip(v.iter()) {
+            *o += x;
         }
     }
 
     let n = vectors.len() as f32;
-    for i in 0..dim {
-        out[i] /= n;
+    for o in out.iter_mut() {
+        *o /= n;
+    }
+
+    out
+}
+
+/// Mean of `vectors` with `vectors[i]` counted `weights[i]` times
+///
+/// Falls back to the plain mean when the weights sum to zero.
+pub fn compute_weighted_centroid(vectors: &[&[f32]], weights: &[f32]) -> Vec<f32> {
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return compute_centroid(vectors);
+    }
+
+    let dim = vectors[0].len();
+    let mut out = vec![0.0; dim];
+
+    for (v, &w) in vectors.iter().zip(weights) {
+        for (o, x) in out.iter_mut().zip(v.iter()) {
+            *o += w * x;
+        }
+    }
+
+    for o in out.iter_mut() {
+        *o /= total;
     }
 
     out
@@ -0,0 +1,116 @@
+use crate::clusterer::{
+    centroid::compute_centroid,
+    similarity::cosine_distance,
+    types::{Cluster, ClusterResult},
+};
+
+/// Cluster id given to points that belong to no cluster
+pub const NOISE_CLUSTER_ID: u32 = u32::MAX;
+
+/// Density-based clustering: groups points that have at least `min_pts`
+/// neighbors (themselves included) within cosine distance `eps`
+///
+/// The number of clusters follows from the data. Points in no dense region
+/// are collected into a final cluster with id `NOISE_CLUSTER_ID`, present
+/// only if there is noise. Clustering takes a single pass, so `iterations`
+/// is 1; `inertia` covers the real clusters only.
+pub fn dbscan(embeddings: &[Vec<f32>], eps: f32, min_pts: usize) -> ClusterResult {
+    let n = embeddings.len();
+    let neighbors = |i: usize| -> Vec<usize> {
+        (0..n)
+            .filter(|&j| cosine_distance(&embeddings[i], &embeddings[j]) <= eps)
+            .collect()
+    };
+
+    let mut labels: Vec<Option<u32>> = vec![None; n];
+    let mut visited = vec![false; n];
+    let mut next_id = 0;
+
+    for i in 0..n {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+
+        let seeds = neighbors(i);
+        if seeds.len() < min_pts {
+            continue; // noise, unless a later cluster reaches it
+        }
+
+        let id = next_id;
+        next_id += 1;
+        labels[i] = Some(id);
+
+        // Grow the cluster through every core point it reaches
+        let mut queue = seeds;
+        while let Some(j) = queue.pop() {
+            if labels[j].is_none() {
+                labels[j] = Some(id);
+            }
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+
+            let reachable = neighbors(j);
+            if reachable.len() >= min_pts {
+                queue.extend(reachable);
+            }
+        }
+    }
+
+    let mut members: Vec<Vec<u32>> = vec![Vec::new(); next_id as usize];
+    let mut noise = Vec::new();
+    for (chunk_id, label) in labels.iter().enumerate() {
+        match label {
+            Some(id) => members[*id as usize].push(chunk_id as u32),
+            None => noise.push(chunk_id as u32),
+        }
+    }
+
+    let mut inertia = 0.0;
+    let mut clusters: Vec<Cluster> = members
+        .into_iter()
+        .enumerate()
+        .map(|(id, chunk_ids)| {
+            let cluster = build_cluster(embeddings, id as u32, chunk_ids);
+            inertia += cluster
+                .chunk_ids
+                .iter()
+                .map(|&c| cosine_distance(&embeddings[c as usize], &cluster.centroid))
+                .sum::<f32>();
+            cluster
+        })
+        .collect();
+
+    if !noise.is_empty() {
+        clusters.push(build_cluster(embeddings, NOISE_CLUSTER_ID, noise));
+    }
+
+    ClusterResult {
+        clusters,
+        iterations: 1,
+        inertia,
+    }
+}
+
+fn build_cluster(embeddings: &[Vec<f32>], id: u32, chunk_ids: Vec<u32>) -> Cluster {
+    let vectors: Vec<&[f32]> = chunk_ids
+        .iter()
+        .map(|&c| &embeddings[c as usize][..])
+        .collect();
+    let centroid = compute_centroid(&vectors);
+
+    let representative_chunk_id = chunk_ids.iter().copied().min_by(|&a, &b| {
+        let da = cosine_distance(&embeddings[a as usize], &centroid);
+        let db = cosine_distance(&embeddings[b as usize], &centroid);
+        da.total_cmp(&db)
+    });
+
+    Cluster {
+        id,
+        chunk_ids,
+        centroid,
+        representative_chunk_id,
+    }
+}
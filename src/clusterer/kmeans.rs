@@ -1,18 +1,107 @@
+use crate::chunker::{Chunk, ChunkId};
 use crate::clusterer::{
-    centroid::compute_centroid,
-    similarity::cosine_distance,
-    types::{Cluster, ClusterResult},
+    centroid::{compute_centroid, compute_weighted_centroid},
+    types::{Cluster, ClusterResult, InitMethod, KmeansConfig, Metric},
 };
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
 
+/// Cluster embeddings with k-means++ seeding (see `kmeans_with_config`)
 pub fn kmeans(embeddings: &[Vec<f32>], k: usize, max_iters: usize, seed: u64) -> ClusterResult {
-    use rand::{SeedableRng, seq::SliceRandom};
-    use rand_chacha::ChaCha8Rng;
+    kmeans_with_config(
+        embeddings,
+        &KmeansConfig {
+            max_iters,
+            seed,
+            init: InitMethod::PlusPlus,
+            metric: Metric::Cosine,
+            ..KmeansConfig::new(k)
+        },
+    )
+}
+
+/// Cluster only the chunks matching `filter`
+///
+/// `embeddings[i]` is the embedding of `chunks[i]`, as is
+/// `config.weights[i]` its weight. Cluster members and
+/// representatives refer to the matching chunks by their `id`, so the
+/// result lines up with `chunks` as if all of them had been clustered.
+///
+/// # Panics
+/// If `chunks` and `embeddings` differ in length.
+pub fn cluster_chunks(
+    chunks: &[Chunk],
+    embeddings: &[Vec<f32>],
+    filter: impl Fn(&Chunk) -> bool,
+    config: &KmeansConfig,
+) -> ClusterResult {
+    assert_eq!(
+        chunks.len(),
+        embeddings.len(),
+        "one embedding per chunk expected"
+    );
+
+    let indices: Vec<usize> = (0..chunks.len()).filter(|&i| filter(&chunks[i])).collect();
+    let ids: Vec<ChunkId> = indices.iter().map(|&i| chunks[i].id).collect();
+    let selected: Vec<Vec<f32>> = indices.iter().map(|&i| embeddings[i].clone()).collect();
+
+    let config = KmeansConfig {
+        weights: config
+            .weights
+            .as_ref()
+            .map(|weights| indices.iter().map(|&i| weights[i]).collect()),
+        ..config.clone()
+    };
+    let mut result = kmeans_with_config(&selected, &config);
+    for cluster in &mut result.clusters {
+        for id in &mut cluster.chunk_ids {
+            *id = ids[*id as usize];
+        }
+        if let Some(id) = &mut cluster.representative_chunk_id {
+            *id = ids[*id as usize];
+        }
+    }
+    result
+}
 
+/// Cluster embeddings into `config.k` groups
+///
+/// `k` is clamped to the number of embeddings; when there are no more
+/// points than clusters, each point gets its own cluster. Empty input or
+/// `k == 0` gives a result with no clusters.
+///
+/// # Panics
+/// If `config.weights` is set and differs in length from `embeddings`.
+pub fn kmeans_with_config(embeddings: &[Vec<f32>], config: &KmeansConfig) -> ClusterResult {
+    let KmeansConfig {
+        k,
+        max_iters,
+        metric,
+        ..
+    } = *config;
     let n = embeddings.len();
-    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    if let Some(weights) = &config.weights {
+        assert_eq!(weights.len(), n, "one weight per embedding expected");
+    }
 
-    // 1. Pick random initial centers
-    let mut centroids: Vec<Vec<f32>> = embeddings.choose_multiple(&mut rng, k).cloned().collect();
+    if n == 0 || k == 0 {
+        return ClusterResult {
+            clusters: Vec::new(),
+            iterations: 0,
+            inertia: 0.0,
+        };
+    }
+    if k >= n {
+        return singleton_clusters(embeddings);
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+
+    // 1. Pick initial centers
+    let mut centroids = match config.init {
+        InitMethod::Random => embeddings.choose_multiple(&mut rng, k).cloned().collect(),
+        InitMethod::PlusPlus => plus_plus_centers(embeddings, k, metric, &mut rng),
+    };
 
     let mut assignments = vec![0usize; n];
     let mut iterations = 0;
@@ -21,20 +110,29 @@ pub fn kmeans(embeddings: &[Vec<f32>], k: usize, max_iters: usize, seed: u64) ->
         iterations += 1;
 
         // 2. Assign each vector to nearest centroid
-        let mut changed = false;
-        for i in 0..n {
-            let best = centroids
-                .iter()
-                .enumerate()
-                .map(|(c, center)| (c, cosine_distance(&embeddings[i], center)))
-                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-                .unwrap()
-                .0;
-
-            if assignments[i] != best {
-                changed = true;
-                assignments[i] = best;
-            }
+        let nearest = |(point, &current): (&Vec<f32>, &usize)| {
+            nearest_centroid(point, &centroids, current, metric)
+        };
+
+        #[cfg(feature = "rayon")]
+        let next: Vec<usize> = {
+            use rayon::prelude::*;
+            embeddings
+                .par_iter()
+                .zip(&assignments)
+                .map(nearest)
+                .collect()
+        };
+
+        #[cfg(not(feature = "rayon"))]
+        let next: Vec<usize> = embeddings.iter().zip(&assignments).map(nearest).collect();
+
+        let mut changed = next != assignments;
+        assignments = next;
+
+        // Clusters that lost all their members get a new point to work with
+        if reseed_empty_clusters(embeddings, &mut centroids, &mut assignments, metric) {
+            changed = true;
         }
 
         if !changed {
@@ -42,17 +140,20 @@ pub fn kmeans(embeddings: &[Vec<f32>], k: usize, max_iters: usize, seed: u64) ->
         }
 
         // 3. Recompute centroids
-        for c in 0..k {
-            let members: Vec<&[f32]> = embeddings
-                .iter()
-                .zip(assignments.iter())
-                .filter(|&(_, a)| *a == c)
-                .map(|(e, _)| &e[..])
-                .collect();
-
-            if !members.is_empty() {
-                centroids[c] = compute_centroid(&members);
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..n).filter(|&i| assignments[i] == c).collect();
+            if members.is_empty() {
+                continue;
             }
+
+            let vectors: Vec<&[f32]> = members.iter().map(|&i| &embeddings[i][..]).collect();
+            *centroid = match &config.weights {
+                Some(weights) => {
+                    let member_weights: Vec<f32> = members.iter().map(|&i| weights[i]).collect();
+                    compute_weighted_centroid(&vectors, &member_weights)
+                }
+                None => compute_centroid(&vectors),
+            };
         }
     }
 
@@ -61,7 +162,8 @@ pub fn kmeans(embeddings: &[Vec<f32>], k: usize, max_iters: usize, seed: u64) ->
         Cluster {
             id: 0,
             chunk_ids: vec![],
-            centroid: vec![]
+            centroid: vec![],
+            representative_chunk_id: None,
         };
         k
     ];
@@ -71,12 +173,159 @@ pub fn kmeans(embeddings: &[Vec<f32>], k: usize, max_iters: usize, seed: u64) ->
         clusters[i].centroid = centroids[i].clone();
     }
 
+    let mut inertia = 0.0;
+    // Distance of each cluster's representative to its centroid
+    let mut closest = vec![f32::INFINITY; k];
     for (chunk_id, &cluster_idx) in assignments.iter().enumerate() {
-        clusters[cluster_idx].chunk_ids.push(chunk_id as u32);
+        let cluster = &mut clusters[cluster_idx];
+        cluster.chunk_ids.push(chunk_id as u32);
+
+        let distance = metric.distance(&embeddings[chunk_id], &centroids[cluster_idx]);
+        inertia += distance;
+        if distance < closest[cluster_idx] {
+            closest[cluster_idx] = distance;
+            cluster.representative_chunk_id = Some(chunk_id as u32);
+        }
     }
 
     ClusterResult {
         clusters,
         iterations,
+        inertia,
     }
 }
+
+/// Index of the centroid closest to `point`, staying with `current` on
+/// ties so duplicate centroids can't trade points
+fn nearest_centroid(
+    point: &[f32],
+    centroids: &[Vec<f32>],
+    current: usize,
+    metric: Metric,
+) -> usize {
+    let (best, best_dist) = centroids
+        .iter()
+        .enumerate()
+        .map(|(c, center)| (c, metric.distance(point, center)))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap();
+
+    if best_dist < metric.distance(point, &centroids[current]) {
+        best
+    } else {
+        current
+    }
+}
+
+/// One cluster per point, centered on it
+fn singleton_clusters(embeddings: &[Vec<f32>]) -> ClusterResult {
+    let clusters = embeddings
+        .iter()
+        .enumerate()
+        .map(|(i, e)| Cluster {
+            id: i as u32,
+            chunk_ids: vec![i as u32],
+            centroid: e.clone(),
+            representative_chunk_id: Some(i as u32),
+        })
+        .collect();
+
+    ClusterResult {
+        clusters,
+        iterations: 0,
+        inertia: 0.0,
+    }
+}
+
+/// Move the point farthest from its centroid into each empty cluster and
+/// center the cluster on it
+///
+/// Points are only taken from clusters with more than one member, so no
+/// new empty clusters are created. Returns whether anything moved.
+fn reseed_empty_clusters(
+    embeddings: &[Vec<f32>],
+    centroids: &mut [Vec<f32>],
+    assignments: &mut [usize],
+    metric: Metric,
+) -> bool {
+    let mut sizes = vec![0usize; centroids.len()];
+    for &a in assignments.iter() {
+        sizes[a] += 1;
+    }
+
+    let mut changed = false;
+    for c in 0..centroids.len() {
+        if sizes[c] > 0 {
+            continue;
+        }
+
+        let farthest = (0..embeddings.len())
+            .filter(|&i| sizes[assignments[i]] > 1)
+            .map(|i| {
+                (
+                    i,
+                    metric.distance(&embeddings[i], &centroids[assignments[i]]),
+                )
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, _)) = farthest else {
+            break; // fewer points than clusters
+        };
+
+        sizes[assignments[i]] -= 1;
+        sizes[c] = 1;
+        assignments[i] = c;
+        centroids[c] = embeddings[i].clone();
+        changed = true;
+    }
+
+    changed
+}
+
+/// k-means++ seeding: the first center is uniform, each next one is drawn
+/// with probability proportional to its squared distance from the nearest
+/// center so far
+fn plus_plus_centers(
+    embeddings: &[Vec<f32>],
+    k: usize,
+    metric: Metric,
+    rng: &mut ChaCha8Rng,
+) -> Vec<Vec<f32>> {
+    let mut centers: Vec<Vec<f32>> = Vec::with_capacity(k);
+    let Some(first) = embeddings.choose(rng) else {
+        return centers;
+    };
+    centers.push(first.clone());
+
+    // Squared distance from each point to its nearest chosen center
+    let mut weights: Vec<f32> = embeddings
+        .iter()
+        .map(|e| metric.distance(e, first).powi(2))
+        .collect();
+
+    while centers.len() < k.min(embeddings.len()) {
+        let total: f32 = weights.iter().sum();
+        let next = if total > 0.0 {
+            let mut target = rng.r#gen::<f32>() * total;
+            weights
+                .iter()
+                .position(|&w| {
+                    target -= w;
+                    target < 0.0 && w > 0.0
+                })
+                // Rounding can leave a sliver of `target`; take the last candidate
+                .unwrap_or_else(|| weights.iter().rposition(|&w| w > 0.0).unwrap_or(0))
+        } else {
+            // Every point coincides with a center; any choice is as good
+            rng.gen_range(0..embeddings.len())
+        };
+
+        let center = embeddings[next].clone();
+        for (w, e) in weights.iter_mut().zip(embeddings) {
+            *w = w.min(metric.distance(e, &center).powi(2));
+        }
+        centers.push(center);
+    }
+
+    centers
+}
@@ -0,0 +1,59 @@
+use crate::clusterer::{similarity::cosine_distance, types::ClusterResult};
+
+/// Mean silhouette coefficient of a clustering, in [-1, 1]
+///
+/// For each point, compares the mean cosine distance to the rest of its own
+/// cluster (`a`) with the mean distance to the nearest other cluster (`b`)
+/// as `(b - a) / max(a, b)`. Points alone in their cluster score 0. Higher
+/// is better; returns 0.0 when there are fewer than two non-empty clusters.
+///
+/// Chunk ids in `result` index into `embeddings`, as produced by `kmeans`.
+pub fn silhouette_score(embeddings: &[Vec<f32>], result: &ClusterResult) -> f32 {
+    let clusters: Vec<&[u32]> = result
+        .clusters
+        .iter()
+        .map(|c| &c.chunk_ids[..])
+        .filter(|ids| !ids.is_empty())
+        .collect();
+
+    if clusters.len() < 2 {
+        return 0.0;
+    }
+
+    let mean_distance = |i: u32, ids: &[u32]| -> f32 {
+        let point = &embeddings[i as usize];
+        let total: f32 = ids
+            .iter()
+            .filter(|&&j| j != i)
+            .map(|&j| cosine_distance(point, &embeddings[j as usize]))
+            .sum();
+        let others = ids.iter().filter(|&&j| j != i).count();
+        total / others as f32
+    };
+
+    let mut total = 0.0;
+    let mut count = 0;
+    for (c, ids) in clusters.iter().enumerate() {
+        for &i in ids.iter() {
+            count += 1;
+            if ids.len() < 2 {
+                continue;
+            }
+
+            let a = mean_distance(i, ids);
+            let b = clusters
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != c)
+                .map(|(_, other)| mean_distance(i, other))
+                .fold(f32::INFINITY, f32::min);
+
+            let max = a.max(b);
+            if max > 0.0 {
+                total += (b - a) / max;
+            }
+        }
+    }
+
+    total / count as f32
+}
@@ -1,7 +1,14 @@
 mod centroid;
+mod dbscan;
 mod kmeans;
+mod metrics;
 mod similarity;
 mod types;
 
-pub use kmeans::kmeans;
-pub use types::{Cluster, ClusterResult};
+#[cfg(test)]
+mod tests;
+
+pub use dbscan::{NOISE_CLUSTER_ID, dbscan};
+pub use kmeans::{cluster_chunks, kmeans, kmeans_with_config};
+pub use metrics::silhouette_score;
+pub use types::{Cluster, ClusterResult, InitMethod, KmeansConfig, Metric};
@@ -16,6 +16,23 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     dot / (na.sqrt() * nb.sqrt())
 }
 
+/// `1 - cosine_similarity`, in [0, 2]
+///
+/// Zero vectors have no direction, so by convention two zero vectors are
+/// identical (0.0) and a zero vector is orthogonal to any non-zero one (1.0).
 pub fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let is_zero = |v: &[f32]| v.iter().all(|&x| x == 0.0);
+    if is_zero(a) && is_zero(b) {
+        return 0.0;
+    }
+
     1.0 - cosine_similarity(a, b)
 }
+
+pub fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt()
+}
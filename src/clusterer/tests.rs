@@ -1,3 +1,5 @@
+use super::*;
+
 #[test]
 fn test_simple_kmeans() {
     let v1 = vec![1.0, 0.0];
@@ -5,7 +7,472 @@ fn test_simple_kmeans() {
     let v3 = vec![0.0, 1.0];
     let v4 = vec![0.1, 0.9];
 
-    let res = kmeans(&vec![v1, v2, v3, v4], 2, 20, 42);
+    let res = kmeans(&[v1, v2, v3, v4], 2, 20, 42);
 
     assert_eq!(res.clusters.len(), 2);
 }
+
+/// Three tight groups of 5 points around the axes of 3D space
+fn three_groups() -> Vec<Vec<f32>> {
+    let mut points = Vec::new();
+    for axis in 0..3 {
+        for j in 0..5 {
+            let mut v = vec![0.05 * j as f32; 3];
+            v[axis] = 1.0;
+            points.push(v);
+        }
+    }
+    points
+}
+
+fn sorted_sizes(result: &ClusterResult) -> Vec<usize> {
+    let mut sizes: Vec<usize> = result.clusters.iter().map(|c| c.chunk_ids.len()).collect();
+    sizes.sort();
+    sizes
+}
+
+#[test]
+fn test_plus_plus_finds_separated_groups() {
+    for seed in 0..20 {
+        let mut config = KmeansConfig::new(3);
+        config.seed = seed;
+
+        let res = kmeans_with_config(&three_groups(), &config);
+
+        assert_eq!(sorted_sizes(&res), vec![5, 5, 5], "seed {seed}");
+    }
+}
+
+#[test]
+fn test_config_defaults_to_plus_plus() {
+    let config = KmeansConfig::new(4);
+    assert_eq!(config.init, InitMethod::PlusPlus);
+    assert_eq!(config.k, 4);
+}
+
+#[test]
+fn test_kmeans_is_reproducible() {
+    for init in [InitMethod::Random, InitMethod::PlusPlus] {
+        let config = KmeansConfig {
+            init,
+            ..KmeansConfig::new(3)
+        };
+
+        let a = kmeans_with_config(&three_groups(), &config);
+        let b = kmeans_with_config(&three_groups(), &config);
+
+        let ids = |r: &ClusterResult| -> Vec<Vec<u32>> {
+            r.clusters.iter().map(|c| c.chunk_ids.clone()).collect()
+        };
+        assert_eq!(ids(&a), ids(&b));
+    }
+}
+
+#[test]
+fn test_kmeans_reseeds_empty_clusters() {
+    // Mostly duplicates: seeding can pick the same point twice, which
+    // leaves one of the two identical centroids without members
+    let mut points = vec![vec![1.0, 0.0]; 6];
+    points.push(vec![0.0, 1.0]);
+    points.push(vec![0.7, 0.7]);
+
+    for init in [InitMethod::Random, InitMethod::PlusPlus] {
+        for seed in 0..20 {
+            let config = KmeansConfig {
+                seed,
+                init,
+                ..KmeansConfig::new(4)
+            };
+
+            let res = kmeans_with_config(&points, &config);
+
+            assert_eq!(res.clusters.len(), 4);
+            assert!(
+                res.clusters.iter().all(|c| !c.chunk_ids.is_empty()),
+                "empty cluster with {init:?}, seed {seed}: {:?}",
+                sorted_sizes(&res)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_inertia_decreases_as_k_grows() {
+    let points = three_groups();
+
+    let inertias: Vec<f32> = (1..=5)
+        .map(|k| kmeans(&points, k, 100, 42).inertia)
+        .collect();
+
+    for pair in inertias.windows(2) {
+        assert!(pair[1] <= pair[0], "inertia went up: {inertias:?}");
+    }
+    assert!(inertias[2] < inertias[0]);
+}
+
+#[test]
+fn test_inertia_is_zero_for_identical_points() {
+    let points = vec![vec![1.0, 2.0]; 4];
+
+    let res = kmeans(&points, 1, 10, 42);
+
+    assert!(res.inertia.abs() < 1e-6);
+}
+
+#[test]
+fn test_silhouette_prefers_true_cluster_count() {
+    let points = three_groups();
+
+    let right = silhouette_score(&points, &kmeans(&points, 3, 100, 42));
+    let wrong = silhouette_score(&points, &kmeans(&points, 2, 100, 42));
+
+    assert!(right > 0.8, "silhouette {right}");
+    assert!(right > wrong);
+}
+
+#[test]
+fn test_silhouette_of_single_cluster_is_zero() {
+    let points = three_groups();
+
+    let res = kmeans(&points, 1, 100, 42);
+
+    assert_eq!(silhouette_score(&points, &res), 0.0);
+}
+
+#[test]
+fn test_kmeans_empty_input() {
+    let res = kmeans(&[], 3, 100, 42);
+
+    assert!(res.clusters.is_empty());
+    assert_eq!(res.iterations, 0);
+}
+
+#[test]
+fn test_kmeans_zero_k() {
+    let res = kmeans(&three_groups(), 0, 100, 42);
+
+    assert!(res.clusters.is_empty());
+    assert_eq!(res.inertia, 0.0);
+}
+
+#[test]
+fn test_kmeans_k_larger_than_n() {
+    let points = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]];
+
+    for init in [InitMethod::Random, InitMethod::PlusPlus] {
+        let config = KmeansConfig {
+            init,
+            ..KmeansConfig::new(10)
+        };
+
+        let res = kmeans_with_config(&points, &config);
+
+        assert_eq!(res.clusters.len(), 3);
+        for (i, cluster) in res.clusters.iter().enumerate() {
+            assert_eq!(cluster.chunk_ids, vec![i as u32]);
+            assert_eq!(cluster.centroid, points[i]);
+        }
+        assert_eq!(res.inertia, 0.0);
+    }
+}
+
+#[test]
+fn test_cosine_distance_between_zero_vectors_is_zero() {
+    assert_eq!(similarity::cosine_distance(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+}
+
+#[test]
+fn test_cosine_distance_from_zero_vector_is_one() {
+    assert_eq!(similarity::cosine_distance(&[0.0, 0.0], &[3.0, 4.0]), 1.0);
+    assert_eq!(similarity::cosine_distance(&[3.0, 4.0], &[0.0, 0.0]), 1.0);
+}
+
+#[test]
+fn test_kmeans_groups_zero_vectors_together() {
+    let mut points = vec![vec![0.0, 0.0]; 3];
+    points.extend([vec![1.0, 0.0], vec![0.9, 0.1]]);
+
+    let res = kmeans(&points, 2, 100, 42);
+
+    let zeros = res
+        .clusters
+        .iter()
+        .find(|c| c.chunk_ids.contains(&0))
+        .unwrap();
+    assert_eq!(zeros.chunk_ids, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_euclidean_distance() {
+    assert_eq!(
+        similarity::euclidean_distance(&[0.0, 0.0], &[3.0, 4.0]),
+        5.0
+    );
+    assert_eq!(
+        similarity::euclidean_distance(&[1.0, 2.0], &[1.0, 2.0]),
+        0.0
+    );
+}
+
+#[test]
+fn test_kmeans_with_each_metric() {
+    let points = [
+        vec![1.0, 0.0],
+        vec![0.9, 0.1],
+        vec![0.0, 1.0],
+        vec![0.1, 0.9],
+    ];
+
+    for metric in [Metric::Cosine, Metric::Euclidean] {
+        let config = KmeansConfig {
+            metric,
+            ..KmeansConfig::new(2)
+        };
+
+        let res = kmeans_with_config(&points, &config);
+
+        let mut groups: Vec<Vec<u32>> = res.clusters.iter().map(|c| c.chunk_ids.clone()).collect();
+        groups.sort();
+        assert_eq!(groups, vec![vec![0, 1], vec![2, 3]], "{metric:?}");
+    }
+}
+
+#[test]
+fn test_euclidean_metric_separates_by_length() {
+    // Same direction, different magnitudes: cosine can't tell them apart
+    let points = [
+        vec![1.0, 1.0],
+        vec![1.1, 1.1],
+        vec![10.0, 10.0],
+        vec![10.5, 10.5],
+    ];
+    let config = KmeansConfig {
+        metric: Metric::Euclidean,
+        ..KmeansConfig::new(2)
+    };
+
+    let res = kmeans_with_config(&points, &config);
+
+    let mut groups: Vec<Vec<u32>> = res.clusters.iter().map(|c| c.chunk_ids.clone()).collect();
+    groups.sort();
+    assert_eq!(groups, vec![vec![0, 1], vec![2, 3]]);
+}
+
+#[test]
+fn test_representative_is_member_closest_to_centroid() {
+    let points = three_groups();
+
+    let res = kmeans(&points, 3, 100, 42);
+
+    for cluster in &res.clusters {
+        let rep = cluster.representative_chunk_id.expect("non-empty cluster");
+        assert!(cluster.chunk_ids.contains(&rep));
+
+        let rep_dist = similarity::cosine_distance(&points[rep as usize], &cluster.centroid);
+        for &id in &cluster.chunk_ids {
+            let dist = similarity::cosine_distance(&points[id as usize], &cluster.centroid);
+            assert!(rep_dist <= dist);
+        }
+    }
+}
+
+#[test]
+fn test_singleton_clusters_represent_themselves() {
+    let points = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+    let res = kmeans(&points, 5, 100, 42);
+
+    for cluster in &res.clusters {
+        assert_eq!(cluster.representative_chunk_id, Some(cluster.id));
+    }
+}
+
+#[test]
+fn test_dbscan_finds_blobs_and_noise() {
+    let mut points = Vec::new();
+    for j in 0..5 {
+        points.push(vec![1.0, 0.02 * j as f32, 0.0]);
+    }
+    for j in 0..5 {
+        points.push(vec![0.02 * j as f32, 1.0, 0.0]);
+    }
+    points.push(vec![0.0, 0.0, 1.0]);
+
+    let res = dbscan(&points, 0.05, 3);
+
+    assert_eq!(res.clusters.len(), 3);
+    assert_eq!(res.clusters[0].chunk_ids, vec![0, 1, 2, 3, 4]);
+    assert_eq!(res.clusters[1].chunk_ids, vec![5, 6, 7, 8, 9]);
+
+    let noise = &res.clusters[2];
+    assert_eq!(noise.id, NOISE_CLUSTER_ID);
+    assert_eq!(noise.chunk_ids, vec![10]);
+}
+
+#[test]
+fn test_dbscan_without_noise_has_no_noise_cluster() {
+    let res = dbscan(&three_groups(), 0.1, 2);
+
+    assert_eq!(sorted_sizes(&res), vec![5, 5, 5]);
+    assert!(res.clusters.iter().all(|c| c.id != NOISE_CLUSTER_ID));
+}
+
+#[test]
+fn test_dbscan_empty_input() {
+    let res = dbscan(&[], 0.1, 2);
+
+    assert!(res.clusters.is_empty());
+}
+
+fn chunk(id: u32, kind: crate::parser::SemanticKind) -> crate::chunker::Chunk {
+    let text = format!("chunk {id}");
+    let mut chunk = crate::chunker::chunk_semantic_units_heuristic(
+        vec![crate::parser::SemanticUnit {
+            start_offset: 0,
+            end_offset: text.len(),
+            text,
+            kind,
+        }],
+        100,
+    )
+    .remove(0);
+    chunk.id = id;
+    chunk
+}
+
+#[test]
+fn test_cluster_chunks_filters_and_maps_ids() {
+    use crate::parser::SemanticKind;
+
+    // Functions at ids 1, 2 (near x) and 4, 5 (near y); the rest is noise
+    let kinds = [
+        SemanticKind::Blob,
+        SemanticKind::Function,
+        SemanticKind::Function,
+        SemanticKind::Comment,
+        SemanticKind::Function,
+        SemanticKind::Function,
+    ];
+    let chunks: Vec<_> = kinds
+        .iter()
+        .enumerate()
+        .map(|(i, &kind)| chunk(i as u32, kind))
+        .collect();
+    let embeddings = vec![
+        vec![0.0, 0.0, 1.0],
+        vec![1.0, 0.0, 0.0],
+        vec![0.9, 0.1, 0.0],
+        vec![0.0, 0.1, 1.0],
+        vec![0.0, 1.0, 0.0],
+        vec![0.1, 0.9, 0.0],
+    ];
+
+    let res = cluster_chunks(
+        &chunks,
+        &embeddings,
+        |c| c.metadata.kinds.contains(&SemanticKind::Function),
+        &KmeansConfig::new(2),
+    );
+
+    let mut groups: Vec<Vec<u32>> = res.clusters.iter().map(|c| c.chunk_ids.clone()).collect();
+    groups.sort();
+    assert_eq!(groups, vec![vec![1, 2], vec![4, 5]]);
+    for cluster in &res.clusters {
+        let rep = cluster.representative_chunk_id.unwrap();
+        assert!(cluster.chunk_ids.contains(&rep));
+    }
+
+    // Weights line up with `chunks`, not with the filtered subset
+    let weighted = KmeansConfig {
+        weights: Some(vec![1.0; chunks.len()]),
+        ..KmeansConfig::new(2)
+    };
+    let res = cluster_chunks(
+        &chunks,
+        &embeddings,
+        |c| c.metadata.kinds.contains(&SemanticKind::Function),
+        &weighted,
+    );
+    assert_eq!(
+        res.clusters
+            .iter()
+            .map(|c| c.chunk_ids.len())
+            .sum::<usize>(),
+        4
+    );
+}
+
+#[test]
+#[should_panic(expected = "one embedding per chunk")]
+fn test_cluster_chunks_rejects_mismatched_lengths() {
+    let chunks = vec![chunk(0, crate::parser::SemanticKind::Function)];
+
+    cluster_chunks(&chunks, &[], |_| true, &KmeansConfig::new(1));
+}
+
+#[test]
+fn test_weighted_centroid_vs_unweighted() {
+    use super::centroid::{compute_centroid, compute_weighted_centroid};
+
+    let vectors: Vec<&[f32]> = vec![&[0.0, 0.0], &[1.0, 0.0], &[0.0, 4.0]];
+
+    assert_eq!(compute_centroid(&vectors), vec![1.0 / 3.0, 4.0 / 3.0]);
+    assert_eq!(
+        compute_weighted_centroid(&vectors, &[1.0, 6.0, 1.0]),
+        vec![0.75, 0.5]
+    );
+    // Equal or all-zero weights give the plain mean
+    assert_eq!(
+        compute_weighted_centroid(&vectors, &[2.0, 2.0, 2.0]),
+        compute_centroid(&vectors)
+    );
+    assert_eq!(
+        compute_weighted_centroid(&vectors, &[0.0; 3]),
+        compute_centroid(&vectors)
+    );
+}
+
+#[test]
+fn test_kmeans_weights_pull_centroids() {
+    // Two far-apart groups of two; in each, the second point is heavier
+    let points = vec![
+        vec![0.0, 0.0],
+        vec![1.0, 0.0],
+        vec![10.0, 10.0],
+        vec![10.0, 11.0],
+    ];
+    let config = KmeansConfig {
+        metric: Metric::Euclidean,
+        ..KmeansConfig::new(2)
+    };
+    let weighted = KmeansConfig {
+        weights: Some(vec![1.0, 3.0, 1.0, 3.0]),
+        ..config.clone()
+    };
+
+    let centroids = |config: &KmeansConfig| {
+        let mut centroids: Vec<Vec<f32>> = kmeans_with_config(&points, config)
+            .clusters
+            .into_iter()
+            .map(|c| c.centroid)
+            .collect();
+        centroids.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        centroids
+    };
+
+    assert_eq!(centroids(&config), vec![vec![0.5, 0.0], vec![10.0, 10.5]]);
+    assert_eq!(
+        centroids(&weighted),
+        vec![vec![0.75, 0.0], vec![10.0, 10.75]]
+    );
+}
+
+#[test]
+#[should_panic(expected = "one weight per embedding expected")]
+fn test_kmeans_rejects_mismatched_weights() {
+    let config = KmeansConfig {
+        weights: Some(vec![1.0]),
+        ..KmeansConfig::new(2)
+    };
+    kmeans_with_config(&three_groups(), &config);
+}
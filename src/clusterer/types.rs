@@ -1,14 +1,87 @@
 use crate::chunker::ChunkId;
+use crate::clusterer::similarity::{cosine_distance, euclidean_distance};
+use serde::Serialize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Cluster {
     pub id: u32,
     pub chunk_ids: Vec<ChunkId>,
     pub centroid: Vec<f32>,
+    /// Member closest to the centroid, e.g., to summarize the cluster by;
+    /// `None` for an empty cluster
+    pub representative_chunk_id: Option<ChunkId>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ClusterResult {
     pub clusters: Vec<Cluster>,
     pub iterations: usize,
+    /// Sum of distances (in the configured metric) from each point to its
+    /// cluster's centroid;
+    /// lower means tighter clusters
+    pub inertia: f32,
+}
+
+/// How `kmeans` picks its starting centroids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitMethod {
+    /// `k` distinct points chosen uniformly at random
+    Random,
+    /// k-means++: each new center is drawn with probability proportional
+    /// to its squared distance from the nearest center chosen so far
+    #[default]
+    PlusPlus,
+}
+
+/// Distance used to assign points to centroids
+///
+/// Centroids are always the mean of their members, which minimizes
+/// euclidean distance exactly; with cosine it is the usual approximation
+/// (the mean points in the right direction but isn't normalized).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    /// `1 - cosine similarity`; ignores vector length
+    #[default]
+    Cosine,
+    /// Straight-line distance; sensitive to vector length
+    Euclidean,
+}
+
+impl Metric {
+    pub fn distance(self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Metric::Cosine => cosine_distance(a, b),
+            Metric::Euclidean => euclidean_distance(a, b),
+        }
+    }
+}
+
+/// Parameters for `kmeans_with_config`
+#[derive(Debug, Clone)]
+pub struct KmeansConfig {
+    /// Number of clusters
+    pub k: usize,
+    /// Upper bound on assign/update rounds
+    pub max_iters: usize,
+    /// RNG seed; the same seed and input give the same clusters
+    pub seed: u64,
+    pub init: InitMethod,
+    pub metric: Metric,
+    /// Weight of each embedding when recomputing centroids (e.g., its
+    /// token count), so heavier points pull their centroid harder;
+    /// `None` weighs all points equally
+    pub weights: Option<Vec<f32>>,
+}
+
+impl KmeansConfig {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            max_iters: 100,
+            seed: 42,
+            init: InitMethod::default(),
+            metric: Metric::default(),
+            weights: None,
+        }
+    }
 }
@@ -1,4 +1,5 @@
 // batcher.rs - throughput optimizer
+use crate::chunker::estimate_tokens;
 
 pub struct Batcher {
     pub batch_size: usize,
@@ -12,4 +13,27 @@ impl Batcher {
     pub fn split<'a>(&self, items: &'a [String]) -> Vec<&'a [String]> {
         items.chunks(self.batch_size).collect()
     }
+
+    /// Pack consecutive items into batches of at most `max_tokens`
+    /// (by `estimate_tokens`)
+    ///
+    /// An item that alone exceeds the budget gets a batch of its own.
+    pub fn split_by_tokens<'a>(&self, items: &'a [String], max_tokens: usize) -> Vec<&'a [String]> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        let mut tokens = 0;
+        for (i, item) in items.iter().enumerate() {
+            let item_tokens = estimate_tokens(item);
+            if i > start && tokens + item_tokens > max_tokens {
+                batches.push(&items[start..i]);
+                start = i;
+                tokens = 0;
+            }
+            tokens += item_tokens;
+        }
+        if start < items.len() {
+            batches.push(&items[start..]);
+        }
+        batches
+    }
 }
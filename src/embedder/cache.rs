@@ -0,0 +1,122 @@
+// cache.rs - reuse embeddings of unchanged text between runs
+use crate::embedder::client::EmbedError;
+use rusqlite::{Connection, OptionalExtension, params};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Storage for embeddings keyed by `cache_key`
+pub trait EmbeddingCache: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Vec<f32>>, EmbedError>;
+
+    fn put(&self, key: &str, embedding: &[f32]) -> Result<(), EmbedError>;
+
+    /// Store several embeddings; implementations may batch the writes
+    fn put_many(&self, entries: &[(String, Vec<f32>)]) -> Result<(), EmbedError> {
+        for (key, embedding) in entries {
+            self.put(key, embedding)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cache key for `text` embedded by `model`: hex SHA-256 of both
+pub fn cache_key(model: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    // Separator so ("ab", "c") and ("a", "bc") differ
+    hasher.update([0]);
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Embedding cache in a SQLite file, usually kept next to the docpack
+pub struct SqliteEmbeddingCache {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteEmbeddingCache {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EmbedError> {
+        Self::init(Connection::open(path).map_err(cache_error)?)
+    }
+
+    pub fn in_memory() -> Result<Self, EmbedError> {
+        Self::init(Connection::open_in_memory().map_err(cache_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, EmbedError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS embedding_cache (
+                key TEXT PRIMARY KEY,
+                vector BLOB NOT NULL
+            )",
+        )
+        .map_err(cache_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic mid-statement leaves nothing half-written worth guarding
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl EmbeddingCache for SqliteEmbeddingCache {
+    fn get(&self, key: &str) -> Result<Option<Vec<f32>>, EmbedError> {
+        let blob: Option<Vec<u8>> = self
+            .conn()
+            .query_row(
+                "SELECT vector FROM embedding_cache WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(cache_error)?;
+
+        Ok(blob.map(|bytes| decode_vector(&bytes)))
+    }
+
+    fn put(&self, key: &str, embedding: &[f32]) -> Result<(), EmbedError> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO embedding_cache (key, vector) VALUES (?1, ?2)",
+                params![key, encode_vector(embedding)],
+            )
+            .map_err(cache_error)?;
+        Ok(())
+    }
+
+    fn put_many(&self, entries: &[(String, Vec<f32>)]) -> Result<(), EmbedError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(cache_error)?;
+        {
+            let mut stmt = tx
+                .prepare("INSERT OR REPLACE INTO embedding_cache (key, vector) VALUES (?1, ?2)")
+                .map_err(cache_error)?;
+            for (key, embedding) in entries {
+                stmt.execute(params![key, encode_vector(embedding)])
+                    .map_err(cache_error)?;
+            }
+        }
+        tx.commit().map_err(cache_error)
+    }
+}
+
+fn cache_error(e: rusqlite::Error) -> EmbedError {
+    EmbedError::Cache(e.to_string())
+}
+
+/// Pack a vector as little-endian f32s
+fn encode_vector(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
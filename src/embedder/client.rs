@@ -1,10 +1,20 @@
 // client.rs - the HTTP/Subprocess embedder
-use crate::embedder::types::*;
+use crate::embedder::batcher::Batcher;
+use crate::embedder::cache::{EmbeddingCache, cache_key};
+use crate::embedder::codec::{DefaultCodec, EmbeddingCodec};
+use crate::embedder::embed::l2_normalize;
+use crate::embedder::model::EmbeddingModelInfo;
+use crate::embedder::retry::RetryPolicy;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use reqwest::Client;
 use reqwest::blocking::Client as BlockingClient;
+use std::sync::OnceLock;
 use std::time::Duration;
 use thiserror::Error;
 
+/// Characters of an undecodable response body kept in the error
+const BODY_SNIPPET_CHARS: usize = 300;
+
 #[derive(Debug, Error)]
 pub enum EmbedError {
     #[error("HTTP request failed: {0}")]
@@ -18,14 +28,40 @@ pub enum EmbedError {
 
     #[error("Timeout after {0:?}")]
     Timeout(Duration),
+
+    #[error("Embedding cache error: {0}")]
+    Cache(String),
+
+    #[error("Local model error: {0}")]
+    Model(String),
+}
+
+impl EmbedError {
+    /// Whether the failure is transient: the server was unreachable or
+    /// returned a 5xx status. Client errors (4xx) won't succeed on retry.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EmbedError::RequestFailed(e) => e.is_connect() || e.is_timeout(),
+            EmbedError::ServerError { status, .. } => *status >= 500,
+            EmbedError::InvalidResponse(_) | EmbedError::Cache(_) | EmbedError::Model(_) => false,
+            EmbedError::Timeout(_) => true,
+        }
+    }
 }
 
 pub struct EmbeddingClient {
     http: Client,
-    blocking_http: BlockingClient,
+    /// Built on first blocking call; reqwest's blocking client owns a runtime
+    /// that must not be created or dropped inside an async context.
+    blocking_http: OnceLock<BlockingClient>,
     endpoint: String,
-    #[allow(dead_code)]
     timeout: Duration,
+    retry: RetryPolicy,
+    model: EmbeddingModelInfo,
+    normalize: bool,
+    /// Request path appended to the endpoint (e.g., "/embed")
+    path: String,
+    codec: Box<dyn EmbeddingCodec>,
 }
 
 impl EmbeddingClient {
@@ -39,31 +75,108 @@ impl EmbeddingClient {
             .build()
             .expect("Failed to build HTTP client");
 
-        let blocking_http = BlockingClient::builder()
-            .timeout(timeout)
-            .build()
-            .expect("Failed to build blocking HTTP client");
-
         Self {
             http,
-            blocking_http,
+            blocking_http: OnceLock::new(),
             endpoint: endpoint.into(),
             timeout,
+            retry: RetryPolicy::default(),
+            model: EmbeddingModelInfo::default(),
+            normalize: false,
+            path: "/embed".to_string(),
+            codec: Box::new(DefaultCodec),
         }
     }
 
+    /// Post to `path` under the endpoint instead of "/embed"
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+        self
+    }
+
+    /// Speak another server's request/response format (e.g., `TeiCodec`)
+    pub fn with_codec(mut self, codec: impl EmbeddingCodec + 'static) -> Self {
+        self.codec = Box::new(codec);
+        self
+    }
+
+    /// L2-normalize returned vectors (zero vectors are left as-is), so
+    /// cosine-based clustering doesn't depend on the server normalizing
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Describe the model the server runs (used for cache keys)
+    pub fn with_model(mut self, model: EmbeddingModelInfo) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn model(&self) -> &EmbeddingModelInfo {
+        &self.model
+    }
+
+    /// Retry transient failures up to `max_attempts` times in total, with
+    /// exponential backoff starting at `base_delay`
+    pub fn with_retry(self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.with_retry_policy(RetryPolicy::new(max_attempts, base_delay))
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    fn blocking_http(&self) -> &BlockingClient {
+        self.blocking_http.get_or_init(|| {
+            BlockingClient::builder()
+                .timeout(self.timeout)
+                .build()
+                .expect("Failed to build blocking HTTP client")
+        })
+    }
+
+    /// Embed texts in a single request, one vector per text
+    ///
+    /// A response with a different number of vectors than texts is an
+    /// `InvalidResponse`, so vectors can't end up paired with the wrong text.
     pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
         if texts.is_empty() {
             return Ok(Vec::new());
         }
 
-        let req = EmbeddingRequest { texts };
+        let req = self.codec.encode(&texts);
+        let mut retry = 0;
+        let embeddings = loop {
+            match self.send(&req).await {
+                Err(e) if e.is_retryable() && retry + 1 < self.retry.max_attempts => {
+                    tokio::time::sleep(self.retry.delay(retry)).await;
+                    retry += 1;
+                }
+                result => break result?,
+            }
+        };
+        check_count(texts.len(), embeddings)
+    }
+
+    /// Send a single embedding request
+    async fn send(&self, req: &serde_json::Value) -> Result<Vec<Vec<f32>>, EmbedError> {
         let response = self
             .http
-            .post(format!("{}/embed", self.endpoint))
-            .json(&req)
+            .post(format!("{}{}", self.endpoint, self.path))
+            .json(req)
             .send()
-            .await?;
+            .await
+            .map_err(|e| self.request_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -77,27 +190,147 @@ impl EmbeddingClient {
             });
         }
 
-        let res: EmbeddingResponse = response.json().await?;
+        let body = response.text().await.map_err(|e| self.request_error(e))?;
 
-        Ok(res.embeddings)
+        Ok(self.finish(self.decode(&body)?))
     }
 
     pub async fn embed_chunks(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
         self.embed(texts).await
     }
 
+    /// Decode a response body with the codec
+    ///
+    /// Bodies that aren't the expected JSON (e.g., a proxy's HTML error
+    /// page) are quoted in the error, since the serde message alone rarely
+    /// says what the server sent.
+    fn decode(&self, body: &str) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let quoted = |message: String| {
+            EmbedError::InvalidResponse(format!("{}; body: {}", message, body_snippet(body)))
+        };
+
+        let value = serde_json::from_str(body).map_err(|e| quoted(e.to_string()))?;
+        self.codec.decode(value).map_err(|e| match e {
+            EmbedError::InvalidResponse(message) => quoted(message),
+            e => e,
+        })
+    }
+
+    /// Apply client-side post-processing to a server response
+    fn finish(&self, mut embeddings: Vec<Vec<f32>>) -> Vec<Vec<f32>> {
+        if self.normalize {
+            embeddings.iter_mut().for_each(|v| l2_normalize(v));
+        }
+        embeddings
+    }
+
+    /// Report timeouts as `Timeout` so they can be told apart from an
+    /// unreachable server
+    fn request_error(&self, e: reqwest::Error) -> EmbedError {
+        if e.is_timeout() {
+            EmbedError::Timeout(self.timeout)
+        } else {
+            EmbedError::RequestFailed(e)
+        }
+    }
+
+    /// Embed texts in batches of `batch_size`, keeping up to `concurrency`
+    /// requests in flight
+    ///
+    /// Results are returned in the order of `texts`. Use
+    /// `EmbeddingModelInfo::max_batch` as the batch size to match the model.
+    pub async fn embed_batched(
+        &self,
+        texts: Vec<String>,
+        batch_size: usize,
+        concurrency: usize,
+    ) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let batches: Vec<Vec<String>> = Batcher::new(batch_size.max(1))
+            .split(&texts)
+            .into_iter()
+            .map(<[String]>::to_vec)
+            .collect();
+
+        let results: Vec<Vec<Vec<f32>>> = stream::iter(batches)
+            .map(|batch| self.embed(batch))
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Embed texts, reusing cached embeddings and caching new ones
+    ///
+    /// Only texts missing from `cache` (for this client's model) are sent
+    /// to the server; results are returned in the order of `texts`.
+    pub async fn embed_cached(
+        &self,
+        texts: Vec<String>,
+        cache: &dyn EmbeddingCache,
+    ) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let keys: Vec<String> = texts
+            .iter()
+            .map(|text| cache_key(&self.model.name, text))
+            .collect();
+
+        let mut results = Vec::with_capacity(texts.len());
+        let mut misses = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            let hit = cache.get(key)?;
+            if hit.is_none() {
+                misses.push(i);
+            }
+            results.push(hit);
+        }
+
+        if !misses.is_empty() {
+            let miss_texts = misses.iter().map(|&i| texts[i].clone()).collect();
+            let embeddings = self.embed(miss_texts).await?;
+
+            let entries: Vec<(String, Vec<f32>)> = misses
+                .iter()
+                .map(|&i| keys[i].clone())
+                .zip(embeddings)
+                .collect();
+            cache.put_many(&entries)?;
+
+            for (&i, (_, embedding)) in misses.iter().zip(entries) {
+                results[i] = Some(embedding);
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
     /// Blocking version of embed for synchronous contexts
     pub fn embed_blocking(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
         if texts.is_empty() {
             return Ok(Vec::new());
         }
 
-        let req = EmbeddingRequest { texts };
+        let req = self.codec.encode(&texts);
+        let mut retry = 0;
+        let embeddings = loop {
+            match self.send_blocking(&req) {
+                Err(e) if e.is_retryable() && retry + 1 < self.retry.max_attempts => {
+                    std::thread::sleep(self.retry.delay(retry));
+                    retry += 1;
+                }
+                result => break result?,
+            }
+        };
+        check_count(texts.len(), embeddings)
+    }
+
+    /// Send a single embedding request, blocking
+    fn send_blocking(&self, req: &serde_json::Value) -> Result<Vec<Vec<f32>>, EmbedError> {
         let response = self
-            .blocking_http
-            .post(format!("{}/embed", self.endpoint))
-            .json(&req)
-            .send()?;
+            .blocking_http()
+            .post(format!("{}{}", self.endpoint, self.path))
+            .json(req)
+            .send()
+            .map_err(|e| self.request_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -110,9 +343,9 @@ impl EmbeddingClient {
             });
         }
 
-        let res: EmbeddingResponse = response.json()?;
+        let body = response.text().map_err(|e| self.request_error(e))?;
 
-        Ok(res.embeddings)
+        Ok(self.finish(self.decode(&body)?))
     }
 
     /// Blocking version of embed_chunks for synchronous contexts
@@ -120,3 +353,23 @@ impl EmbeddingClient {
         self.embed_blocking(texts)
     }
 }
+
+/// Reject a response whose vector count doesn't match the texts sent
+fn check_count(expected: usize, embeddings: Vec<Vec<f32>>) -> Result<Vec<Vec<f32>>, EmbedError> {
+    if embeddings.len() != expected {
+        return Err(EmbedError::InvalidResponse(format!(
+            "expected {} embeddings, got {}",
+            expected,
+            embeddings.len()
+        )));
+    }
+    Ok(embeddings)
+}
+
+/// The first `BODY_SNIPPET_CHARS` characters of `body`, marked if cut
+fn body_snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
@@ -0,0 +1,90 @@
+// codec.rs - request/response shapes for different embedding servers
+use crate::embedder::client::EmbedError;
+use crate::embedder::types::{EmbeddingRequest, EmbeddingResponse};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Maps texts to a server's JSON request body and its JSON response back
+/// to one vector per text
+pub trait EmbeddingCodec: Send + Sync {
+    fn encode(&self, texts: &[String]) -> Value;
+
+    fn decode(&self, body: Value) -> Result<Vec<Vec<f32>>, EmbedError>;
+}
+
+/// The bundled Python server: `{"texts": [...]}` → `{"embeddings": [...]}`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultCodec;
+
+impl EmbeddingCodec for DefaultCodec {
+    fn encode(&self, texts: &[String]) -> Value {
+        json!(EmbeddingRequest {
+            texts: texts.to_vec()
+        })
+    }
+
+    fn decode(&self, body: Value) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let res: EmbeddingResponse = serde_json::from_value(body).map_err(invalid)?;
+        Ok(res.embeddings)
+    }
+}
+
+/// Hugging Face Text Embeddings Inference (`POST /embed`):
+/// `{"inputs": [...]}` → `[[...], ...]`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TeiCodec;
+
+impl EmbeddingCodec for TeiCodec {
+    fn encode(&self, texts: &[String]) -> Value {
+        json!({ "inputs": texts })
+    }
+
+    fn decode(&self, body: Value) -> Result<Vec<Vec<f32>>, EmbedError> {
+        serde_json::from_value(body).map_err(invalid)
+    }
+}
+
+/// OpenAI-compatible servers (`POST /v1/embeddings`, also served by
+/// Ollama and vLLM): `{"model": ..., "input": [...]}` →
+/// `{"data": [{"index": i, "embedding": [...]}, ...]}`
+#[derive(Debug, Clone)]
+pub struct OpenAiCodec {
+    pub model: String,
+}
+
+impl OpenAiCodec {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+        }
+    }
+}
+
+impl EmbeddingCodec for OpenAiCodec {
+    fn encode(&self, texts: &[String]) -> Value {
+        json!({ "model": self.model, "input": texts })
+    }
+
+    fn decode(&self, body: Value) -> Result<Vec<Vec<f32>>, EmbedError> {
+        #[derive(Deserialize)]
+        struct Item {
+            index: usize,
+            embedding: Vec<f32>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            data: Vec<Item>,
+        }
+
+        // Entries carry their input index and aren't guaranteed to be sorted
+        let mut data = serde_json::from_value::<Response>(body)
+            .map_err(invalid)?
+            .data;
+        data.sort_by_key(|item| item.index);
+        Ok(data.into_iter().map(|item| item.embedding).collect())
+    }
+}
+
+fn invalid(e: serde_json::Error) -> EmbedError {
+    EmbedError::InvalidResponse(e.to_string())
+}
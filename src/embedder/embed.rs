@@ -0,0 +1,51 @@
+// embed.rs - the backend-agnostic embedding interface
+use crate::chunker::Chunk;
+use crate::embedder::batcher::Batcher;
+use crate::embedder::client::{EmbedError, EmbeddingClient};
+
+/// Anything that turns texts into embedding vectors
+///
+/// Implementations return one vector per input text, in input order.
+pub trait Embed {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError>;
+}
+
+impl Embed for EmbeddingClient {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
+        self.embed_blocking(texts)
+    }
+}
+
+/// Embed the text of each chunk, `batch_size` chunks per call
+///
+/// Returns one vector per chunk, in order, regardless of the backend.
+pub fn embed_chunks(
+    embedder: &dyn Embed,
+    chunks: &[Chunk],
+    batch_size: usize,
+) -> Result<Vec<Vec<f32>>, EmbedError> {
+    let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+    let mut embeddings = Vec::with_capacity(texts.len());
+
+    for batch in Batcher::new(batch_size.max(1)).split(&texts) {
+        let batch_embeddings = embedder.embed(batch.to_vec())?;
+        if batch_embeddings.len() != batch.len() {
+            return Err(EmbedError::InvalidResponse(format!(
+                "expected {} embeddings, got {}",
+                batch.len(),
+                batch_embeddings.len()
+            )));
+        }
+        embeddings.extend(batch_embeddings);
+    }
+
+    Ok(embeddings)
+}
+
+/// Scale a vector to unit length (zero vectors are left alone)
+pub(crate) fn l2_normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
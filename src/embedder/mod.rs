@@ -1,12 +1,24 @@
 pub mod batcher;
+pub mod cache;
 pub mod client;
+pub mod codec;
+pub mod embed;
 pub mod model;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod retry;
 pub mod types;
 
 #[cfg(test)]
 mod tests;
 
 pub use batcher::Batcher;
+pub use cache::{EmbeddingCache, SqliteEmbeddingCache, cache_key};
 pub use client::{EmbedError, EmbeddingClient};
+pub use codec::{DefaultCodec, EmbeddingCodec, OpenAiCodec, TeiCodec};
+pub use embed::{Embed, embed_chunks};
 pub use model::EmbeddingModelInfo;
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxEmbedder;
+pub use retry::RetryPolicy;
 pub use types::{EmbeddingRequest, EmbeddingResponse};
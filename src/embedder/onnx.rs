@@ -0,0 +1,158 @@
+// onnx.rs - in-process embedding backend (no Python server)
+use crate::embedder::client::EmbedError;
+use crate::embedder::embed::{Embed, l2_normalize};
+use ort::session::Session;
+use ort::value::Tensor;
+use std::path::Path;
+use std::sync::Mutex;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+/// Longest input, in tokens, passed to the model (EmbeddingGemma's context)
+const MAX_SEQUENCE_LEN: usize = 2048;
+
+/// Embeds texts with a local ONNX model and its Hugging Face tokenizer
+///
+/// Works with exports that output pooled `sentence_embedding`s (as the
+/// EmbeddingGemma ONNX export does) as well as plain encoders whose
+/// `last_hidden_state` is mean-pooled here. Vectors are L2-normalized, like
+/// the embedding server's output. ONNX Runtime is loaded dynamically; set
+/// `ORT_DYLIB_PATH` if it isn't on the library path.
+pub struct OnnxEmbedder {
+    /// `Session::run` needs exclusive access
+    session: Mutex<Session>,
+    tokenizer: Tokenizer,
+}
+
+impl OnnxEmbedder {
+    pub fn new(
+        model_path: impl AsRef<Path>,
+        tokenizer_path: impl AsRef<Path>,
+    ) -> Result<Self, EmbedError> {
+        let session = Session::builder()
+            .and_then(|builder| builder.commit_from_file(model_path))
+            .map_err(model_error)?;
+
+        let mut tokenizer = Tokenizer::from_file(tokenizer_path).map_err(model_error)?;
+        if tokenizer.get_padding().is_none() {
+            tokenizer.with_padding(Some(PaddingParams::default()));
+        }
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_SEQUENCE_LEN,
+                ..Default::default()
+            }))
+            .map_err(model_error)?;
+
+        Ok(Self {
+            session: Mutex::new(session),
+            tokenizer,
+        })
+    }
+
+    /// Load `model.onnx` (or `onnx/model.onnx`) and `tokenizer.json` from a
+    /// downloaded model directory
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, EmbedError> {
+        let dir = dir.as_ref();
+        let model_path = [dir.join("model.onnx"), dir.join("onnx/model.onnx")]
+            .into_iter()
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                EmbedError::Model(format!("no model.onnx found in {}", dir.display()))
+            })?;
+
+        Self::new(model_path, dir.join("tokenizer.json"))
+    }
+}
+
+impl Embed for OnnxEmbedder {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(model_error)?;
+        let batch = encodings.len();
+        let seq_len = encodings[0].get_ids().len();
+
+        let collect = |field: fn(&tokenizers::Encoding) -> &[u32]| -> Vec<i64> {
+            encodings
+                .iter()
+                .flat_map(|e| field(e).iter().map(|&v| i64::from(v)))
+                .collect()
+        };
+        let ids = collect(tokenizers::Encoding::get_ids);
+        let mask = collect(tokenizers::Encoding::get_attention_mask);
+        let type_ids = collect(tokenizers::Encoding::get_type_ids);
+
+        let shape = [batch, seq_len];
+        let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        let wants_type_ids = session.inputs.iter().any(|i| i.name == "token_type_ids");
+
+        let mut inputs = ort::inputs! {
+            "input_ids" => Tensor::from_array((shape, ids)).map_err(model_error)?,
+            "attention_mask" => Tensor::from_array((shape, mask.clone())).map_err(model_error)?,
+        };
+        if wants_type_ids {
+            inputs.push((
+                "token_type_ids".into(),
+                Tensor::from_array((shape, type_ids))
+                    .map_err(model_error)?
+                    .into(),
+            ));
+        }
+
+        let outputs = session.run(inputs).map_err(model_error)?;
+
+        let mut embeddings = if let Some(pooled) = outputs.get("sentence_embedding") {
+            let (dims, data) = pooled.try_extract_tensor::<f32>().map_err(model_error)?;
+            let dim = *dims.last().unwrap_or(&0) as usize;
+            data.chunks_exact(dim.max(1)).map(<[f32]>::to_vec).collect()
+        } else {
+            let (dims, data) = outputs[0]
+                .try_extract_tensor::<f32>()
+                .map_err(model_error)?;
+            let dim = *dims.last().unwrap_or(&0) as usize;
+            mean_pool(data, &mask, batch, seq_len, dim)
+        };
+
+        for embedding in &mut embeddings {
+            l2_normalize(embedding);
+        }
+
+        Ok(embeddings)
+    }
+}
+
+/// Average token vectors of shape [batch, seq_len, dim], skipping padding
+fn mean_pool(
+    data: &[f32],
+    mask: &[i64],
+    batch: usize,
+    seq_len: usize,
+    dim: usize,
+) -> Vec<Vec<f32>> {
+    (0..batch)
+        .map(|b| {
+            let mut sum = vec![0.0; dim];
+            let mut count = 0.0;
+            for t in 0..seq_len {
+                if mask[b * seq_len + t] == 0 {
+                    continue;
+                }
+                let token = &data[(b * seq_len + t) * dim..][..dim];
+                for (s, v) in sum.iter_mut().zip(token) {
+                    *s += v;
+                }
+                count += 1.0;
+            }
+            sum.iter().map(|s| s / f32::max(count, 1.0)).collect()
+        })
+        .collect()
+}
+
+fn model_error(e: impl std::fmt::Display) -> EmbedError {
+    EmbedError::Model(e.to_string())
+}
@@ -0,0 +1,44 @@
+// retry.rs - backoff policy for transient server failures
+use rand::Rng;
+use std::time::Duration;
+
+/// How often and how patiently to retry failed embedding requests
+///
+/// Only connection failures and 5xx responses are retried; the delay
+/// doubles after each attempt, with random jitter so that concurrent
+/// requests don't retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first (1 disables retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+        }
+    }
+
+    /// A policy that gives up after the first failure
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Delay before retry number `retry` (0-based): `base_delay * 2^retry`,
+    /// scaled by a random factor in 50-100%
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(1 << retry.min(16));
+        backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts starting at 500ms, enough to ride out a model load
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(500))
+    }
+}
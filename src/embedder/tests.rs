@@ -1,4 +1,6 @@
 use super::*;
+use crate::testutil::{MockEmbedServer, StubResponse, StubServer};
+use std::time::Duration;
 
 #[test]
 fn test_batching_small() {
@@ -37,6 +39,32 @@ fn test_batching_empty() {
     assert_eq!(batches.len(), 0);
 }
 
+#[test]
+fn test_batching_by_tokens() {
+    let batcher = Batcher::new(100);
+    // 10, 10, 20 and 5 estimated tokens
+    let items: Vec<String> = [40, 40, 80, 20].iter().map(|&n| "x".repeat(n)).collect();
+
+    let batches = batcher.split_by_tokens(&items, 25);
+    let sizes: Vec<usize> = batches.iter().map(|b| b.len()).collect();
+    assert_eq!(sizes, vec![2, 2]);
+
+    let batches = batcher.split_by_tokens(&items, 20);
+    let sizes: Vec<usize> = batches.iter().map(|b| b.len()).collect();
+    assert_eq!(sizes, vec![2, 1, 1]);
+}
+
+#[test]
+fn test_batching_by_tokens_oversized_item_alone() {
+    let batcher = Batcher::new(100);
+    let items = vec!["a".to_string(), "x".repeat(400), "b".to_string()];
+
+    let batches = batcher.split_by_tokens(&items, 10);
+    assert_eq!(batches.len(), 3);
+    assert_eq!(batches[1], &items[1..2]);
+    assert!(batcher.split_by_tokens(&[], 10).is_empty());
+}
+
 #[test]
 fn test_model_info_default() {
     let model = EmbeddingModelInfo::default();
@@ -61,50 +89,517 @@ async fn test_client_empty_input() {
     assert_eq!(result.unwrap().len(), 0);
 }
 
-// Integration test - requires Python server running
 #[tokio::test]
-#[ignore]
 async fn test_client_round_trip() {
-    let client = EmbeddingClient::new("http://localhost:18115");
+    let server = MockEmbedServer::start();
+    let client = EmbeddingClient::new(server.endpoint());
     let texts = vec![
         "This is a test sentence.".to_string(),
         "Another test sentence here.".to_string(),
     ];
 
-    let result = client.embed(texts.clone()).await;
-    assert!(result.is_ok());
+    let embeddings = client.embed(texts.clone()).await.unwrap();
 
-    let embeddings = result.unwrap();
     assert_eq!(embeddings.len(), 2);
-
-    // Each embedding should have 768 dimensions (for gemma-300m)
-    assert_eq!(embeddings[0].len(), 768);
-    assert_eq!(embeddings[1].len(), 768);
-
-    // Vectors should be normalized (L2 norm ~= 1.0)
+    assert_eq!(embeddings[0].len(), client.model().dim);
+    assert_eq!(embeddings[0], MockEmbedServer::embedding(&texts[0], 768));
+    assert_ne!(embeddings[0], embeddings[1]);
     let norm: f32 = embeddings[0].iter().map(|x| x * x).sum::<f32>().sqrt();
     assert!((norm - 1.0).abs() < 0.01, "Vector should be normalized");
 }
 
-// Integration test - test batching with client
 #[tokio::test]
-#[ignore]
 async fn test_batched_embedding() {
-    let client = EmbeddingClient::new("http://localhost:18115");
-    let batcher = Batcher::new(2);
-
+    let server = MockEmbedServer::with_dim(8);
+    let client = EmbeddingClient::new(server.endpoint());
     let texts: Vec<String> = (0..5)
         .map(|i| format!("Test sentence number {}", i))
         .collect();
 
-    let mut all_embeddings = Vec::new();
+    let embeddings = client.embed_batched(texts.clone(), 2, 2).await.unwrap();
+
+    assert_eq!(server.request_count(), 3);
+    let expected: Vec<Vec<f32>> = texts
+        .iter()
+        .map(|t| MockEmbedServer::embedding(t, 8))
+        .collect();
+    assert_eq!(embeddings, expected);
+}
+
+#[test]
+fn test_mock_server_rejects_malformed_requests() {
+    let server = MockEmbedServer::start();
+    let client = EmbeddingClient::new(server.endpoint()).with_codec(TeiCodec);
+
+    let result = client.embed_blocking(vec!["a".to_string()]);
+
+    assert!(matches!(
+        result,
+        Err(EmbedError::ServerError { status: 400, .. })
+    ));
+}
+
+/// Embed each text as a one-element vector holding its numeric value
+fn echo_numbers(_path: &str, request: &str) -> StubResponse {
+    let request: serde_json::Value = serde_json::from_str(request).unwrap();
+    let embeddings: Vec<Vec<f32>> = request["texts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| vec![t.as_str().unwrap().parse().unwrap()])
+        .collect();
+    StubResponse::json(
+        200,
+        serde_json::json!({ "embeddings": embeddings }).to_string(),
+    )
+}
+
+#[test]
+fn test_client_blocking_empty_input() {
+    let client = EmbeddingClient::new("http://localhost:18115");
+    let result = client.embed_chunks_blocking(vec![]);
+    assert_eq!(result.unwrap().len(), 0);
+}
+
+#[test]
+fn test_client_blocking_round_trip() {
+    let server = StubServer::with_responses(vec![StubResponse::json(
+        200,
+        r#"{"embeddings": [[1.0, 0.0], [0.0, 1.0]]}"#,
+    )]);
+    let client = EmbeddingClient::new(server.endpoint());
+
+    // No async runtime is needed on the calling side
+    let embeddings = client
+        .embed_chunks_blocking(vec!["a".to_string(), "b".to_string()])
+        .unwrap();
 
-    for batch in batcher.split(&texts) {
-        let batch_texts = batch.iter().map(|s| s.to_string()).collect();
-        let embeddings = client.embed(batch_texts).await.unwrap();
-        all_embeddings.extend(embeddings);
+    assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+}
+
+#[test]
+fn test_client_blocking_server_error() {
+    let server = StubServer::with_responses(vec![StubResponse::json(400, "bad input")]);
+    let client = EmbeddingClient::new(server.endpoint());
+
+    let result = client.embed_chunks_blocking(vec!["a".to_string()]);
+
+    assert!(matches!(
+        result,
+        Err(EmbedError::ServerError { status: 400, ref body }) if body == "bad input"
+    ));
+}
+
+#[test]
+fn test_client_quotes_undecodable_body() {
+    let page = format!(
+        "<html><body>502 Bad Gateway{}</body></html>",
+        " ".repeat(1000)
+    );
+    let server = StubServer::with_responses(vec![
+        StubResponse::json(200, page),
+        StubResponse::json(200, r#"{"vectors": [[1.0]]}"#),
+    ]);
+    let client = EmbeddingClient::new(server.endpoint());
+
+    let Err(EmbedError::InvalidResponse(message)) = client.embed_blocking(vec!["a".to_string()])
+    else {
+        panic!("expected an invalid response error");
+    };
+    assert!(message.contains("<html><body>502 Bad Gateway"));
+    assert!(message.ends_with("..."));
+    assert!(message.len() < 500);
+
+    let Err(EmbedError::InvalidResponse(message)) = client.embed_blocking(vec!["a".to_string()])
+    else {
+        panic!("expected an invalid response error");
+    };
+    assert!(message.contains(r#"{"vectors": [[1.0]]}"#));
+}
+
+#[tokio::test]
+async fn test_embed_batched_preserves_order() {
+    let server = StubServer::start(echo_numbers);
+    let client = EmbeddingClient::new(server.endpoint());
+    let texts: Vec<String> = (0..23).map(|i| i.to_string()).collect();
+
+    let embeddings = client.embed_batched(texts, 4, 3).await.unwrap();
+
+    let expected: Vec<Vec<f32>> = (0..23).map(|i| vec![i as f32]).collect();
+    assert_eq!(embeddings, expected);
+}
+
+#[tokio::test]
+async fn test_embed_batched_rejects_short_batch() {
+    let server =
+        StubServer::with_responses(vec![StubResponse::json(200, r#"{"embeddings": [[1.0]]}"#)]);
+    let client = EmbeddingClient::new(server.endpoint());
+
+    let result = client
+        .embed_batched(vec!["a".to_string(), "b".to_string()], 2, 1)
+        .await;
+
+    assert!(matches!(result, Err(EmbedError::InvalidResponse(_))));
+}
+
+#[tokio::test]
+async fn test_embed_rejects_vector_count_mismatch() {
+    let server = StubServer::with_responses(vec![
+        StubResponse::json(200, r#"{"embeddings": [[1.0]]}"#),
+        StubResponse::json(200, r#"{"embeddings": [[1.0], [2.0], [3.0]]}"#),
+    ]);
+    let client = EmbeddingClient::new(server.endpoint());
+    let texts = vec!["a".to_string(), "b".to_string()];
+
+    for _ in 0..2 {
+        let result = client.embed(texts.clone()).await;
+        assert!(matches!(
+            result,
+            Err(EmbedError::InvalidResponse(ref message)) if message.contains("expected 2 embeddings")
+        ));
     }
+}
+
+#[test]
+fn test_embed_blocking_rejects_vector_count_mismatch() {
+    let server =
+        StubServer::with_responses(vec![StubResponse::json(200, r#"{"embeddings": [[1.0]]}"#)]);
+    let client = EmbeddingClient::new(server.endpoint());
+
+    let result = client.embed_blocking(vec!["a".to_string(), "b".to_string()]);
+
+    assert!(matches!(result, Err(EmbedError::InvalidResponse(_))));
+}
+
+/// Fail the first `failures` requests with `status`, then echo numbers
+fn fail_then_echo(failures: usize, status: u16) -> StubServer {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let failed = AtomicUsize::new(0);
+    StubServer::start(move |path, body| {
+        if failed.fetch_add(1, Ordering::SeqCst) < failures {
+            StubResponse::json(status, "unavailable")
+        } else {
+            echo_numbers(path, body)
+        }
+    })
+}
+
+#[test]
+fn test_retry_policy_delay_grows() {
+    let policy = RetryPolicy::new(5, Duration::from_millis(100));
+
+    for retry in 0..4 {
+        let full = Duration::from_millis(100 << retry);
+        let delay = policy.delay(retry);
+        assert!(delay >= full / 2 && delay <= full, "{delay:?} vs {full:?}");
+    }
+}
+
+#[test]
+fn test_retry_policy_at_least_one_attempt() {
+    assert_eq!(RetryPolicy::new(0, Duration::ZERO).max_attempts, 1);
+    assert_eq!(RetryPolicy::none().max_attempts, 1);
+}
+
+#[tokio::test]
+async fn test_client_retries_server_errors() {
+    let server = fail_then_echo(2, 503);
+    let client = EmbeddingClient::new(server.endpoint()).with_retry(3, Duration::from_millis(1));
+
+    let embeddings = client.embed(vec!["7".to_string()]).await.unwrap();
+
+    assert_eq!(embeddings, vec![vec![7.0]]);
+    assert_eq!(server.request_count(), 3);
+}
+
+#[test]
+fn test_client_gives_up_after_max_attempts() {
+    let server = fail_then_echo(usize::MAX, 503);
+    let client = EmbeddingClient::new(server.endpoint()).with_retry(3, Duration::from_millis(1));
+
+    let result = client.embed_blocking(vec!["7".to_string()]);
+
+    assert!(matches!(
+        result,
+        Err(EmbedError::ServerError { status: 503, .. })
+    ));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[test]
+fn test_client_does_not_retry_client_errors() {
+    let server = fail_then_echo(1, 422);
+    let client = EmbeddingClient::new(server.endpoint()).with_retry(3, Duration::from_millis(1));
+
+    let result = client.embed_blocking(vec!["7".to_string()]);
+
+    assert!(matches!(
+        result,
+        Err(EmbedError::ServerError { status: 422, .. })
+    ));
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_client_reports_timeout() {
+    // Connections queue in the backlog but are never answered
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let timeout = Duration::from_millis(100);
+    let client =
+        EmbeddingClient::with_timeout(endpoint, timeout).with_retry_policy(RetryPolicy::none());
+
+    let result = client.embed(vec!["a".to_string()]).await;
+
+    assert!(matches!(result, Err(EmbedError::Timeout(t)) if t == timeout));
+}
+
+#[test]
+fn test_client_blocking_reports_timeout() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let timeout = Duration::from_millis(100);
+    let client =
+        EmbeddingClient::with_timeout(endpoint, timeout).with_retry_policy(RetryPolicy::none());
+
+    let result = client.embed_blocking(vec!["a".to_string()]);
+
+    assert!(matches!(result, Err(EmbedError::Timeout(t)) if t == timeout));
+}
+
+#[test]
+fn test_cache_key_depends_on_model_and_text() {
+    let key = cache_key("model-a", "text");
+    assert_eq!(key.len(), 64);
+    assert_eq!(key, cache_key("model-a", "text"));
+    assert_ne!(key, cache_key("model-b", "text"));
+    assert_ne!(key, cache_key("model-a", "other"));
+    assert_ne!(cache_key("ab", "c"), cache_key("a", "bc"));
+}
+
+#[test]
+fn test_sqlite_cache_round_trip() {
+    let cache = SqliteEmbeddingCache::in_memory().unwrap();
+
+    assert_eq!(cache.get("missing").unwrap(), None);
+    cache.put("k1", &[1.5, -2.0]).unwrap();
+    cache
+        .put_many(&[("k2".to_string(), vec![3.0]), ("k1".to_string(), vec![4.0])])
+        .unwrap();
+
+    assert_eq!(cache.get("k1").unwrap(), Some(vec![4.0]));
+    assert_eq!(cache.get("k2").unwrap(), Some(vec![3.0]));
+}
+
+#[test]
+fn test_sqlite_cache_persists_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embeddings.sqlite");
+
+    SqliteEmbeddingCache::open(&path)
+        .unwrap()
+        .put("k", &[0.25])
+        .unwrap();
+
+    let reopened = SqliteEmbeddingCache::open(&path).unwrap();
+    assert_eq!(reopened.get("k").unwrap(), Some(vec![0.25]));
+}
+
+#[tokio::test]
+async fn test_embed_cached_only_sends_misses() {
+    use std::sync::{Arc, Mutex};
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+    let server = StubServer::start(move |path, body| {
+        log.lock().unwrap().push(body.to_string());
+        echo_numbers(path, body)
+    });
+    let client = EmbeddingClient::new(server.endpoint());
+    let cache = SqliteEmbeddingCache::in_memory().unwrap();
+
+    let first = client
+        .embed_cached(vec!["1".to_string(), "2".to_string()], &cache)
+        .await
+        .unwrap();
+    let second = client
+        .embed_cached(
+            vec!["2".to_string(), "3".to_string(), "1".to_string()],
+            &cache,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(first, vec![vec![1.0], vec![2.0]]);
+    assert_eq!(second, vec![vec![2.0], vec![3.0], vec![1.0]]);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1], r#"{"texts":["3"]}"#);
+}
+
+#[tokio::test]
+async fn test_embed_cached_all_hits_skips_server() {
+    let client = EmbeddingClient::new("http://127.0.0.1:9");
+    let cache = SqliteEmbeddingCache::in_memory().unwrap();
+    let key = cache_key(&client.model().name, "hello");
+    cache.put(&key, &[0.5]).unwrap();
+
+    let embeddings = client
+        .embed_cached(vec!["hello".to_string()], &cache)
+        .await
+        .unwrap();
+
+    assert_eq!(embeddings, vec![vec![0.5]]);
+}
+
+#[test]
+fn test_client_implements_embed() {
+    let server = StubServer::start(echo_numbers);
+    let client = EmbeddingClient::new(server.endpoint());
+    let backend: &dyn Embed = &client;
+
+    let embeddings = backend.embed(vec!["4".to_string()]).unwrap();
+
+    assert_eq!(embeddings, vec![vec![4.0]]);
+}
+
+#[cfg(feature = "onnx")]
+#[test]
+fn test_onnx_from_dir_requires_model() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let result = OnnxEmbedder::from_dir(dir.path());
+
+    assert!(matches!(result, Err(EmbedError::Model(_))));
+}
+
+/// Embeds each text as its length, recording the batch sizes it sees
+struct MockEmbedder {
+    batches: std::sync::Mutex<Vec<usize>>,
+}
+
+impl Embed for MockEmbedder {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
+        self.batches.lock().unwrap().push(texts.len());
+        Ok(texts.iter().map(|t| vec![t.len() as f32]).collect())
+    }
+}
+
+fn make_chunk(text: &str) -> crate::chunker::Chunk {
+    crate::chunker::chunk_semantic_units_heuristic(
+        vec![crate::parser::SemanticUnit {
+            text: text.to_string(),
+            start_offset: 0,
+            end_offset: text.len(),
+            kind: crate::parser::SemanticKind::Unknown,
+        }],
+        100,
+    )
+    .remove(0)
+}
+
+#[test]
+fn test_embed_chunks_with_mock_backend() {
+    let embedder = MockEmbedder {
+        batches: std::sync::Mutex::new(Vec::new()),
+    };
+    let chunks: Vec<_> = ["a", "bb", "ccc", "dddd", "eeeee"]
+        .iter()
+        .map(|t| make_chunk(t))
+        .collect();
+
+    let embeddings = embed_chunks(&embedder, &chunks, 2).unwrap();
+
+    let expected: Vec<Vec<f32>> = (1..=5).map(|n| vec![n as f32]).collect();
+    assert_eq!(embeddings, expected);
+    assert_eq!(*embedder.batches.lock().unwrap(), vec![2, 2, 1]);
+}
+
+#[test]
+fn test_embed_chunks_rejects_missing_vectors() {
+    struct DropsOne;
+    impl Embed for DropsOne {
+        fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
+            Ok(vec![vec![0.0]; texts.len() - 1])
+        }
+    }
+
+    let result = embed_chunks(&DropsOne, &[make_chunk("a"), make_chunk("b")], 8);
+
+    assert!(matches!(result, Err(EmbedError::InvalidResponse(_))));
+}
+
+#[tokio::test]
+async fn test_client_normalizes_when_enabled() {
+    let response = r#"{"embeddings": [[3.0, 4.0], [0.0, 0.0]]}"#.to_string();
+    let server = StubServer::with_responses(vec![
+        StubResponse::json(200, response.clone()),
+        StubResponse::json(200, response),
+    ]);
+
+    let raw = EmbeddingClient::new(server.endpoint());
+    let normalized = EmbeddingClient::new(server.endpoint()).with_normalize(true);
+    let texts = vec!["a".to_string(), "b".to_string()];
+
+    assert_eq!(
+        raw.embed(texts.clone()).await.unwrap(),
+        vec![vec![3.0, 4.0], vec![0.0, 0.0]]
+    );
+    assert_eq!(
+        normalized.embed(texts).await.unwrap(),
+        vec![vec![0.6, 0.8], vec![0.0, 0.0]]
+    );
+}
+
+#[test]
+fn test_default_codec_shape() {
+    let body = DefaultCodec.encode(&["a".to_string()]);
+    assert_eq!(body, serde_json::json!({ "texts": ["a"] }));
+
+    let decoded = DefaultCodec.decode(serde_json::json!({ "embeddings": [[1.0]] }));
+    assert_eq!(decoded.unwrap(), vec![vec![1.0]]);
+
+    let invalid = DefaultCodec.decode(serde_json::json!({ "vectors": [] }));
+    assert!(matches!(invalid, Err(EmbedError::InvalidResponse(_))));
+}
+
+#[test]
+fn test_openai_codec_orders_by_index() {
+    let codec = OpenAiCodec::new("nomic-embed-text");
+    assert_eq!(
+        codec.encode(&["a".to_string(), "b".to_string()]),
+        serde_json::json!({ "model": "nomic-embed-text", "input": ["a", "b"] })
+    );
+
+    let body = serde_json::json!({
+        "object": "list",
+        "data": [
+            { "object": "embedding", "index": 1, "embedding": [2.0] },
+            { "object": "embedding", "index": 0, "embedding": [1.0] },
+        ],
+    });
+    assert_eq!(codec.decode(body).unwrap(), vec![vec![1.0], vec![2.0]]);
+}
+
+#[tokio::test]
+async fn test_client_with_path_and_tei_codec() {
+    let server = StubServer::start(|path, body| {
+        let request: serde_json::Value = serde_json::from_str(body).unwrap();
+        if path != "/v2/embed" || request["inputs"] != serde_json::json!(["x", "y"]) {
+            return StubResponse::json(404, "");
+        }
+        StubResponse::json(200, "[[0.5], [1.5]]")
+    });
+    let client = EmbeddingClient::new(server.endpoint())
+        .with_path("v2/embed")
+        .with_codec(TeiCodec);
+
+    let embeddings = client
+        .embed(vec!["x".to_string(), "y".to_string()])
+        .await
+        .unwrap();
 
-    assert_eq!(all_embeddings.len(), 5);
-    assert_eq!(all_embeddings[0].len(), 768);
+    assert_eq!(embeddings, vec![vec![0.5], vec![1.5]]);
 }
@@ -1,12 +1,12 @@
 // the python contract
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct EmbeddingRequest {
     pub texts: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct EmbeddingResponse {
     pub embeddings: Vec<Vec<f32>>,
 }
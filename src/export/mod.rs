@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests;
+
+use crate::chunker::Chunk;
+use crate::clusterer::ClusterResult;
+use std::io::{self, Write};
+
+/// Write one JSON object per chunk, each on its own line
+pub fn write_chunks_jsonl(mut writer: impl Write, chunks: &[Chunk]) -> io::Result<()> {
+    for chunk in chunks {
+        serde_json::to_writer(&mut writer, chunk)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Write a clustering as a single JSON document
+pub fn write_clusters_json(mut writer: impl Write, result: &ClusterResult) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, result)?;
+    writer.flush()
+}
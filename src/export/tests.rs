@@ -0,0 +1,70 @@
+use super::*;
+use crate::chunker::chunk_semantic_units_heuristic;
+use crate::clusterer::kmeans;
+use crate::parser::{SemanticKind, SemanticUnit};
+use serde_json::Value;
+
+fn chunks() -> Vec<Chunk> {
+    ["fn a() {}", "fn b() {}"]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let mut chunk = chunk_semantic_units_heuristic(
+                vec![SemanticUnit {
+                    text: text.to_string(),
+                    start_offset: 0,
+                    end_offset: text.len(),
+                    kind: SemanticKind::Function,
+                }],
+                100,
+            )
+            .remove(0);
+            chunk.id = i as u32;
+            chunk
+        })
+        .collect()
+}
+
+#[test]
+fn test_write_chunks_jsonl() {
+    let mut out = Vec::new();
+
+    write_chunks_jsonl(&mut out, &chunks()).unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["id"], 1);
+    assert_eq!(lines[1]["text"], "fn b() {}");
+    assert_eq!(lines[1]["metadata"]["kinds"][0], "Function");
+    assert_eq!(lines[1]["metadata"]["end_offset"], 9);
+}
+
+#[test]
+fn test_write_chunks_jsonl_empty() {
+    let mut out = Vec::new();
+
+    write_chunks_jsonl(&mut out, &[]).unwrap();
+
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_write_clusters_json() {
+    let result = kmeans(&[vec![1.0, 0.0], vec![0.0, 1.0]], 2, 10, 42);
+    let mut out = Vec::new();
+
+    write_clusters_json(&mut out, &result).unwrap();
+
+    let json: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["clusters"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        json["clusters"][0]["chunk_ids"].as_array().unwrap().len(),
+        1
+    );
+    assert!(json["iterations"].is_u64());
+    assert!(json["inertia"].is_number());
+}
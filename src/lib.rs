@@ -2,20 +2,51 @@
 pub mod chunker;
 pub mod clusterer;
 pub mod embedder;
+pub mod export;
 pub mod parser;
+pub mod pipeline;
 pub mod sandbox;
 pub mod security;
+pub mod summarizer;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
 
 // Re-export main types for convenience
-pub use sandbox::{FileEntry, Sandbox, SandboxBuilder, SandboxError};
+pub use sandbox::{
+    FileEntry, GitRef, IngestPlan, IngestProgress, IngestReport, Sandbox, SandboxBuilder,
+    SandboxError, SkipReason, UpdateReport,
+};
 pub use security::PathSanitizer;
 
 pub use parser::{
-    FileMetadata, ParseResult, Parser, ParserRegistry, SemanticKind, SemanticUnit, UnknownParser,
+    FileMetadata, JsonParser, LineEnding, MarkdownParser, ParseResult, ParseWarning, ParsedSymbol,
+    Parser, ParserRegistry, PythonParser, RustParser, SemanticKind, SemanticUnit, TomlParser,
+    UnknownParser, UnknownParserWithThreshold, YamlParser,
+};
+
+pub use chunker::{
+    Chunk, ChunkId, ChunkMetadata, ChunkOptions, DEFAULT_MAX_TOKENS, HeuristicEstimator,
+    TokenEstimator, chunk_parse_result, chunk_parse_result_opts, chunk_parse_results,
+    chunk_parse_results_opts, chunk_semantic_units, chunk_semantic_units_heuristic,
+    chunk_semantic_units_opts, dedupe_chunks, estimate_tokens,
+};
+
+#[cfg(feature = "onnx")]
+pub use embedder::OnnxEmbedder;
+pub use embedder::{
+    Batcher, DefaultCodec, Embed, EmbedError, EmbeddingCache, EmbeddingClient, EmbeddingCodec,
+    EmbeddingModelInfo, OpenAiCodec, RetryPolicy, SqliteEmbeddingCache, TeiCodec, embed_chunks,
 };
 
-pub use chunker::{Chunk, ChunkId, ChunkMetadata, DEFAULT_MAX_TOKENS, chunk_semantic_units};
+pub use export::{write_chunks_jsonl, write_clusters_json};
+
+pub use summarizer::{DocumenterClient, SseDecoder, SummarizerError};
 
-pub use embedder::{Batcher, EmbedError, EmbeddingClient, EmbeddingModelInfo};
+pub use clusterer::{
+    Cluster, ClusterResult, InitMethod, KmeansConfig, Metric, NOISE_CLUSTER_ID, cluster_chunks,
+    dbscan, kmeans, kmeans_with_config, silhouette_score,
+};
 
-pub use clusterer::{Cluster, ClusterResult, kmeans};
+pub use pipeline::{
+    Pipeline, PipelineBuilder, PipelineError, PipelineResult, PipelineStats, default_cluster_count,
+};
@@ -1,15 +1,26 @@
-use doctown_v10::{
-    DEFAULT_MAX_TOKENS, EmbeddingClient, ParserRegistry, SandboxBuilder, SandboxError,
-    chunk_semantic_units, kmeans,
-};
+use doctown_v10::{EmbeddingClient, ParserRegistry, Pipeline, SandboxBuilder, SandboxError};
+use std::io::Write;
 use std::time::Instant;
 use std::process::{Command, Child};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// A backend service this process launched (and so may stop)
+struct LaunchedService {
+    child: Child,
+    /// `pkill -f` pattern matching the service's server process
+    pattern: &'static str,
+}
+
+/// (name, working dir, health URL, pkill pattern) of each backend service
+const SERVICES: [(&str, &str, &str, &str); 2] = [
+    ("Embedding Service", "python/embedding", "http://localhost:18115/health", "python3.*embedding.*server.py"),
+    ("Documenter Service", "python/documenter", "http://localhost:18116/health", "python3.*documenter.*server.py"),
+];
+
 fn main() -> Result<(), SandboxError> {
     // Track spawned service processes so we can clean them up
-    let service_processes = Arc::new(Mutex::new(Vec::<Child>::new()));
+    let service_processes = Arc::new(Mutex::new(Vec::<LaunchedService>::new()));
     let processes_clone = Arc::clone(&service_processes);
     
     // Register cleanup handler for Ctrl+C
@@ -31,8 +42,25 @@ fn main() -> Result<(), SandboxError> {
     let sandbox = SandboxBuilder::new()
         .max_file_size(10 * 1024 * 1024) // 10 MB per file
         .max_total_size(150 * 1024 * 1024) // 150 MB total
+        .with_progress(|progress| {
+            let downloaded_mb = progress.bytes_downloaded as f64 / (1024.0 * 1024.0);
+            match progress.total_bytes {
+                Some(total) => print!(
+                    "\r  {:.1}/{:.1} MB downloaded, {} files extracted",
+                    downloaded_mb,
+                    total as f64 / (1024.0 * 1024.0),
+                    progress.files_extracted
+                ),
+                None => print!(
+                    "\r  {:.1} MB downloaded, {} files extracted",
+                    downloaded_mb, progress.files_extracted
+                ),
+            }
+            let _ = std::io::stdout().flush();
+        })
         .ingest_github_repo("serde-rs", "serde", "master")?
         .build();
+    println!();
 
     let step1_duration = step1_start.elapsed();
     println!(
@@ -42,139 +70,104 @@ fn main() -> Result<(), SandboxError> {
         step1_duration.as_secs_f64()
     );
 
+    // Largest languages by file count
+    let mut languages: Vec<_> = sandbox.language_histogram().into_iter().collect();
+    languages.sort_by(|a, b| b.1.0.cmp(&a.1.0).then_with(|| a.0.cmp(&b.0)));
+    for (language, (files, bytes)) in languages.iter().take(5) {
+        println!("  {:<12} {:>6} files {:>10} bytes", language, files, bytes);
+    }
+    println!();
+
     // Step 2: Create parser registry
     let step2_start = Instant::now();
     println!("Step 2: Initializing parser registry...");
-    let registry = ParserRegistry::new();
+    let registry = ParserRegistry::with_defaults();
     let step2_duration = step2_start.elapsed();
     println!(
-        "✓ Registry created with fallback parser [{:.2}s]\n",
+        "✓ Registry created with {} parser(s) + fallback [{:.2}s]\n",
+        registry.parser_count(),
         step2_duration.as_secs_f64()
     );
 
-    // Step 3: Process all files through parser pipeline
-    let step3_start = Instant::now();
-    println!("Step 3: Processing files through parser pipeline...\n");
-    let mut total_semantic_units = 0;
-    let mut total_normalized_bytes = 0;
-    let mut utf8_count = 0;
-    let mut binary_count = 0;
-    let mut all_parse_results = Vec::new();
+    // Steps 3-6: Parse, chunk, embed and cluster
+    println!("Steps 3-6: Parsing, chunking, embedding and clustering...\n");
 
-    for file_entry in sandbox.list() {
-        let bytes = sandbox.get(&file_entry.virtual_path).unwrap();
-        let parser = registry.select(&file_entry.virtual_path);
-        let result = parser.parse(&file_entry.virtual_path, bytes);
-
-        total_semantic_units += result.semantic_units.len();
-        total_normalized_bytes += result.normalized_text.len();
+    let embedding_client = EmbeddingClient::new("http://localhost:18115");
+    let pipeline = Pipeline::builder(&sandbox, &embedding_client)
+        .registry(registry)
+        .batch_size(embedding_client.model().max_batch)
+        .build();
 
-        if result.metadata.is_utf8 {
-            utf8_count += 1;
-        } else {
-            binary_count += 1;
+    let result = match pipeline.run() {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            eprintln!("\n  Make sure the Python embedding server is running:");
+            eprintln!("    cd python/embedding && python server.py\n");
+            return Ok(());
         }
-
-        // Show first few files as examples
-        if utf8_count + binary_count <= 5 {
-            println!(
-                "  {} [{}] - {} units, {} lines, {} bytes",
-                result.metadata.path,
-                result.metadata.language,
-                result.semantic_units.len(),
-                result.metadata.line_count,
-                result.metadata.size_bytes
-            );
+    };
+    let stats = &result.stats;
+    let embeddings = &result.embeddings;
+    let cluster_result = &result.clusters;
+
+    // Show first few files as examples
+    for file in result.files.iter().take(5) {
+        println!(
+            "  {} [{}] - {} lines, {} bytes",
+            file.path,
+            file.language,
+            file.line_count,
+            file.size_bytes
+        );
+    }
+    if !result.parse_warnings.is_empty() {
+        println!("\n{} parse warning(s):", result.parse_warnings.len());
+        for (path, warning) in result.parse_warnings.iter().take(5) {
+            match warning.line {
+                Some(line) => println!("  {}:{}: {}", path, line, warning.message),
+                None => println!("  {}: {}", path, warning.message),
+            }
         }
-
-        all_parse_results.push(result);
     }
-
-    let step3_duration = step3_start.elapsed();
     println!(
         "\n✓ Parsing complete [{:.2}s]\n",
-        step3_duration.as_secs_f64()
+        stats.parse_time.as_secs_f64()
     );
 
-    // Step 4: Chunk semantic units
-    let step4_start = Instant::now();
-    println!("Step 4: Chunking semantic units...\n");
-    let mut total_chunks = 0;
-    let mut total_chunk_tokens = 0;
-    let mut chunks_shown = 0;
-    let mut all_chunks = Vec::new();
-
-    for parse_result in all_parse_results {
-        let chunks = chunk_semantic_units(parse_result.semantic_units, DEFAULT_MAX_TOKENS);
+    let total_chunks = result.chunks.len();
+    let total_chunk_tokens = stats.total_tokens;
 
-        // Show first few chunked files
-        if chunks_shown < 5 && !chunks.is_empty() {
-            println!(
-                "  {} - {} chunks, avg {} tokens/chunk",
-                parse_result.metadata.path,
-                chunks.len(),
-                chunks.iter().map(|c| c.metadata.token_count).sum::<usize>() / chunks.len()
-            );
-            chunks_shown += 1;
-        }
-
-        total_chunks += chunks.len();
-        total_chunk_tokens += chunks.iter().map(|c| c.metadata.token_count).sum::<usize>();
-        all_chunks.extend(chunks);
+    // Show first few chunked files
+    for chunks in result
+        .chunks
+        .chunk_by(|a, b| a.metadata.file_path == b.metadata.file_path)
+        .take(5)
+    {
+        println!(
+            "  {} - {} chunks, avg {} tokens/chunk",
+            chunks[0].metadata.file_path,
+            chunks.len(),
+            chunks.iter().map(|c| c.metadata.token_count).sum::<usize>() / chunks.len()
+        );
     }
-
-    let step4_duration = step4_start.elapsed();
     println!(
         "\n✓ Chunking complete [{:.2}s]\n",
-        step4_duration.as_secs_f64()
+        stats.chunk_time.as_secs_f64()
     );
 
-    // Step 5: Embed chunks
-    let step5_start = Instant::now();
-    println!("Step 5: Embedding chunks...\n");
-
-    let embedding_client = EmbeddingClient::new("http://localhost:18115");
-    let chunk_texts: Vec<String> = all_chunks.iter().map(|c| c.text.clone()).collect();
-
-    println!(
-        "  Sending {} chunks to embedding server...",
-        chunk_texts.len()
-    );
-    let embeddings = match embedding_client.embed_chunks_blocking(chunk_texts) {
-        Ok(emb) => {
-            println!("  ✓ Received {} embeddings", emb.len());
-            if !emb.is_empty() {
-                println!("  Embedding dimensions: {}", emb[0].len());
-            }
-            emb
-        }
-        Err(e) => {
-            eprintln!("  ✗ Embedding failed: {}", e);
-            eprintln!("\n  Make sure the Python embedding server is running:");
-            eprintln!("    cd python/embedding && python server.py\n");
-            return Ok(());
-        }
-    };
-
-    let step5_duration = step5_start.elapsed();
+    println!("  ✓ Received {} embeddings", embeddings.len());
+    if !embeddings.is_empty() {
+        println!("  Embedding dimensions: {}", embeddings[0].len());
+    }
     println!(
         "\n✓ Embedding complete [{:.2}s]\n",
-        step5_duration.as_secs_f64()
+        stats.embed_time.as_secs_f64()
     );
 
-    // Step 6: Cluster embeddings
-    let step6_start = Instant::now();
-    println!("Step 6: Clustering embeddings...\n");
-
-    // Calculate number of clusters (heuristic: sqrt(n) or max 50)
-    let k = (embeddings.len() as f64).sqrt().ceil() as usize;
-    let k = k.min(50).max(2);
-
-    println!("  Running k-means with k={} clusters...", k);
-    let cluster_result = kmeans(&embeddings, k, 100, 42);
-
     println!("  ✓ Converged in {} iterations", cluster_result.iterations);
     println!("  Total clusters: {}", cluster_result.clusters.len());
+    println!("  Inertia: {:.3}", cluster_result.inertia);
 
     // Show cluster size distribution
     let mut cluster_sizes: Vec<(u32, usize)> = cluster_result
@@ -189,22 +182,22 @@ fn main() -> Result<(), SandboxError> {
         println!("    Cluster {}: {} chunks", id, size);
     }
 
-    let step6_duration = step6_start.elapsed();
     println!(
         "\n✓ Clustering complete [{:.2}s]\n",
-        step6_duration.as_secs_f64()
+        stats.cluster_time.as_secs_f64()
     );
 
     // Statistics
     println!("=== Pipeline Statistics ===");
     println!("Total files:          {}", sandbox.file_count());
-    println!("UTF-8 files:          {}", utf8_count);
-    println!("Binary files:         {}", binary_count);
-    println!("Semantic units:       {}", total_semantic_units);
-    println!("Normalized bytes:     {}", total_normalized_bytes);
+    println!("Text files:           {}", stats.text_files);
+    println!("Binary files:         {}", stats.binary_files);
+    println!("Semantic units:       {}", stats.semantic_units);
+    println!("Symbols extracted:    {}", stats.symbols);
+    println!("Normalized bytes:     {}", stats.normalized_bytes);
     println!(
         "Avg units/file:       {:.1}",
-        total_semantic_units as f64 / sandbox.file_count() as f64
+        stats.semantic_units as f64 / sandbox.file_count() as f64
     );
     println!("Total chunks:         {}", total_chunks);
     println!("Total tokens:         {}", total_chunk_tokens);
@@ -228,7 +221,7 @@ fn main() -> Result<(), SandboxError> {
     println!("Clusters:             {}", cluster_result.clusters.len());
     println!(
         "Avg chunks/cluster:   {:.1}",
-        if cluster_result.clusters.len() > 0 {
+        if !cluster_result.clusters.is_empty() {
             total_chunks as f64 / cluster_result.clusters.len() as f64
         } else {
             0.0
@@ -250,36 +243,36 @@ fn main() -> Result<(), SandboxError> {
     );
     println!(
         "Step 3 (Parsing):     {:.3}s ({:.1}%)",
-        step3_duration.as_secs_f64(),
-        100.0 * step3_duration.as_secs_f64() / total_duration.as_secs_f64()
+        stats.parse_time.as_secs_f64(),
+        100.0 * stats.parse_time.as_secs_f64() / total_duration.as_secs_f64()
     );
     println!(
         "Step 4 (Chunking):    {:.3}s ({:.1}%)",
-        step4_duration.as_secs_f64(),
-        100.0 * step4_duration.as_secs_f64() / total_duration.as_secs_f64()
+        stats.chunk_time.as_secs_f64(),
+        100.0 * stats.chunk_time.as_secs_f64() / total_duration.as_secs_f64()
     );
     println!(
         "Step 5 (Embedding):   {:.3}s ({:.1}%)",
-        step5_duration.as_secs_f64(),
-        100.0 * step5_duration.as_secs_f64() / total_duration.as_secs_f64()
+        stats.embed_time.as_secs_f64(),
+        100.0 * stats.embed_time.as_secs_f64() / total_duration.as_secs_f64()
     );
     println!(
         "Step 6 (Clustering):  {:.3}s ({:.1}%)",
-        step6_duration.as_secs_f64(),
-        100.0 * step6_duration.as_secs_f64() / total_duration.as_secs_f64()
+        stats.cluster_time.as_secs_f64(),
+        100.0 * stats.cluster_time.as_secs_f64() / total_duration.as_secs_f64()
     );
     println!("─────────────────────────────────");
     println!("Total execution:      {:.3}s", total_duration.as_secs_f64());
 
     println!("\n=== System Extensibility ===");
     println!(
-        "Current parsers:      {} (fallback only)",
-        registry.parser_count()
+        "Current parsers:      {} (+ fallback)",
+        pipeline.registry().parser_count()
     );
     println!("Ready for:            Rust, Python, Markdown, JSON, etc.");
     println!(
         "Chunker configured:   Max {} tokens per chunk",
-        DEFAULT_MAX_TOKENS
+        pipeline.chunk_options().max_tokens
     );
     println!("Embedding model:      google/embeddinggemma-300m (768-dim)");
     println!("Clustering:           K-means with cosine distance");
@@ -292,36 +285,32 @@ fn main() -> Result<(), SandboxError> {
     Ok(())
 }
 
-fn check_and_launch_services(service_processes: &Arc<Mutex<Vec<Child>>>) {
+fn check_and_launch_services(service_processes: &Arc<Mutex<Vec<LaunchedService>>>) {
     println!("Checking backend services...");
     
-    // First, clean up any existing Python server processes to avoid port conflicts and CUDA memory leaks
-    println!("  🧹 Cleaning up existing backend processes...");
-    kill_existing_services();
-    
-    // Give the OS a moment to clean up
-    std::thread::sleep(std::time::Duration::from_millis(500));
-    
-    // Launch embedding service
-    println!("  🚀 Launching embedding service...");
-    match launch_service("Embedding Service", &["python3", "server.py"], "python/embedding") {
-        Ok(child) => {
-            service_processes.lock().unwrap().push(child);
-            println!("  ⏳ Waiting for embedding service to be ready...");
-            wait_for_service("http://localhost:18115/health", "Embedding Service", 60);
+    for (name, dir, health_url, pattern) in SERVICES {
+        // A healthy service may belong to someone else; reuse it and leave it running
+        if check_service(health_url) {
+            println!("  ✓ {} is already running - reusing it", name);
+            continue;
         }
-        Err(e) => eprintln!("  ✗ Failed to launch embedding service: {}", e),
-    }
-    
-    // Launch documenter service
-    println!("  🚀 Launching documenter service...");
-    match launch_service("Documenter Service", &["python3", "server.py"], "python/documenter") {
-        Ok(child) => {
-            service_processes.lock().unwrap().push(child);
-            println!("  ⏳ Waiting for documenter service to be ready...");
-            wait_for_service("http://localhost:18116/health", "Documenter Service", 60);
+        
+        // Clean up stale server processes to avoid port conflicts and CUDA memory leaks
+        println!("  🧹 Cleaning up stale {} processes...", name);
+        kill_service(pattern);
+        
+        // Give the OS a moment to clean up
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        
+        println!("  🚀 Launching {}...", name);
+        match launch_service(name, &["python3", "server.py"], dir) {
+            Ok(child) => {
+                service_processes.lock().unwrap().push(LaunchedService { child, pattern });
+                println!("  ⏳ Waiting for {} to be ready...", name);
+                wait_for_service(health_url, name, 60);
+            }
+            Err(e) => eprintln!("  ✗ Failed to launch {}: {}", name, e),
         }
-        Err(e) => eprintln!("  ✗ Failed to launch documenter service: {}", e),
     }
     
     println!();
@@ -339,7 +328,7 @@ fn wait_for_service(url: &str, name: &str, timeout_secs: u64) {
         
         // Show progress every 5 seconds
         let elapsed = start.elapsed().as_secs();
-        if elapsed > 0 && elapsed % 5 == 0 {
+        if elapsed > 0 && elapsed.is_multiple_of(5) {
             println!("    ... still waiting ({:.0}s elapsed)", elapsed);
         }
         
@@ -349,16 +338,11 @@ fn wait_for_service(url: &str, name: &str, timeout_secs: u64) {
     eprintln!("  ⚠ {} did not respond within {}s - continuing anyway", name, timeout_secs);
 }
 
-fn kill_existing_services() {
-    // Kill any Python processes running server.py in embedding or documenter directories
+fn kill_service(pattern: &str) {
+    // Kill any Python processes running the service's server.py
     let _ = Command::new("pkill")
         .arg("-f")
-        .arg("python3.*embedding.*server.py")
-        .output();
-    
-    let _ = Command::new("pkill")
-        .arg("-f")
-        .arg("python3.*documenter.*server.py")
+        .arg(pattern)
         .output();
 }
 
@@ -382,21 +366,20 @@ fn launch_service(title: &str, command_args: &[&str], relative_path: &str) -> st
     launch_in_terminal(title, command_args, &working_dir)
 }
 
-fn cleanup_services(service_processes: &Arc<Mutex<Vec<Child>>>) {
+fn cleanup_services(service_processes: &Arc<Mutex<Vec<LaunchedService>>>) {
     let mut processes = service_processes.lock().unwrap();
     
-    // Kill all tracked child processes
-    for child in processes.iter_mut() {
-        let _ = child.kill();
+    // Kill the services we launched; reused ones keep running
+    for service in processes.iter_mut() {
+        let _ = service.child.kill();
+        // The child is the terminal window, so also kill the server inside it
+        kill_service(service.pattern);
     }
     
-    // Also kill any lingering Python server processes
-    kill_existing_services();
-    
     processes.clear();
 }
 
-fn launch_in_terminal(title: &str, command_args: &[&str], working_dir: &PathBuf) -> std::io::Result<Child> {
+fn launch_in_terminal(title: &str, command_args: &[&str], working_dir: &Path) -> std::io::Result<Child> {
     let command_str = command_args.join(" ");
     
     // Get project root to access python/.venv
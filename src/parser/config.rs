@@ -0,0 +1,276 @@
+use super::{
+    FileMetadata, ParseResult, ParseWarning, ParsedSymbol, Parser, SemanticKind, SemanticUnit,
+    UnknownParser, normalize_utf8,
+};
+use serde::Deserialize;
+
+/// Parser for TOML configuration files
+///
+/// Each `[table]` and `[[array-of-tables]]` header starts a `Config` unit
+/// that runs until the next header; root-level keys before the first header
+/// form their own unit. Root-level keys and table headers (named by their
+/// dotted path) become symbols. Malformed input falls back to line chunking.
+pub struct TomlParser;
+
+/// Parser for YAML configuration files
+///
+/// Each top-level mapping key becomes a `Config` unit, together with any
+/// comment lines directly above it, and a symbol. Documents without
+/// top-level keys are emitted whole. Malformed input falls back to line
+/// chunking.
+pub struct YamlParser;
+
+impl Parser for TomlParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        if let Err(e) = source.parse::<toml::Table>() {
+            let line = e
+                .span()
+                .map(|span| source[..span.start].matches('\n').count() + 1);
+            let warning = ParseWarning::new(
+                format!("invalid TOML, fell back to line chunking: {}", e.message()),
+                line,
+            );
+            return UnknownParser::new()
+                .parse(path, bytes)
+                .with_warning(warning);
+        }
+
+        let lines = split_lines(source);
+        let mut starts = vec![0];
+        let mut symbols = Vec::new();
+        let mut depth = 0usize;
+        let mut multiline: Option<&str> = None;
+
+        for (i, &(_, line)) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if multiline.is_none() && depth == 0 {
+                if trimmed.starts_with('[') {
+                    starts.push(i);
+                    let name = TomlParser::header_name(trimmed);
+                    symbols.push(config_symbol(name, i, trimmed));
+                    continue;
+                }
+
+                // Only keys before the first header are top-level
+                if starts.len() == 1
+                    && !trimmed.is_empty()
+                    && !trimmed.starts_with('#')
+                    && let Some((key, _)) = trimmed.split_once('=')
+                {
+                    symbols.push(config_symbol(unquote(key.trim()), i, trimmed));
+                }
+            }
+            TomlParser::scan_value_line(line, &mut depth, &mut multiline);
+        }
+
+        config_result(path, bytes, source, &lines, starts, symbols)
+    }
+}
+
+impl TomlParser {
+    /// Table name of a `[table]` or `[[array-of-tables]]` header line
+    fn header_name(line: &str) -> &str {
+        let line = line
+            .split_once('#')
+            .map_or(line, |(header, _)| header)
+            .trim();
+        line.trim_start_matches('[').trim_end_matches(']').trim()
+    }
+
+    /// Track open arrays/inline tables and multi-line strings in a value line
+    fn scan_value_line(line: &str, depth: &mut usize, multiline: &mut Option<&'static str>) {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if let Some(delim) = *multiline {
+                match line[i..].find(delim) {
+                    Some(pos) => {
+                        i += pos + 3;
+                        *multiline = None;
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+
+            match bytes[i] {
+                b'#' => return,
+                b'[' | b'{' => *depth += 1,
+                b']' | b'}' => *depth = depth.saturating_sub(1),
+                quote @ (b'"' | b'\'') => {
+                    let delim = if quote == b'"' { "\"\"\"" } else { "'''" };
+                    if line[i..].starts_with(delim) {
+                        *multiline = Some(delim);
+                        i += 3;
+                        continue;
+                    }
+
+                    // Single-line string; only basic strings have escapes
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                        if quote == b'"' && bytes[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+impl Parser for YamlParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        let error = serde_yaml::Deserializer::from_str(source)
+            .find_map(|document| serde_yaml::Value::deserialize(document).err());
+        if let Some(e) = error {
+            let warning = ParseWarning::new(
+                format!("invalid YAML, fell back to line chunking: {}", e),
+                e.location().map(|location| location.line()),
+            );
+            return UnknownParser::new()
+                .parse(path, bytes)
+                .with_warning(warning);
+        }
+
+        let lines = split_lines(source);
+        let mut starts = Vec::new();
+        let mut symbols = Vec::new();
+        let mut comment_start = None;
+
+        for (i, &(_, line)) in lines.iter().enumerate() {
+            let trimmed = line.trim_end();
+            if trimmed.starts_with('#') {
+                comment_start.get_or_insert(i);
+                continue;
+            }
+
+            let is_key = !trimmed.is_empty()
+                && !trimmed.starts_with([' ', '\t', '-', '%'])
+                && trimmed != "..."
+                && !trimmed.starts_with("---");
+
+            if is_key {
+                starts.push(comment_start.unwrap_or(i));
+                if let Some(key) = YamlParser::key_name(trimmed) {
+                    symbols.push(config_symbol(key, i, trimmed));
+                }
+            } else if trimmed == "..." || trimmed.starts_with("---") {
+                // Document markers end the previous key
+                starts.push(i);
+            }
+            comment_start = None;
+        }
+
+        if starts.first() != Some(&0) {
+            starts.insert(0, 0);
+        }
+
+        config_result(path, bytes, source, &lines, starts, symbols)
+    }
+}
+
+impl YamlParser {
+    /// Key of a `key: value` line, or None for a bare scalar
+    fn key_name(line: &str) -> Option<&str> {
+        if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'')
+            && let Some(end) = line[1..].find(quote)
+        {
+            return Some(&line[1..end + 1]);
+        }
+
+        let key = line
+            .find(": ")
+            .or_else(|| line.ends_with(':').then(|| line.len() - 1))
+            .map(|end| line[..end].trim_end())?;
+        (!key.is_empty()).then_some(key)
+    }
+}
+
+/// Strip one pair of matching quotes from a TOML key
+fn unquote(key: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| key.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(key)
+}
+
+/// Symbol for a top-level key or table declared on line `i` (0-based)
+fn config_symbol(name: &str, i: usize, line: &str) -> ParsedSymbol {
+    ParsedSymbol {
+        name: name.to_string(),
+        kind: SemanticKind::Config,
+        start_line: i + 1,
+        signature: line.to_string(),
+    }
+}
+
+/// Split source into `(offset, line)` pairs, keeping line terminators
+fn split_lines(source: &str) -> Vec<(usize, &str)> {
+    source
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect()
+}
+
+/// Build a result with one `Config` unit per region between `starts`, plus
+/// the given symbols
+fn config_result(
+    path: &str,
+    bytes: &[u8],
+    source: &str,
+    lines: &[(usize, &str)],
+    starts: Vec<usize>,
+    symbols: Vec<ParsedSymbol>,
+) -> ParseResult {
+    let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+    metadata.set_line_count(source.lines().count());
+
+    let mut units = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(lines.len());
+        if start >= end {
+            continue;
+        }
+
+        let (first, _) = lines[start];
+        let (last, last_line) = lines[end - 1];
+        let region = &source[first..last + last_line.len()];
+
+        // Skip regions that hold only whitespace, comments or doc markers
+        let has_content = region.lines().map(str::trim).any(|line| {
+            !line.is_empty() && !line.starts_with('#') && line != "---" && line != "..."
+        });
+        if !has_content {
+            continue;
+        }
+
+        let text = region.trim();
+        let start_offset = first + (region.len() - region.trim_start().len());
+        units.push(SemanticUnit {
+            text: text.to_string(),
+            start_offset,
+            end_offset: start_offset + text.len(),
+            kind: SemanticKind::Config,
+        });
+    }
+
+    ParseResult::new(source.to_string(), metadata, units, symbols)
+}
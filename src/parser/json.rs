@@ -0,0 +1,182 @@
+use super::{
+    FileMetadata, ParseResult, ParseWarning, ParsedSymbol, Parser, SemanticKind, SemanticUnit,
+    UnknownParser, normalize_utf8,
+};
+use serde::de::IgnoredAny;
+
+/// Parser for JSON documents
+///
+/// Emits one `Object` unit per top-level object member (`"key": value`) or
+/// array element, so large config and data files chunk along their
+/// structure; the keys of a top-level object double as symbols. The
+/// document is validated with `serde_json` first, and malformed input falls
+/// back to line-based chunking.
+pub struct JsonParser;
+
+impl Parser for JsonParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        if let Err(e) = serde_json::from_str::<IgnoredAny>(source) {
+            let warning = ParseWarning::new(
+                format!("invalid JSON, fell back to line chunking: {}", e),
+                Some(e.line()),
+            );
+            return UnknownParser::new()
+                .parse(path, bytes)
+                .with_warning(warning);
+        }
+
+        let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+        metadata.set_line_count(source.lines().count());
+
+        let ranges = Self::member_ranges(source.as_bytes());
+        let mut units: Vec<SemanticUnit> = ranges
+            .iter()
+            .map(|&(start, end)| SemanticUnit {
+                text: source[start..end].to_string(),
+                start_offset: start,
+                end_offset: end,
+                kind: SemanticKind::Object,
+            })
+            .collect();
+
+        // Scalars and empty containers become a single unit
+        let trimmed = source.trim();
+        if units.is_empty() && !trimmed.is_empty() {
+            let start = source.len() - source.trim_start().len();
+            units.push(SemanticUnit {
+                text: trimmed.to_string(),
+                start_offset: start,
+                end_offset: start + trimmed.len(),
+                kind: SemanticKind::Object,
+            });
+        }
+
+        // Keys of a top-level object double as the document's symbols
+        let symbols = if source.trim_start().starts_with('{') {
+            ranges
+                .iter()
+                .map(|&(start, end)| Self::member_symbol(source, start, end))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        ParseResult::new(text, metadata, units, symbols)
+    }
+}
+
+impl JsonParser {
+    /// Symbol for the object member spanning `start..end`
+    fn member_symbol(source: &str, start: usize, end: usize) -> ParsedSymbol {
+        let key = &source[start..Self::skip_value(source.as_bytes(), start)];
+        ParsedSymbol {
+            name: serde_json::from_str(key).unwrap_or_else(|_| key.to_string()),
+            kind: SemanticKind::Object,
+            start_line: source[..start].matches('\n').count() + 1,
+            signature: source[start..end]
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+        }
+    }
+
+    /// Byte ranges of each top-level member or element
+    ///
+    /// Assumes the input has already been validated as JSON.
+    fn member_ranges(bytes: &[u8]) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut i = Self::skip_whitespace(bytes, 0);
+
+        let is_object = match bytes.get(i) {
+            Some(b'{') => true,
+            Some(b'[') => false,
+            _ => return ranges,
+        };
+        i += 1;
+
+        loop {
+            i = Self::skip_whitespace(bytes, i);
+            match bytes.get(i) {
+                Some(b'}' | b']') | None => break,
+                Some(b',') => {
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let start = i;
+            if is_object {
+                // "key" : value
+                i = Self::skip_value(bytes, i);
+                i = Self::skip_whitespace(bytes, i) + 1;
+                i = Self::skip_whitespace(bytes, i);
+            }
+            i = Self::skip_value(bytes, i);
+            ranges.push((start, i));
+        }
+
+        ranges
+    }
+
+    /// Skip a single JSON value starting at `i`, returning the index past it
+    fn skip_value(bytes: &[u8], mut i: usize) -> usize {
+        match bytes.get(i) {
+            Some(b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i + 1
+            }
+            Some(b'{' | b'[') => {
+                let mut depth = 0usize;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'"' => {
+                            i = Self::skip_value(bytes, i);
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return i + 1;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i
+            }
+            // Numbers, true, false, null
+            _ => {
+                while i < bytes.len()
+                    && !matches!(bytes[i], b',' | b'}' | b']')
+                    && !bytes[i].is_ascii_whitespace()
+                {
+                    i += 1;
+                }
+                i
+            }
+        }
+    }
+
+    fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    }
+}
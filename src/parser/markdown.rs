@@ -0,0 +1,196 @@
+use super::{
+    FileMetadata, ParseResult, ParsedSymbol, Parser, SemanticKind, SemanticUnit, UnknownParser,
+    normalize_utf8,
+};
+
+/// Parser for Markdown documents
+///
+/// Each heading starts a `Section` unit that runs until the next heading,
+/// so subsections become their own sections rather than being duplicated
+/// inside their parent. Text before the first heading is split into
+/// `Paragraph` units on blank lines. Units never overlap, so no text reaches
+/// the chunker twice. Headings and blank lines inside fenced code blocks are
+/// ignored, so fences are never split.
+pub struct MarkdownParser;
+
+impl Parser for MarkdownParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+        metadata.set_line_count(source.lines().count());
+
+        let lines: Vec<(usize, &str)> = source
+            .split_inclusive('\n')
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some((start, line))
+            })
+            .collect();
+
+        let headings = Self::heading_lines(&lines);
+        let mut units = Vec::new();
+
+        // Preamble before the first heading is split into paragraphs
+        let preamble_end = headings.first().copied().unwrap_or(lines.len());
+        Self::push_paragraphs(source, &lines[..preamble_end], &mut units);
+
+        // Every heading owns the text up to the next heading
+        for (i, &start) in headings.iter().enumerate() {
+            let end = headings.get(i + 1).copied().unwrap_or(lines.len());
+            Self::push_region(
+                source,
+                &lines[start..end],
+                SemanticKind::Section,
+                &mut units,
+            );
+        }
+
+        // Headings double as the document's symbols
+        let symbols = headings
+            .iter()
+            .map(|&i| {
+                let line = lines[i].1.trim();
+                ParsedSymbol {
+                    name: line
+                        .trim_start_matches('#')
+                        .trim_end_matches('#')
+                        .trim()
+                        .to_string(),
+                    kind: SemanticKind::Section,
+                    start_line: i + 1,
+                    signature: line.to_string(),
+                }
+            })
+            .collect();
+
+        ParseResult::new(text, metadata, units, symbols)
+    }
+}
+
+impl MarkdownParser {
+    /// Indices of lines that start a heading, skipping fenced code blocks
+    fn heading_lines(lines: &[(usize, &str)]) -> Vec<usize> {
+        let mut headings = Vec::new();
+        let mut fence: Option<(char, usize)> = None;
+
+        for (i, &(_, line)) in lines.iter().enumerate() {
+            if Self::update_fence(line, &mut fence) || fence.is_some() {
+                continue;
+            }
+
+            if Self::is_atx_heading(line) || Self::is_setext_heading(lines, i) {
+                headings.push(i);
+            }
+        }
+
+        headings
+    }
+
+    /// Track opening/closing code fences; returns true for fence lines
+    fn update_fence(line: &str, fence: &mut Option<(char, usize)>) -> bool {
+        let trimmed = line.trim_end();
+        let content = trimmed.trim_start_matches(' ');
+        if trimmed.len() - content.len() > 3 {
+            return false;
+        }
+
+        let Some(marker) = content.chars().next().filter(|c| *c == '`' || *c == '~') else {
+            return false;
+        };
+        let run = content.chars().take_while(|c| *c == marker).count();
+        if run < 3 {
+            return false;
+        }
+
+        match *fence {
+            None => {
+                *fence = Some((marker, run));
+                true
+            }
+            // A closing fence uses the same marker, is at least as long and
+            // carries no info string
+            Some((open, len)) if open == marker && run >= len && content.len() == run => {
+                *fence = None;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// `# Heading` style: 1-6 hashes followed by whitespace or end of line
+    fn is_atx_heading(line: &str) -> bool {
+        let content = line.trim_start_matches(' ');
+        if line.len() - content.len() > 3 {
+            return false;
+        }
+
+        let hashes = content.chars().take_while(|c| *c == '#').count();
+        (1..=6).contains(&hashes)
+            && content[hashes..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+    }
+
+    /// `Heading\n=====` style: a single text line underlined with = or -
+    fn is_setext_heading(lines: &[(usize, &str)], i: usize) -> bool {
+        let text = lines[i].1.trim();
+        let Some(&(_, underline)) = lines.get(i + 1) else {
+            return false;
+        };
+        let underline = underline.trim();
+
+        let is_underline = !underline.is_empty()
+            && (underline.chars().all(|c| c == '=') || underline.chars().all(|c| c == '-'));
+        let starts_paragraph = i == 0 || lines[i - 1].1.trim().is_empty();
+
+        is_underline && starts_paragraph && !text.is_empty() && !Self::is_atx_heading(lines[i].1)
+    }
+
+    /// Split lines on blank lines (outside fences) into paragraph units
+    fn push_paragraphs(source: &str, lines: &[(usize, &str)], units: &mut Vec<SemanticUnit>) {
+        let mut fence = None;
+        let mut start = 0;
+
+        for (i, &(_, line)) in lines.iter().enumerate() {
+            let is_fence = Self::update_fence(line, &mut fence);
+            if !is_fence && fence.is_none() && line.trim().is_empty() {
+                Self::push_region(source, &lines[start..i], SemanticKind::Paragraph, units);
+                start = i + 1;
+            }
+        }
+
+        Self::push_region(source, &lines[start..], SemanticKind::Paragraph, units);
+    }
+
+    /// Emit a run of lines as one unit, trimming surrounding whitespace
+    fn push_region(
+        source: &str,
+        lines: &[(usize, &str)],
+        kind: SemanticKind,
+        units: &mut Vec<SemanticUnit>,
+    ) {
+        let (Some(&(first, _)), Some(&(last, last_line))) = (lines.first(), lines.last()) else {
+            return;
+        };
+
+        let region = &source[first..last + last_line.len()];
+        let text = region.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        let start = first + (region.len() - region.trim_start().len());
+        units.push(SemanticUnit {
+            text: text.to_string(),
+            start_offset: start,
+            end_offset: start + text.len(),
+            kind,
+        });
+    }
+}
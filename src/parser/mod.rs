@@ -1,13 +1,26 @@
+mod config;
+mod json;
+mod markdown;
+mod python;
 mod registry;
 mod result;
+mod rust;
 mod unknown;
 
 #[cfg(test)]
 mod tests;
 
+pub use config::{TomlParser, YamlParser};
+pub use json::JsonParser;
+pub use markdown::MarkdownParser;
+pub use python::PythonParser;
 pub use registry::ParserRegistry;
-pub use result::{FileMetadata, ParseResult, SemanticKind, SemanticUnit};
-pub use unknown::UnknownParser;
+pub use result::{
+    BINARY_SAMPLE_SIZE, DEFAULT_BINARY_THRESHOLD, FileMetadata, LineEnding, ParseResult,
+    ParseWarning, ParsedSymbol, SemanticKind, SemanticUnit, normalize_utf8,
+};
+pub use rust::RustParser;
+pub use unknown::{UnknownParser, UnknownParserWithThreshold};
 
 /// Core trait that all parsers must implement
 pub trait Parser: Send + Sync {
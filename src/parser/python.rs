@@ -0,0 +1,330 @@
+use super::{
+    FileMetadata, ParseResult, ParsedSymbol, Parser, SemanticKind, SemanticUnit, UnknownParser,
+    normalize_utf8,
+};
+
+/// Parser for Python source files
+///
+/// Walks indentation to find `def`/`class` blocks. Functions are emitted
+/// whole (including nested helpers), while classes are split into a header
+/// unit followed by one unit per method or nested class. Decorators belong
+/// to the block they decorate, and the leading docstring/import section
+/// becomes a `Module` unit.
+pub struct PythonParser;
+
+/// Structural information about a single source line
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    /// Byte offset of the first character
+    start: usize,
+    /// Byte offset past the line terminator
+    end: usize,
+    /// Indentation width (tabs advance to the next multiple of 8)
+    indent: usize,
+    /// Blank or comment-only line, which Python ignores for indentation
+    ignorable: bool,
+    /// Line starts inside brackets, a triple-quoted string or after `\`
+    continuation: bool,
+}
+
+impl Line {
+    /// Whether this line participates in block structure
+    fn is_structural(&self) -> bool {
+        !self.ignorable && !self.continuation
+    }
+}
+
+/// Units and symbols accumulated while walking blocks
+#[derive(Default)]
+struct BlockOutput {
+    units: Vec<SemanticUnit>,
+    symbols: Vec<ParsedSymbol>,
+}
+
+impl Parser for PythonParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+        metadata.set_line_count(source.lines().count());
+
+        let lines = Self::scan_lines(source);
+        let mut output = BlockOutput::default();
+        Self::collect_blocks(source, &lines, 0, lines.len(), None, &mut output);
+
+        ParseResult::new(text, metadata, output.units, output.symbols)
+    }
+}
+
+impl PythonParser {
+    /// Split source into lines, tracking brackets and strings that span lines
+    fn scan_lines(source: &str) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        let mut depth = 0usize;
+        let mut triple = None;
+        let mut backslash = false;
+
+        for raw in source.split_inclusive('\n') {
+            let continuation = depth > 0 || triple.is_some() || backslash;
+            let content = raw.trim_start_matches([' ', '\t']);
+
+            let indent = raw[..raw.len() - content.len()].chars().fold(0, |col, c| {
+                if c == '\t' {
+                    (col / 8 + 1) * 8
+                } else {
+                    col + 1
+                }
+            });
+            let trimmed = content.trim();
+            let ignorable = trimmed.is_empty() || trimmed.starts_with('#');
+
+            Self::scan_line(raw, &mut depth, &mut triple);
+            backslash = triple.is_none() && raw.trim_end().ends_with('\\');
+
+            lines.push(Line {
+                start: offset,
+                end: offset + raw.len(),
+                indent,
+                ignorable: ignorable && !continuation,
+                continuation,
+            });
+            offset += raw.len();
+        }
+
+        lines
+    }
+
+    /// Update bracket depth and triple-quote state for one line
+    fn scan_line(line: &str, depth: &mut usize, triple: &mut Option<&'static str>) {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if let Some(delim) = *triple {
+                match line[i..].find(delim) {
+                    Some(pos) => {
+                        i += pos + 3;
+                        *triple = None;
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+
+            match bytes[i] {
+                b'#' => return,
+                b'(' | b'[' | b'{' => *depth += 1,
+                b')' | b']' | b'}' => *depth = depth.saturating_sub(1),
+                quote @ (b'"' | b'\'') => {
+                    let delim = if quote == b'"' { "\"\"\"" } else { "'''" };
+                    if line[i..].starts_with(delim) {
+                        *triple = Some(delim);
+                        i += 3;
+                        continue;
+                    }
+
+                    // Single-line string: skip to the matching unescaped quote
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                        if bytes[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Emit units for lines `lo..hi`, splitting out def/class blocks
+    ///
+    /// `scope` is the enclosing class name, used to qualify symbols.
+    fn collect_blocks(
+        source: &str,
+        lines: &[Line],
+        lo: usize,
+        hi: usize,
+        scope: Option<&str>,
+        output: &mut BlockOutput,
+    ) {
+        let mut gap_start = lo;
+        let mut decorator_start = None;
+        let mut leading = scope.is_none();
+        let mut i = lo;
+
+        while i < hi {
+            let line = lines[i];
+            if !line.is_structural() {
+                i += 1;
+                continue;
+            }
+
+            let text = source[line.start..line.end].trim();
+            let Some(kind) = Self::block_kind(text) else {
+                if text.starts_with('@') {
+                    decorator_start.get_or_insert(i);
+                } else {
+                    decorator_start = None;
+                }
+                i += 1;
+                continue;
+            };
+
+            let block_start = decorator_start.take().unwrap_or(i);
+            let block_end = Self::block_end(lines, i, hi);
+
+            let name = Self::block_name(text);
+            let qualified = match scope {
+                Some(scope) => format!("{}.{}", scope, name),
+                None => name.to_string(),
+            };
+            output.symbols.push(ParsedSymbol {
+                name: qualified.clone(),
+                kind,
+                start_line: i + 1,
+                signature: text.to_string(),
+            });
+
+            let gap_kind = if leading {
+                SemanticKind::Module
+            } else {
+                SemanticKind::Unknown
+            };
+            Self::push_lines(
+                source,
+                lines,
+                gap_start,
+                block_start,
+                gap_kind,
+                &mut output.units,
+            );
+            leading = false;
+
+            if kind == SemanticKind::Class {
+                // Class header (signature, docstring, attributes) up to the
+                // first nested block, then recurse into the rest of the body
+                let body_split = (i + 1..block_end)
+                    .find(|&j| {
+                        let line = lines[j];
+                        let text = source[line.start..line.end].trim();
+                        line.is_structural()
+                            && (text.starts_with('@') || Self::block_kind(text).is_some())
+                    })
+                    .unwrap_or(block_end);
+
+                Self::push_lines(
+                    source,
+                    lines,
+                    block_start,
+                    body_split,
+                    kind,
+                    &mut output.units,
+                );
+                Self::collect_blocks(
+                    source,
+                    lines,
+                    body_split,
+                    block_end,
+                    Some(&qualified),
+                    output,
+                );
+            } else {
+                Self::push_lines(
+                    source,
+                    lines,
+                    block_start,
+                    block_end,
+                    kind,
+                    &mut output.units,
+                );
+            }
+
+            gap_start = block_end;
+            i = block_end;
+        }
+
+        let gap_kind = if leading {
+            SemanticKind::Module
+        } else {
+            SemanticKind::Unknown
+        };
+        Self::push_lines(source, lines, gap_start, hi, gap_kind, &mut output.units);
+    }
+
+    /// Classify a stripped line as a function or class header
+    fn block_kind(text: &str) -> Option<SemanticKind> {
+        let text = text.strip_prefix("async ").unwrap_or(text).trim_start();
+        if text.starts_with("def ") {
+            Some(SemanticKind::Function)
+        } else if text.starts_with("class ") {
+            Some(SemanticKind::Class)
+        } else {
+            None
+        }
+    }
+
+    /// Extract the defined name from a `def`/`class` header line
+    fn block_name(text: &str) -> &str {
+        let text = text.strip_prefix("async ").unwrap_or(text).trim_start();
+        let rest = text
+            .strip_prefix("def ")
+            .or_else(|| text.strip_prefix("class "))
+            .unwrap_or(text)
+            .trim_start();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    /// Find the line index just past the block whose header is at `header`
+    fn block_end(lines: &[Line], header: usize, hi: usize) -> usize {
+        let indent = lines[header].indent;
+        let mut end = header + 1;
+
+        while end < hi && (!lines[end].is_structural() || lines[end].indent > indent) {
+            end += 1;
+        }
+
+        // Trailing blank lines and dedented comments belong to what follows
+        while end > header + 1 && lines[end - 1].ignorable && lines[end - 1].indent <= indent {
+            end -= 1;
+        }
+
+        end
+    }
+
+    /// Emit lines `lo..hi` as one unit, trimming surrounding whitespace
+    fn push_lines(
+        source: &str,
+        lines: &[Line],
+        lo: usize,
+        hi: usize,
+        kind: SemanticKind,
+        units: &mut Vec<SemanticUnit>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let region = &source[lines[lo].start..lines[hi - 1].end];
+        let text = region.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        let start = lines[lo].start + (region.len() - region.trim_start().len());
+        units.push(SemanticUnit {
+            text: text.to_string(),
+            start_offset: start,
+            end_offset: start + text.len(),
+            kind,
+        });
+    }
+}
@@ -1,4 +1,7 @@
-use super::{Parser, UnknownParser};
+use super::{
+    FileMetadata, JsonParser, MarkdownParser, ParseResult, Parser, PythonParser, RustParser,
+    TomlParser, UnknownParser, YamlParser,
+};
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -14,11 +17,24 @@ impl ParserRegistry {
     /// Create a new registry with UnknownParser as fallback
     pub fn new() -> Self {
         Self {
-            fallback: Box::new(UnknownParser),
+            fallback: Box::new(UnknownParser::new()),
             map: HashMap::new(),
         }
     }
 
+    /// Create a registry with all built-in language parsers registered
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("rs", RustParser);
+        registry.register("py", PythonParser);
+        registry.register("md", MarkdownParser);
+        registry.register("json", JsonParser);
+        registry.register("toml", TomlParser);
+        registry.register("yaml", YamlParser);
+        registry.register("yml", YamlParser);
+        registry
+    }
+
     /// Register a parser for a specific file extension
     ///
     /// # Arguments
@@ -27,24 +43,73 @@ impl ParserRegistry {
     ///
     /// # Example
     /// ```ignore
-    /// registry.register("rs", RustParser::new());
-    /// registry.register("py", PythonParser::new());
+    /// registry.register("rs", RustParser);
+    /// registry.register("py", PythonParser);
     /// ```
     pub fn register(&mut self, extension: impl Into<String>, parser: impl Parser + 'static) {
         self.map.insert(extension.into(), Box::new(parser));
     }
 
+    /// Replace the fallback parser used when no registered parser matches
+    pub fn set_fallback(&mut self, parser: impl Parser + 'static) {
+        self.fallback = Box::new(parser);
+    }
+
     /// Select the appropriate parser for a given file path
     ///
     /// Falls back to UnknownParser if no extension-specific parser exists
     pub fn select(&self, path: &str) -> &dyn Parser {
-        let ext = Path::new(path)
+        self.map
+            .get(&Self::extension(path))
+            .map(|p| &**p)
+            .unwrap_or(&*self.fallback)
+    }
+
+    /// Select a parser using the file contents as well as its path
+    ///
+    /// Registered extensions win. Otherwise well-known file names
+    /// (`Dockerfile`, `Makefile`, `CMakeLists.txt`) and `#!` interpreter
+    /// lines are mapped to keys like "dockerfile" or "py" and looked up in
+    /// the registry, before falling back to UnknownParser.
+    pub fn select_with_content(&self, path: &str, bytes: &[u8]) -> &dyn Parser {
+        if let Some(parser) = self.map.get(&Self::extension(path)) {
+            return &**parser;
+        }
+
+        FileMetadata::sniff_extension(path, bytes)
+            .and_then(|key| self.map.get(key))
+            .map(|p| &**p)
+            .unwrap_or(&*self.fallback)
+    }
+
+    /// Parse many files, returning results in the same order as the input
+    ///
+    /// With the `rayon` feature (on by default) files are parsed in parallel;
+    /// without it they are parsed sequentially.
+    pub fn parse_all(&self, files: &[(&str, &[u8])]) -> Vec<ParseResult> {
+        let parse = |&(path, bytes): &(&str, &[u8])| {
+            self.select_with_content(path, bytes).parse(path, bytes)
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            files.par_iter().map(parse).collect()
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            files.iter().map(parse).collect()
+        }
+    }
+
+    /// Lowercased extension of a path, or empty if it has none
+    fn extension(path: &str) -> String {
+        Path::new(path)
             .extension()
             .and_then(|e| e.to_str())
             .map(|s| s.to_lowercase())
-            .unwrap_or_default();
-
-        self.map.get(&ext).map(|p| &**p).unwrap_or(&*self.fallback)
+            .unwrap_or_default()
     }
 
     /// Get the number of registered parsers (excluding fallback)
@@ -81,7 +146,7 @@ mod tests {
     #[test]
     fn test_extension_selection() {
         let mut registry = ParserRegistry::new();
-        registry.register("test", UnknownParser);
+        registry.register("test", UnknownParser::new());
 
         assert_eq!(registry.parser_count(), 1);
         assert!(registry.registered_extensions().contains(&"test"));
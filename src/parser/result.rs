@@ -1,3 +1,11 @@
+use serde::Serialize;
+
+/// Number of leading bytes sampled when classifying a file as binary
+pub const BINARY_SAMPLE_SIZE: usize = 8000;
+
+/// Default fraction of control bytes above which a file is considered binary
+pub const DEFAULT_BINARY_THRESHOLD: f64 = 0.3;
+
 /// Result of parsing any file type
 #[derive(Debug, Clone)]
 pub struct ParseResult {
@@ -7,6 +15,91 @@ pub struct ParseResult {
     pub metadata: FileMetadata,
     /// Semantic units for chunk splitting
     pub semantic_units: Vec<SemanticUnit>,
+    /// Named definitions found in the file (empty for unstructured formats)
+    pub symbols: Vec<ParsedSymbol>,
+    /// Byte offset of the start of each line in `normalized_text`
+    pub line_index: Vec<usize>,
+    /// Problems that made the parser degrade (e.g., to line chunking)
+    pub errors: Vec<ParseWarning>,
+}
+
+/// Why a parser produced less structure than it normally would
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// What went wrong and what the parser did instead
+    pub message: String,
+    /// 1-based line of the problem, if known
+    pub line: Option<usize>,
+}
+
+impl ParseWarning {
+    pub fn new(message: impl Into<String>, line: Option<usize>) -> Self {
+        Self {
+            message: message.into(),
+            line,
+        }
+    }
+}
+
+impl ParseResult {
+    /// Assemble a parse result, building the line index from the text
+    pub fn new(
+        normalized_text: String,
+        metadata: FileMetadata,
+        semantic_units: Vec<SemanticUnit>,
+        symbols: Vec<ParsedSymbol>,
+    ) -> Self {
+        let line_index = Self::build_line_index(&normalized_text);
+        Self {
+            normalized_text,
+            metadata,
+            semantic_units,
+            symbols,
+            line_index,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Record a problem encountered while producing this result
+    pub fn with_warning(mut self, warning: ParseWarning) -> Self {
+        self.errors.push(warning);
+        self
+    }
+
+    /// 1-based line number containing `byte_offset` in `normalized_text`
+    ///
+    /// Offsets past the end of the text map to the last line.
+    pub fn line_at(&self, byte_offset: usize) -> usize {
+        self.line_index
+            .partition_point(|&start| start <= byte_offset)
+            .max(1)
+    }
+
+    /// Compute line start offsets; a trailing newline doesn't start a line
+    fn build_line_index(text: &str) -> Vec<usize> {
+        let mut index = vec![0];
+        index.extend(
+            text.bytes()
+                .enumerate()
+                .filter(|&(i, b)| b == b'\n' && i + 1 < text.len())
+                .map(|(i, _)| i + 1),
+        );
+        index
+    }
+}
+
+/// A named definition extracted by a language parser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSymbol {
+    /// Symbol name, qualified by its enclosing type where applicable
+    /// (e.g., "Point::new" in Rust, "Point.norm" in Python)
+    pub name: String,
+    /// Semantic type of the definition
+    pub kind: SemanticKind,
+    /// 1-based line where the name appears
+    pub start_line: usize,
+    /// Declaration line, trimmed (e.g., "pub fn new(x: i32) -> Self")
+    pub signature: String,
 }
 
 /// Metadata extracted during parsing
@@ -24,6 +117,71 @@ pub struct FileMetadata {
     pub line_count: usize,
     /// Whether file is valid UTF-8
     pub is_utf8: bool,
+    /// Whether the content looks binary rather than text in some encoding
+    pub is_binary: bool,
+    /// Whether the original bytes started with a UTF-8 byte order mark
+    /// (stripped from the normalized text)
+    pub had_bom: bool,
+    /// Line endings used by the original bytes (normalized text uses LF)
+    pub original_line_ending: LineEnding,
+}
+
+/// Line ending convention detected in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Unix `\n` (also used when the file has no line breaks)
+    #[default]
+    Lf,
+    /// Windows `\r\n`
+    Crlf,
+    /// Classic Mac `\r`
+    Cr,
+    /// More than one convention in the same file
+    Mixed,
+}
+
+impl LineEnding {
+    /// Detect the line ending convention used in raw bytes
+    pub fn detect(bytes: &[u8]) -> Self {
+        let (mut lf, mut crlf, mut cr) = (false, false, false);
+        let mut i = 0;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    crlf = true;
+                    i += 1;
+                }
+                b'\r' => cr = true,
+                b'\n' => lf = true,
+                _ => {}
+            }
+            i += 1;
+        }
+
+        match (lf, crlf, cr) {
+            (_, false, false) => LineEnding::Lf,
+            (false, true, false) => LineEnding::Crlf,
+            (false, false, true) => LineEnding::Cr,
+            _ => LineEnding::Mixed,
+        }
+    }
+}
+
+/// Decode UTF-8 bytes into normalized text
+///
+/// Strips a leading byte order mark and converts CRLF and lone CR line
+/// endings to LF, so offsets and embeddings don't depend on the platform
+/// a file was written on. Returns None if the bytes aren't valid UTF-8.
+pub fn normalize_utf8(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    if !text.contains('\r') {
+        return Some(text.to_string());
+    }
+
+    Some(text.replace("\r\n", "\n").replace('\r', "\n"))
 }
 
 /// A semantic unit representing a chunkable section
@@ -31,16 +189,16 @@ pub struct FileMetadata {
 pub struct SemanticUnit {
     /// Text content of this unit
     pub text: String,
-    /// Byte offset in original file (start)
+    /// Byte offset in normalized text (start)
     pub start_offset: usize,
-    /// Byte offset in original file (end)
+    /// Byte offset in normalized text (end)
     pub end_offset: usize,
     /// Semantic type of this unit
     pub kind: SemanticKind,
 }
 
 /// Classification of semantic units
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum SemanticKind {
     /// Unknown or unclassified content
     Unknown,
@@ -75,15 +233,37 @@ impl FileMetadata {
 
         // Try to detect if it's valid UTF-8
         let is_utf8 = std::str::from_utf8(bytes).is_ok();
+        let language = Self::detect_language(path, bytes);
 
         Self {
             path: path.to_string(),
             extension: extension.clone(),
-            language: Self::guess_language(&extension),
+            language,
             size_bytes: bytes.len(),
             line_count: 0, // Will be set after parsing
             is_utf8,
+            is_binary: !is_utf8 && Self::detect_binary(bytes, DEFAULT_BINARY_THRESHOLD),
+            had_bom: bytes.starts_with(b"\xEF\xBB\xBF"),
+            original_line_ending: LineEnding::detect(bytes),
+        }
+    }
+
+    /// Language of a file from its extension, falling back to the file
+    /// name or shebang when the extension is unknown
+    pub fn detect_language(path: &str, bytes: &[u8]) -> String {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        let language = Self::guess_language(&extension);
+        if language == "unknown"
+            && let Some(key) = Self::sniff_extension(path, bytes)
+        {
+            return Self::guess_language(key);
         }
+        language
     }
 
     /// Heuristic language detection from extension
@@ -110,12 +290,77 @@ impl FileMetadata {
             "html" => "html",
             "css" => "css",
             "sh" | "bash" => "shell",
+            "dockerfile" => "dockerfile",
+            "makefile" | "mk" => "makefile",
+            "cmake" => "cmake",
             "sql" => "sql",
             _ => "unknown",
         }
         .to_string()
     }
 
+    /// Classify bytes as binary by sampling the start of the file
+    ///
+    /// Like git, any NUL byte in the first `BINARY_SAMPLE_SIZE` bytes marks
+    /// the file as binary. Otherwise it is binary only if the fraction of
+    /// control bytes (other than common whitespace and escape) exceeds
+    /// `threshold`, so Latin-1 text or a stray invalid byte stays text.
+    pub fn detect_binary(bytes: &[u8], threshold: f64) -> bool {
+        let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_SIZE)];
+        if sample.is_empty() {
+            return false;
+        }
+
+        if sample.contains(&0) {
+            return true;
+        }
+
+        let control = sample
+            .iter()
+            .filter(|&&b| {
+                (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || b == 0x7f
+            })
+            .count();
+
+        control as f64 / sample.len() as f64 > threshold
+    }
+
+    /// Infer an extension-like key for files whose extension doesn't
+    /// identify them, from well-known file names or a `#!` interpreter line
+    ///
+    /// Returns keys such as "dockerfile", "makefile", "cmake", "py" or "sh".
+    pub(crate) fn sniff_extension(path: &str, bytes: &[u8]) -> Option<&'static str> {
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+
+        match file_name {
+            "Dockerfile" | "Containerfile" => return Some("dockerfile"),
+            "Makefile" | "makefile" | "GNUmakefile" => return Some("makefile"),
+            "CMakeLists.txt" => return Some("cmake"),
+            _ => {}
+        }
+
+        let first_line = bytes.split(|&b| b == b'\n').next()?;
+        let interpreter_line = std::str::from_utf8(first_line.strip_prefix(b"#!")?).ok()?;
+
+        // `#!/usr/bin/env python3` names the interpreter in the next argument
+        let mut args = interpreter_line.split_whitespace();
+        let mut program = args.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = args.find(|arg| !arg.starts_with('-'))?;
+        }
+
+        if program.starts_with("python") {
+            Some("py")
+        } else if matches!(program, "sh" | "bash" | "zsh" | "dash" | "ksh") {
+            Some("sh")
+        } else {
+            None
+        }
+    }
+
     /// Update line count after text normalization
     pub fn set_line_count(&mut self, count: usize) {
         self.line_count = count;
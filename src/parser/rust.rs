@@ -0,0 +1,242 @@
+use super::{
+    FileMetadata, ParseResult, ParseWarning, ParsedSymbol, Parser, SemanticKind, SemanticUnit,
+    UnknownParser, normalize_utf8,
+};
+use std::ops::Range;
+use syn::spanned::Spanned;
+use syn::{Ident, ImplItem, Item, Type};
+
+/// Parser for Rust source files backed by `syn`
+///
+/// Emits one semantic unit per function, type and module. Source between
+/// items (imports, constants, loose comments) is kept as `Unknown` or
+/// `Comment` units so no text is lost. Files that fail to parse fall back
+/// to line-based chunking.
+pub struct RustParser;
+
+/// Location of a parsed item in the source
+struct ItemSpan {
+    /// Byte range of the whole item, including attributes and doc comments
+    range: Range<usize>,
+    kind: SemanticKind,
+    /// Qualified name (e.g., "Point::new" for methods)
+    name: String,
+    /// Byte offset of the item's identifier
+    name_offset: usize,
+}
+
+impl Parser for RustParser {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        let Some(text) = normalize_utf8(bytes) else {
+            return UnknownParser::new().parse(path, bytes);
+        };
+        let source = text.as_str();
+
+        let items = match Self::item_ranges(source) {
+            Ok(items) => items,
+            Err(warning) => {
+                return UnknownParser::new()
+                    .parse(path, bytes)
+                    .with_warning(warning);
+            }
+        };
+
+        let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+        metadata.set_line_count(source.lines().count());
+
+        let symbols = items
+            .iter()
+            .map(|item| Self::symbol(source, item))
+            .collect();
+        let units = Self::build_units(source, &items);
+
+        ParseResult::new(text, metadata, units, symbols)
+    }
+}
+
+impl RustParser {
+    /// Parse the source and collect the spans of interesting items
+    ///
+    /// Fails with the syntax error if the file is not valid Rust.
+    ///
+    /// Spans are recorded in a thread-local source map that grows with
+    /// every parse and is only freed with its thread, so parsing happens on
+    /// a short-lived worker thread. Only owned offsets leave it; no span
+    /// escapes this function.
+    fn item_ranges(source: &str) -> Result<Vec<ItemSpan>, ParseWarning> {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| Self::parse_items(source))
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    fn parse_items(source: &str) -> Result<Vec<ItemSpan>, ParseWarning> {
+        // syn::parse_file strips these itself, which would shift every span
+        let base = Self::preamble_len(source);
+
+        syn::parse_str::<syn::File>(&source[base..])
+            .map_err(|e| {
+                let line = e.span().start().line + source[..base].matches('\n').count();
+                ParseWarning::new(
+                    format!("invalid Rust, fell back to line chunking: {}", e),
+                    Some(line),
+                )
+            })
+            .map(|file| {
+                let mut items = Vec::new();
+                for item in &file.items {
+                    Self::collect_item(item, &mut items);
+                }
+                for item in &mut items {
+                    item.range = item.range.start + base..item.range.end + base;
+                    item.name_offset += base;
+                }
+                items
+            })
+    }
+
+    /// Length of a leading BOM and shebang line, if present
+    fn preamble_len(source: &str) -> usize {
+        let mut len = 0;
+        if source.starts_with('\u{feff}') {
+            len += '\u{feff}'.len_utf8();
+        }
+
+        let rest = &source[len..];
+        if rest.starts_with("#!") && !rest[2..].trim_start().starts_with('[') {
+            len += rest.find('\n').unwrap_or(rest.len());
+        }
+
+        len
+    }
+
+    /// Record the span of a top-level item, descending into impl blocks
+    fn collect_item(item: &Item, out: &mut Vec<ItemSpan>) {
+        let (kind, ident) = match item {
+            Item::Fn(f) => (SemanticKind::Function, &f.sig.ident),
+            Item::Struct(s) => (SemanticKind::Class, &s.ident),
+            Item::Enum(e) => (SemanticKind::Class, &e.ident),
+            Item::Union(u) => (SemanticKind::Class, &u.ident),
+            Item::Trait(t) => (SemanticKind::Class, &t.ident),
+            Item::TraitAlias(t) => (SemanticKind::Class, &t.ident),
+            Item::Mod(m) => (SemanticKind::Module, &m.ident),
+            Item::Impl(item_impl) => {
+                // Qualify methods with the implementing type's name
+                let self_name = match &*item_impl.self_ty {
+                    Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+                    _ => None,
+                };
+
+                for impl_item in &item_impl.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        let name = match &self_name {
+                            Some(self_name) => format!("{}::{}", self_name, method.sig.ident),
+                            None => method.sig.ident.to_string(),
+                        };
+                        out.push(Self::item_span(
+                            method.span(),
+                            SemanticKind::Function,
+                            name,
+                            &method.sig.ident,
+                        ));
+                    }
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        out.push(Self::item_span(item.span(), kind, ident.to_string(), ident));
+    }
+
+    /// Resolve spans to byte offsets while they are still valid
+    fn item_span(
+        span: proc_macro2::Span,
+        kind: SemanticKind,
+        name: String,
+        ident: &Ident,
+    ) -> ItemSpan {
+        ItemSpan {
+            range: span.byte_range(),
+            kind,
+            name,
+            name_offset: ident.span().byte_range().start,
+        }
+    }
+
+    /// Build the symbol entry for an item from the line holding its name
+    fn symbol(source: &str, item: &ItemSpan) -> ParsedSymbol {
+        let offset = item.name_offset.min(source.len());
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+
+        ParsedSymbol {
+            name: item.name.clone(),
+            kind: item.kind,
+            start_line: source[..line_start].matches('\n').count() + 1,
+            signature: source[line_start..line_end]
+                .trim()
+                .trim_end_matches('{')
+                .trim_end()
+                .to_string(),
+        }
+    }
+
+    /// Turn item ranges into semantic units, filling the gaps between them
+    fn build_units(source: &str, items: &[ItemSpan]) -> Vec<SemanticUnit> {
+        let mut units = Vec::new();
+        let mut cursor = 0;
+
+        for item in items {
+            let range = item.range.clone();
+            if range.start < cursor || range.end > source.len() {
+                continue;
+            }
+
+            Self::push_gap(source, cursor, range.start, &mut units);
+            units.push(SemanticUnit {
+                text: source[range.clone()].to_string(),
+                start_offset: range.start,
+                end_offset: range.end,
+                kind: item.kind,
+            });
+            cursor = range.end;
+        }
+
+        Self::push_gap(source, cursor, source.len(), &mut units);
+        units
+    }
+
+    /// Emit the text between two items, skipping whitespace and lone braces
+    fn push_gap(source: &str, start: usize, end: usize, units: &mut Vec<SemanticUnit>) {
+        let gap = &source[start..end];
+        if !gap.chars().any(char::is_alphanumeric) {
+            return;
+        }
+
+        let start = start + (gap.len() - gap.trim_start().len());
+        let end = end - (gap.len() - gap.trim_end().len());
+        let text = &source[start..end];
+
+        let is_comment = text
+            .lines()
+            .map(str::trim_start)
+            .filter(|line| !line.is_empty())
+            .all(|line| line.starts_with("//") || line.starts_with("/*") || line.starts_with('*'));
+
+        units.push(SemanticUnit {
+            text: text.to_string(),
+            start_offset: start,
+            end_offset: end,
+            kind: if is_comment {
+                SemanticKind::Comment
+            } else {
+                SemanticKind::Unknown
+            },
+        });
+    }
+}
@@ -1,350 +1,1435 @@
-#[cfg(test)]
-mod tests {
-    use crate::{FileMetadata, ParseResult, Parser, ParserRegistry, SemanticKind, UnknownParser};
-
-    // ========================================================================
-    // FileMetadata Tests
-    // ========================================================================
-
-    #[test]
-    fn test_metadata_from_rust_file() {
-        let metadata = FileMetadata::from_path_and_bytes("src/main.rs", b"fn main() {}");
-        assert_eq!(metadata.path, "src/main.rs");
-        assert_eq!(metadata.extension, "rs");
-        assert_eq!(metadata.language, "rust");
-        assert_eq!(metadata.size_bytes, 12);
-        assert!(metadata.is_utf8);
-    }
+use crate::{
+    FileMetadata, JsonParser, LineEnding, MarkdownParser, ParseResult, ParsedSymbol, Parser,
+    ParserRegistry, PythonParser, RustParser, SemanticKind, TomlParser, UnknownParser, YamlParser,
+};
 
-    #[test]
-    fn test_metadata_from_python_file() {
-        let metadata = FileMetadata::from_path_and_bytes("script.py", b"print('hello')");
-        assert_eq!(metadata.extension, "py");
-        assert_eq!(metadata.language, "python");
-    }
+// ========================================================================
+// FileMetadata Tests
+// ========================================================================
 
-    #[test]
-    fn test_metadata_language_detection() {
-        let test_cases = vec![
-            ("file.rs", "rust"),
-            ("file.py", "python"),
-            ("file.js", "javascript"),
-            ("file.ts", "typescript"),
-            ("file.go", "go"),
-            ("file.java", "java"),
-            ("file.md", "markdown"),
-            ("file.json", "json"),
-            ("file.yaml", "yaml"),
-            ("file.toml", "toml"),
-            ("file.cpp", "cpp"),
-            ("file.c", "c"),
-            ("file.unknown", "unknown"),
-        ];
-
-        for (path, expected_lang) in test_cases {
-            let metadata = FileMetadata::from_path_and_bytes(path, b"");
-            assert_eq!(metadata.language, expected_lang, "Failed for {}", path);
-        }
-    }
+#[test]
+fn test_metadata_from_rust_file() {
+    let metadata = FileMetadata::from_path_and_bytes("src/main.rs", b"fn main() {}");
+    assert_eq!(metadata.path, "src/main.rs");
+    assert_eq!(metadata.extension, "rs");
+    assert_eq!(metadata.language, "rust");
+    assert_eq!(metadata.size_bytes, 12);
+    assert!(metadata.is_utf8);
+}
 
-    #[test]
-    fn test_metadata_no_extension() {
-        let metadata = FileMetadata::from_path_and_bytes("Makefile", b"all:");
-        assert_eq!(metadata.extension, "");
-        assert_eq!(metadata.language, "unknown");
+#[test]
+fn test_metadata_from_python_file() {
+    let metadata = FileMetadata::from_path_and_bytes("script.py", b"print('hello')");
+    assert_eq!(metadata.extension, "py");
+    assert_eq!(metadata.language, "python");
+}
+
+#[test]
+fn test_metadata_language_detection() {
+    let test_cases = vec![
+        ("file.rs", "rust"),
+        ("file.py", "python"),
+        ("file.js", "javascript"),
+        ("file.ts", "typescript"),
+        ("file.go", "go"),
+        ("file.java", "java"),
+        ("file.md", "markdown"),
+        ("file.json", "json"),
+        ("file.yaml", "yaml"),
+        ("file.toml", "toml"),
+        ("file.cpp", "cpp"),
+        ("file.c", "c"),
+        ("file.unknown", "unknown"),
+    ];
+
+    for (path, expected_lang) in test_cases {
+        let metadata = FileMetadata::from_path_and_bytes(path, b"");
+        assert_eq!(metadata.language, expected_lang, "Failed for {}", path);
     }
+}
+
+#[test]
+fn test_metadata_no_extension() {
+    let metadata = FileMetadata::from_path_and_bytes("LICENSE", b"MIT");
+    assert_eq!(metadata.extension, "");
+    assert_eq!(metadata.language, "unknown");
+
+    // Well-known extensionless names are detected from the file name
+    let metadata = FileMetadata::from_path_and_bytes("Makefile", b"all:");
+    assert_eq!(metadata.extension, "");
+    assert_eq!(metadata.language, "makefile");
+}
+
+#[test]
+fn test_metadata_binary_detection() {
+    let binary_data = vec![0xFF, 0xFE, 0x00, 0x01, 0x02];
+    let metadata = FileMetadata::from_path_and_bytes("binary.dat", &binary_data);
+    assert!(!metadata.is_utf8);
+}
+
+#[test]
+fn test_metadata_utf8_detection() {
+    let metadata = FileMetadata::from_path_and_bytes("text.txt", "Hello, 世界".as_bytes());
+    assert!(metadata.is_utf8);
+}
+
+#[test]
+fn test_metadata_line_ending_detection() {
+    let cases: Vec<(&[u8], LineEnding)> = vec![
+        (b"a\nb\n", LineEnding::Lf),
+        (b"a\r\nb\r\n", LineEnding::Crlf),
+        (b"a\rb\r", LineEnding::Cr),
+        (b"a\r\nb\n", LineEnding::Mixed),
+        (b"no newline", LineEnding::Lf),
+    ];
 
-    #[test]
-    fn test_metadata_binary_detection() {
-        let binary_data = vec![0xFF, 0xFE, 0x00, 0x01, 0x02];
-        let metadata = FileMetadata::from_path_and_bytes("binary.dat", &binary_data);
-        assert!(!metadata.is_utf8);
+    for (bytes, expected) in cases {
+        let metadata = FileMetadata::from_path_and_bytes("f.txt", bytes);
+        assert_eq!(
+            metadata.original_line_ending, expected,
+            "Failed for {:?}",
+            bytes
+        );
     }
+}
+
+#[test]
+fn test_metadata_bom_detection() {
+    let metadata = FileMetadata::from_path_and_bytes("f.txt", b"\xEF\xBB\xBFhello");
+    assert!(metadata.had_bom);
+    assert!(metadata.is_utf8);
+
+    let metadata = FileMetadata::from_path_and_bytes("f.txt", b"hello");
+    assert!(!metadata.had_bom);
+}
+
+// ========================================================================
+// ParseResult Tests
+// ========================================================================
+
+#[test]
+fn test_line_index() {
+    let result = UnknownParser::new().parse("f.txt", b"ab\ncd\n\nef\n");
+
+    assert_eq!(result.line_index, vec![0, 3, 6, 7]);
+    assert_eq!(result.line_at(0), 1);
+    assert_eq!(result.line_at(2), 1); // the newline belongs to its line
+    assert_eq!(result.line_at(3), 2);
+    assert_eq!(result.line_at(6), 3);
+    assert_eq!(result.line_at(8), 4);
+    assert_eq!(result.line_at(100), 4);
+}
 
-    #[test]
-    fn test_metadata_utf8_detection() {
-        let metadata = FileMetadata::from_path_and_bytes("text.txt", "Hello, 世界".as_bytes());
-        assert!(metadata.is_utf8);
+#[test]
+fn test_line_index_empty_and_no_trailing_newline() {
+    let result = UnknownParser::new().parse("f.txt", b"");
+    assert_eq!(result.line_index, vec![0]);
+    assert_eq!(result.line_at(0), 1);
+
+    let result = UnknownParser::new().parse("f.txt", b"one\ntwo");
+    assert_eq!(result.line_index, vec![0, 4]);
+    assert_eq!(result.line_at(5), 2);
+}
+
+#[test]
+fn test_line_at_matches_unit_offsets() {
+    let result = RustParser.parse("src/lib.rs", RUST_SOURCE.as_bytes());
+
+    for symbol in &result.symbols {
+        let unit = result
+            .semantic_units
+            .iter()
+            .find(|u| u.text.contains(&symbol.signature))
+            .unwrap();
+        assert!(result.line_at(unit.start_offset) <= symbol.start_line);
+        assert!(result.line_at(unit.end_offset - 1) >= symbol.start_line);
     }
+}
+
+// ========================================================================
+// UnknownParser Tests
+// ========================================================================
+
+#[test]
+fn test_parse_simple_text() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("test.txt", b"line1\nline2\nline3");
+
+    assert_eq!(result.metadata.path, "test.txt");
+    assert_eq!(result.metadata.line_count, 3);
+    assert!(result.metadata.is_utf8);
+    assert_eq!(result.semantic_units.len(), 3);
+}
+
+#[test]
+fn test_parse_empty_file() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("empty.txt", b"");
+
+    assert_eq!(result.metadata.line_count, 0);
+    assert_eq!(result.semantic_units.len(), 0);
+    assert_eq!(result.normalized_text, "");
+}
+
+#[test]
+fn test_parse_single_line_no_newline() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("single.txt", b"single line");
 
-    // ========================================================================
-    // UnknownParser Tests
-    // ========================================================================
+    assert_eq!(result.metadata.line_count, 1);
+    assert_eq!(result.semantic_units.len(), 1);
+    // UnknownParser adds newlines to each line
+    assert_eq!(result.semantic_units[0].text, "single line\n");
+}
+
+#[test]
+fn test_parse_binary_file() {
+    let parser = UnknownParser::new();
+    let binary = vec![0xFF, 0xFE, 0x41, 0x42, 0x00, 0x01];
+    let result = parser.parse("binary.dat", &binary);
+
+    assert!(!result.metadata.is_utf8);
+    // Should extract only printable ASCII (A and B)
+    assert!(result.normalized_text.contains('A'));
+    assert!(result.normalized_text.contains('B'));
+}
+
+#[test]
+fn test_semantic_unit_offsets() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("test.txt", b"line1\nline2\nline3");
+
+    // First unit
+    assert_eq!(result.semantic_units[0].start_offset, 0);
+    assert_eq!(result.semantic_units[0].end_offset, 6); // "line1\n"
+
+    // Second unit
+    assert_eq!(result.semantic_units[1].start_offset, 6);
+    assert_eq!(result.semantic_units[1].end_offset, 12); // "line2\n"
+
+    // Third unit
+    assert_eq!(result.semantic_units[2].start_offset, 12);
+    assert_eq!(result.semantic_units[2].end_offset, 18); // "line3\n"
+}
+
+#[test]
+fn test_parse_crlf_file_normalized() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("win.txt", b"line1\r\nline2\r\nline3\r\n");
+
+    assert_eq!(result.normalized_text, "line1\nline2\nline3\n");
+    assert_eq!(result.metadata.original_line_ending, LineEnding::Crlf);
+    assert_eq!(result.semantic_units.len(), 3);
+    assert_eq!(result.semantic_units[1].text, "line2\n");
+    assert_eq!(result.semantic_units[1].start_offset, 6);
+}
 
-    #[test]
-    fn test_parse_simple_text() {
-        let parser = UnknownParser;
-        let result = parser.parse("test.txt", b"line1\nline2\nline3");
+#[test]
+fn test_parse_bom_file_stripped() {
+    let result = UnknownParser::new().parse("bom.txt", b"\xEF\xBB\xBFhello\n");
+    assert_eq!(result.normalized_text, "hello\n");
+    assert!(result.metadata.had_bom);
 
-        assert_eq!(result.metadata.path, "test.txt");
-        assert_eq!(result.metadata.line_count, 3);
-        assert!(result.metadata.is_utf8);
-        assert_eq!(result.semantic_units.len(), 3);
+    let result = RustParser.parse("bom.rs", b"\xEF\xBB\xBF\r\nfn main() {}\r\n");
+    let main = &result.semantic_units[0];
+    assert_eq!(main.text, "fn main() {}");
+    assert_eq!(
+        &result.normalized_text[main.start_offset..main.end_offset],
+        main.text
+    );
+    assert!(!result.normalized_text.contains('\r'));
+}
+
+#[test]
+fn test_parse_utf16le_file() {
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in "Hello\nWorld".encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
     }
 
-    #[test]
-    fn test_parse_empty_file() {
-        let parser = UnknownParser;
-        let result = parser.parse("empty.txt", b"");
+    let result = UnknownParser::new().parse("win.txt", &bytes);
+
+    assert_eq!(result.normalized_text, "Hello\nWorld");
+    assert_eq!(result.semantic_units.len(), 2);
+    assert_eq!(result.semantic_units[0].text, "Hello\n");
+    assert_eq!(result.semantic_units[1].text, "World\n");
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+}
 
-        assert_eq!(result.metadata.line_count, 0);
-        assert_eq!(result.semantic_units.len(), 0);
-        assert_eq!(result.normalized_text, "");
+#[test]
+fn test_parse_utf16be_crlf_file() {
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in "a\r\nb\r\n".encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
     }
 
-    #[test]
-    fn test_parse_single_line_no_newline() {
-        let parser = UnknownParser;
-        let result = parser.parse("single.txt", b"single line");
+    let result = UnknownParser::new().parse("reg.reg", &bytes);
+
+    assert_eq!(result.normalized_text, "a\nb\n");
+    assert_eq!(result.metadata.original_line_ending, LineEnding::Crlf);
+}
+
+#[test]
+fn test_parse_latin1_text_not_binary() {
+    // "café\nnaïve\n" in Latin-1 is not valid UTF-8
+    let latin1 = b"caf\xE9\nna\xEFve\n";
+    let result = UnknownParser::new().parse("notes.txt", latin1);
+
+    assert!(!result.metadata.is_utf8);
+    assert!(!result.metadata.is_binary);
+    assert_eq!(result.normalized_text, "café\nnaïve\n");
+    assert_eq!(result.semantic_units.len(), 2);
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+}
+
+#[test]
+fn test_parse_true_binary_detected() {
+    let binary: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+    let result = UnknownParser::new().parse("image.png", &binary);
+
+    assert!(result.metadata.is_binary);
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Blob)
+    );
+}
+
+#[test]
+fn test_binary_threshold_option() {
+    // 2 control bytes out of 10, no NULs
+    let bytes = b"abc\x01def\x02gh";
+    assert!(FileMetadata::detect_binary(bytes, 0.1));
+    assert!(!FileMetadata::detect_binary(bytes, 0.3));
+
+    let strict = UnknownParser::new().with_binary_threshold(0.1);
+    assert!(
+        strict
+            .parse("f.dat", b"\xFFbc\x01def\x02gh")
+            .metadata
+            .is_binary
+    );
+
+    // The unit struct still works as a value, with the default threshold
+    let lenient = UnknownParser;
+    assert!(
+        !lenient
+            .parse("f.dat", b"\xFFbc\x01def\x02gh")
+            .metadata
+            .is_binary
+    );
+}
+
+#[test]
+fn test_registry_set_fallback() {
+    let mut registry = ParserRegistry::new();
+    registry.set_fallback(UnknownParser::new().with_binary_threshold(0.0));
+
+    let result = registry.select("x.bin").parse("x.bin", b"\xFFabc\x01");
+    assert!(result.metadata.is_binary);
+}
 
-        assert_eq!(result.metadata.line_count, 1);
-        assert_eq!(result.semantic_units.len(), 1);
-        // UnknownParser adds newlines to each line
-        assert_eq!(result.semantic_units[0].text, "single line\n");
+#[test]
+fn test_semantic_unit_kind_text_file() {
+    let parser = UnknownParser::new();
+    let result = parser.parse("text.txt", b"content");
+
+    for unit in &result.semantic_units {
+        assert_eq!(unit.kind, SemanticKind::Unknown);
     }
+}
 
-    #[test]
-    fn test_parse_binary_file() {
-        let parser = UnknownParser;
-        let binary = vec![0xFF, 0xFE, 0x41, 0x42, 0x00, 0x01];
-        let result = parser.parse("binary.dat", &binary);
+#[test]
+fn test_semantic_unit_kind_binary_file() {
+    let parser = UnknownParser::new();
+    let binary = vec![0xFF, 0xFE, 0x00];
+    let result = parser.parse("binary.dat", &binary);
 
-        assert!(!result.metadata.is_utf8);
-        // Should extract only printable ASCII (A and B)
-        assert!(result.normalized_text.contains('A'));
-        assert!(result.normalized_text.contains('B'));
+    for unit in &result.semantic_units {
+        assert_eq!(unit.kind, SemanticKind::Blob);
     }
+}
 
-    #[test]
-    fn test_semantic_unit_offsets() {
-        let parser = UnknownParser;
-        let result = parser.parse("test.txt", b"line1\nline2\nline3");
+#[test]
+fn test_parse_utf8_with_unicode() {
+    let parser = UnknownParser::new();
+    let text = "Hello 世界\nBonjour 🌍\n";
+    let result = parser.parse("unicode.txt", text.as_bytes());
 
-        // First unit
-        assert_eq!(result.semantic_units[0].start_offset, 0);
-        assert_eq!(result.semantic_units[0].end_offset, 6); // "line1\n"
+    assert!(result.metadata.is_utf8);
+    assert_eq!(result.metadata.line_count, 2);
+    assert_eq!(result.normalized_text, text);
+}
 
-        // Second unit
-        assert_eq!(result.semantic_units[1].start_offset, 6);
-        assert_eq!(result.semantic_units[1].end_offset, 12); // "line2\n"
+#[test]
+fn test_extract_printable_from_binary() {
+    let parser = UnknownParser::new();
+    // Binary with embedded ASCII text
+    let binary = b"\xFF\xFEHELLO\x00\x01WORLD\xFF";
+    let result = parser.parse("mixed.dat", binary);
 
-        // Third unit
-        assert_eq!(result.semantic_units[2].start_offset, 12);
-        assert_eq!(result.semantic_units[2].end_offset, 18); // "line3\n"
-    }
+    assert!(result.normalized_text.contains("HELLO"));
+    assert!(result.normalized_text.contains("WORLD"));
+    assert!(!result.normalized_text.contains('\u{FF}')); // Non-printable removed
+}
 
-    #[test]
-    fn test_semantic_unit_kind_text_file() {
-        let parser = UnknownParser;
-        let result = parser.parse("text.txt", b"content");
+// ========================================================================
+// ParserRegistry Tests
+// ========================================================================
+
+#[test]
+fn test_registry_new() {
+    let registry = ParserRegistry::new();
+    assert_eq!(registry.parser_count(), 0);
+}
+
+#[test]
+fn test_registry_register() {
+    let mut registry = ParserRegistry::new();
+    registry.register("rs", UnknownParser::new());
+
+    assert_eq!(registry.parser_count(), 1);
+    assert!(registry.registered_extensions().contains(&"rs"));
+}
+
+#[test]
+fn test_registry_select_fallback() {
+    let registry = ParserRegistry::new();
+    let parser = registry.select("unknown.xyz");
+
+    // Should return fallback parser (UnknownParser)
+    let result = parser.parse("test.xyz", b"content");
+    assert_eq!(result.metadata.language, "unknown");
+}
 
-        for unit in &result.semantic_units {
-            assert_eq!(unit.kind, SemanticKind::Unknown);
+#[test]
+fn test_registry_select_registered() {
+    let mut registry = ParserRegistry::new();
+
+    // Create a custom test parser
+    struct TestParser;
+    impl Parser for TestParser {
+        fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+            let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+            metadata.language = "custom".to_string(); // Override
+            metadata.set_line_count(0);
+
+            ParseResult::new(String::new(), metadata, vec![], vec![])
         }
     }
 
-    #[test]
-    fn test_semantic_unit_kind_binary_file() {
-        let parser = UnknownParser;
-        let binary = vec![0xFF, 0xFE, 0x00];
-        let result = parser.parse("binary.dat", &binary);
+    registry.register("test", TestParser);
+    let parser = registry.select("file.test");
+    let result = parser.parse("file.test", b"");
+
+    assert_eq!(result.metadata.language, "custom");
+}
+
+#[test]
+fn test_registry_case_insensitive() {
+    let mut registry = ParserRegistry::new();
+    registry.register("rs", UnknownParser::new());
+
+    // Should match regardless of case
+    let parser1 = registry.select("file.rs");
+
+    // All should resolve to the registered parser (not fallback)
+    // We can't directly compare trait objects, but we can test behavior
+    let result = parser1.parse("test.rs", b"");
+    assert_eq!(result.metadata.extension, "rs");
+}
+
+#[test]
+fn test_registry_multiple_extensions() {
+    let mut registry = ParserRegistry::new();
+    registry.register("rs", UnknownParser::new());
+    registry.register("py", UnknownParser::new());
+    registry.register("js", UnknownParser::new());
+
+    assert_eq!(registry.parser_count(), 3);
+
+    let extensions = registry.registered_extensions();
+    assert_eq!(extensions.len(), 3);
+    assert!(extensions.contains(&"rs"));
+    assert!(extensions.contains(&"py"));
+    assert!(extensions.contains(&"js"));
+}
+
+#[test]
+fn test_registry_overwrite_extension() {
+    struct Parser1;
+    impl Parser for Parser1 {
+        fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+            let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+            metadata.language = "parser1".to_string();
+            metadata.set_line_count(0);
+            ParseResult::new(String::new(), metadata, vec![], vec![])
+        }
+    }
 
-        for unit in &result.semantic_units {
-            assert_eq!(unit.kind, SemanticKind::Blob);
+    struct Parser2;
+    impl Parser for Parser2 {
+        fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+            let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+            metadata.language = "parser2".to_string();
+            metadata.set_line_count(0);
+            ParseResult::new(String::new(), metadata, vec![], vec![])
         }
     }
 
-    #[test]
-    fn test_parse_utf8_with_unicode() {
-        let parser = UnknownParser;
-        let text = "Hello 世界\nBonjour 🌍\n";
-        let result = parser.parse("unicode.txt", text.as_bytes());
+    let mut registry = ParserRegistry::new();
+    registry.register("test", Parser1);
+    registry.register("test", Parser2); // Overwrite
+
+    let parser = registry.select("file.test");
+    let result = parser.parse("file.test", b"");
 
-        assert!(result.metadata.is_utf8);
-        assert_eq!(result.metadata.line_count, 2);
-        assert_eq!(result.normalized_text, text);
+    // Should use the second parser
+    assert_eq!(result.metadata.language, "parser2");
+}
+
+#[test]
+fn test_registry_no_extension() {
+    let registry = ParserRegistry::new();
+    let parser = registry.select("Makefile");
+
+    // Should fall back to unknown parser
+    let result = parser.parse("Makefile", b"all:");
+    assert_eq!(result.metadata.extension, "");
+}
+
+#[test]
+fn test_registry_with_defaults() {
+    let registry = ParserRegistry::with_defaults();
+    assert!(registry.registered_extensions().contains(&"rs"));
+    assert!(registry.registered_extensions().contains(&"py"));
+    assert!(registry.registered_extensions().contains(&"md"));
+    assert!(registry.registered_extensions().contains(&"json"));
+    assert!(registry.registered_extensions().contains(&"toml"));
+    assert!(registry.registered_extensions().contains(&"yaml"));
+    assert!(registry.registered_extensions().contains(&"yml"));
+    assert!(registry.registered_extensions().contains(&"py"));
+    assert!(registry.registered_extensions().contains(&"md"));
+    assert!(registry.registered_extensions().contains(&"json"));
+    assert!(registry.registered_extensions().contains(&"toml"));
+    assert!(registry.registered_extensions().contains(&"yaml"));
+    assert!(registry.registered_extensions().contains(&"yml"));
+
+    let result = registry.select("lib.rs").parse("lib.rs", b"fn main() {}");
+    assert_eq!(result.semantic_units[0].kind, SemanticKind::Function);
+}
+
+#[test]
+fn test_select_with_content_file_names() {
+    struct NamedParser(&'static str);
+    impl Parser for NamedParser {
+        fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+            let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
+            metadata.language = self.0.to_string();
+            ParseResult::new(String::new(), metadata, vec![], vec![])
+        }
     }
 
-    #[test]
-    fn test_extract_printable_from_binary() {
-        let parser = UnknownParser;
-        // Binary with embedded ASCII text
-        let binary = b"\xFF\xFEHELLO\x00\x01WORLD\xFF";
-        let result = parser.parse("mixed.dat", binary);
+    let mut registry = ParserRegistry::new();
+    registry.register("dockerfile", NamedParser("docker"));
+    registry.register("makefile", NamedParser("make"));
+    registry.register("cmake", NamedParser("cmake"));
 
-        assert!(result.normalized_text.contains("HELLO"));
-        assert!(result.normalized_text.contains("WORLD"));
-        assert!(!result.normalized_text.contains('\u{FF}')); // Non-printable removed
+    let cases = [
+        ("Dockerfile", "docker"),
+        ("build/Makefile", "make"),
+        ("GNUmakefile", "make"),
+        ("CMakeLists.txt", "cmake"),
+    ];
+    for (path, expected) in cases {
+        let result = registry.select_with_content(path, b"").parse(path, b"");
+        assert_eq!(result.metadata.language, expected, "Failed for {}", path);
     }
+}
+
+#[test]
+fn test_select_with_content_shebangs() {
+    let registry = ParserRegistry::with_defaults();
+
+    let script = b"#!/usr/bin/env python3\ndef main():\n    pass\n";
+    let result = registry
+        .select_with_content("bin/tool", script)
+        .parse("bin/tool", script);
+    assert_eq!(result.metadata.language, "python");
+    assert_eq!(result.semantic_units[1].kind, SemanticKind::Function);
+
+    let script = b"#!/usr/bin/python3.11 -u\nclass A:\n    pass\n";
+    let result = registry
+        .select_with_content("run", script)
+        .parse("run", script);
+    assert_eq!(result.semantic_units[1].kind, SemanticKind::Class);
+}
+
+#[test]
+fn test_select_with_content_shell_shebang() {
+    let mut registry = ParserRegistry::new();
+    registry.register("sh", UnknownParser::new());
 
-    // ========================================================================
-    // ParserRegistry Tests
-    // ========================================================================
+    let script = b"#!/bin/bash\necho hi\n";
+    let result = registry
+        .select_with_content("deploy", script)
+        .parse("deploy", script);
+    assert_eq!(result.metadata.language, "shell");
+
+    let script = b"#!/usr/bin/env -S bash -e\necho hi\n";
+    let metadata = FileMetadata::from_path_and_bytes("deploy", script);
+    assert_eq!(metadata.language, "shell");
+}
 
-    #[test]
-    fn test_registry_new() {
-        let registry = ParserRegistry::new();
-        assert_eq!(registry.parser_count(), 0);
+#[test]
+fn test_select_with_content_extension_wins() {
+    let registry = ParserRegistry::with_defaults();
+
+    // A registered extension takes priority over the shebang
+    let source = b"#!/usr/bin/env python3\n{\"a\": 1}";
+    let result = registry
+        .select_with_content("weird.md", source)
+        .parse("weird.md", source);
+    assert_eq!(result.metadata.language, "markdown");
+}
+
+#[test]
+fn test_select_with_content_fallback() {
+    let registry = ParserRegistry::with_defaults();
+
+    let result = registry
+        .select_with_content("LICENSE", b"MIT License")
+        .parse("LICENSE", b"MIT License");
+    assert_eq!(result.metadata.language, "unknown");
+
+    // Unregistered sniffed key still falls back
+    let result = registry
+        .select_with_content("Dockerfile", b"FROM rust")
+        .parse("Dockerfile", b"FROM rust");
+    assert_eq!(result.metadata.language, "dockerfile");
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+}
+
+#[test]
+fn test_parse_all_preserves_order() {
+    let registry = ParserRegistry::with_defaults();
+    let sources: Vec<(String, String)> = (0..200)
+        .map(|i| (format!("src/file_{}.rs", i), format!("fn f{}() {{}}\n", i)))
+        .collect();
+    let files: Vec<(&str, &[u8])> = sources
+        .iter()
+        .map(|(path, source)| (path.as_str(), source.as_bytes()))
+        .collect();
+
+    let results = registry.parse_all(&files);
+
+    assert_eq!(results.len(), files.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.metadata.path, format!("src/file_{}.rs", i));
+        assert_eq!(result.semantic_units[0].text, format!("fn f{}() {{}}", i));
     }
+}
+
+#[test]
+fn test_parse_all_mixed_types() {
+    let registry = ParserRegistry::with_defaults();
+    let files: Vec<(&str, &[u8])> = vec![
+        ("a.md", b"# A\n"),
+        ("b.json", b"{\"b\": 1}"),
+        ("c.bin", b"\xFF\xFE"),
+    ];
+
+    let results = registry.parse_all(&files);
+    let languages: Vec<&str> = results
+        .iter()
+        .map(|r| r.metadata.language.as_str())
+        .collect();
+
+    assert_eq!(languages, vec!["markdown", "json", "unknown"]);
+    assert!(registry.parse_all(&[]).is_empty());
+}
+
+// ========================================================================
+// RustParser Tests
+// ========================================================================
+
+const RUST_SOURCE: &str = r#"use std::fmt;
 
-    #[test]
-    fn test_registry_register() {
-        let mut registry = ParserRegistry::new();
-        registry.register("rs", UnknownParser);
+/// A point
+struct Point {
+    x: i32,
+}
 
-        assert_eq!(registry.parser_count(), 1);
-        assert!(registry.registered_extensions().contains(&"rs"));
+impl Point {
+    fn new(x: i32) -> Self {
+        Self { x }
     }
 
-    #[test]
-    fn test_registry_select_fallback() {
-        let registry = ParserRegistry::new();
-        let parser = registry.select("unknown.xyz");
+    fn x(&self) -> i32 {
+        self.x
+    }
+}
 
-        // Should return fallback parser (UnknownParser)
-        let result = parser.parse("test.xyz", b"content");
-        assert_eq!(result.metadata.language, "unknown");
+trait Shape {}
+
+mod inner {
+    pub fn helper() {}
+}
+
+fn main() {}
+"#;
+
+#[test]
+fn test_rust_parser_item_kinds() {
+    let result = RustParser.parse("src/lib.rs", RUST_SOURCE.as_bytes());
+    let kinds: Vec<SemanticKind> = result.semantic_units.iter().map(|u| u.kind).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            SemanticKind::Unknown,  // use std::fmt;
+            SemanticKind::Class,    // struct Point
+            SemanticKind::Unknown,  // impl Point {
+            SemanticKind::Function, // fn new
+            SemanticKind::Function, // fn x
+            SemanticKind::Class,    // trait Shape
+            SemanticKind::Module,   // mod inner
+            SemanticKind::Function, // fn main
+        ]
+    );
+    assert_eq!(result.metadata.language, "rust");
+}
+
+#[test]
+fn test_rust_parser_offsets_map_to_source() {
+    let result = RustParser.parse("src/lib.rs", RUST_SOURCE.as_bytes());
+
+    for unit in &result.semantic_units {
+        assert_eq!(&RUST_SOURCE[unit.start_offset..unit.end_offset], unit.text);
     }
 
-    #[test]
-    fn test_registry_select_registered() {
-        let mut registry = ParserRegistry::new();
-
-        // Create a custom test parser
-        struct TestParser;
-        impl Parser for TestParser {
-            fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
-                let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
-                metadata.language = "custom".to_string(); // Override
-                metadata.set_line_count(0);
-
-                ParseResult {
-                    normalized_text: String::new(),
-                    metadata,
-                    semantic_units: vec![],
-                }
-            }
+    let point = &result.semantic_units[1];
+    assert!(point.text.starts_with("/// A point"));
+    assert!(point.text.ends_with('}'));
+
+    let new = &result.semantic_units[3];
+    assert!(new.text.starts_with("fn new"));
+}
+
+#[test]
+fn test_rust_parser_leaves_callers_spans_valid() {
+    use syn::spanned::Spanned;
+
+    // A syntax tree the caller holds on the same thread
+    let file: syn::File = syn::parse_str("\n\nfn held() {}").unwrap();
+
+    RustParser.parse("lib.rs", b"fn parsed() {}");
+
+    assert_eq!(file.items[0].span().start().line, 3);
+}
+
+#[test]
+fn test_rust_parser_symbols() {
+    let result = RustParser.parse("src/lib.rs", RUST_SOURCE.as_bytes());
+    let names: Vec<&str> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+
+    assert_eq!(
+        names,
+        vec!["Point", "Point::new", "Point::x", "Shape", "inner", "main"]
+    );
+    assert_eq!(
+        result.symbols[1],
+        ParsedSymbol {
+            name: "Point::new".to_string(),
+            kind: SemanticKind::Function,
+            start_line: 9,
+            signature: "fn new(x: i32) -> Self".to_string(),
         }
+    );
+    assert_eq!(result.symbols[0].start_line, 4);
+    assert_eq!(result.symbols[0].signature, "struct Point");
+}
+
+#[test]
+fn test_rust_parser_unicode_offsets() {
+    let source = "// héllo wörld\nfn a() { let _ = \"日本\"; }\nfn b() {}\n";
+    let result = RustParser.parse("x.rs", source.as_bytes());
+
+    let functions: Vec<_> = result
+        .semantic_units
+        .iter()
+        .filter(|u| u.kind == SemanticKind::Function)
+        .collect();
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[1].text, "fn b() {}");
+    assert_eq!(
+        &source[functions[1].start_offset..functions[1].end_offset],
+        "fn b() {}"
+    );
+    assert_eq!(result.semantic_units[0].kind, SemanticKind::Comment);
+}
+
+#[test]
+fn test_rust_parser_shebang_offsets() {
+    let source = "#!/usr/bin/env run-cargo-script\nfn main() {}\n";
+    let result = RustParser.parse("script.rs", source.as_bytes());
+
+    let main = result
+        .semantic_units
+        .iter()
+        .find(|u| u.kind == SemanticKind::Function)
+        .unwrap();
+    assert_eq!(&source[main.start_offset..main.end_offset], "fn main() {}");
+}
+
+#[test]
+fn test_rust_parser_falls_back_on_invalid_syntax() {
+    let source = b"fn broken( {\nlet x = ;\n";
+    let result = RustParser.parse("broken.rs", source);
+
+    assert_eq!(result.semantic_units.len(), 2);
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].message.contains("invalid Rust"));
+    assert!(result.errors[0].line.is_some());
+}
+
+#[test]
+fn test_rust_parser_warning_line_accounts_for_preamble() {
+    let source = b"#!/usr/bin/env run-cargo-script
+fn ok() {}
+fn broken( {
+";
+    let result = RustParser.parse("script.rs", source);
+
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].line, Some(3));
+}
 
-        registry.register("test", TestParser);
-        let parser = registry.select("file.test");
-        let result = parser.parse("file.test", b"");
+#[test]
+fn test_valid_files_have_no_parse_warnings() {
+    assert!(
+        RustParser
+            .parse(
+                "a.rs",
+                b"fn a() {}
+"
+            )
+            .errors
+            .is_empty()
+    );
+    assert!(JsonParser.parse("a.json", b"{\"a\": 1}").errors.is_empty());
+    assert!(
+        TomlParser
+            .parse("a.toml", TOML_SOURCE.as_bytes())
+            .errors
+            .is_empty()
+    );
+    assert!(
+        YamlParser
+            .parse("a.yml", YAML_SOURCE.as_bytes())
+            .errors
+            .is_empty()
+    );
+}
+
+// ========================================================================
+// PythonParser Tests
+// ========================================================================
+
+const PYTHON_SOURCE: &str = r#""""Module docstring."""
+import os
+from typing import (
+    List,
+)
+
+
+@dataclass
+@frozen
+class Point:
+    """A point."""
+    x: int = 0
+
+    def norm(self):
+        def helper():
+            return 1
+        return helper()
+
+    @staticmethod
+    async def load(path):
+        text = """
+not a block
+"""
+        return text
+
+
+def main(
+    argv,
+):
+    # comment at body indent
+    print(argv)
+# trailing comment
 
-        assert_eq!(result.metadata.language, "custom");
+
+if __name__ == "__main__":
+    main([])
+"#;
+
+#[test]
+fn test_python_parser_block_kinds() {
+    let result = PythonParser.parse("app.py", PYTHON_SOURCE.as_bytes());
+    let kinds: Vec<SemanticKind> = result.semantic_units.iter().map(|u| u.kind).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            SemanticKind::Module,   // docstring + imports
+            SemanticKind::Class,    // class Point header
+            SemanticKind::Function, // def norm
+            SemanticKind::Function, // async def load
+            SemanticKind::Function, // def main
+            SemanticKind::Unknown,  // trailing comment + __main__ guard
+        ]
+    );
+    assert_eq!(result.metadata.language, "python");
+}
+
+#[test]
+fn test_python_parser_units_map_to_source() {
+    let result = PythonParser.parse("app.py", PYTHON_SOURCE.as_bytes());
+
+    for unit in &result.semantic_units {
+        assert_eq!(
+            &PYTHON_SOURCE[unit.start_offset..unit.end_offset],
+            unit.text
+        );
     }
 
-    #[test]
-    fn test_registry_case_insensitive() {
-        let mut registry = ParserRegistry::new();
-        registry.register("rs", UnknownParser);
+    let units = &result.semantic_units;
+    assert!(units[0].text.starts_with("\"\"\"Module docstring"));
+    assert!(units[0].text.ends_with(")"));
+    assert!(
+        units[1]
+            .text
+            .starts_with("@dataclass\n@frozen\nclass Point:")
+    );
+    assert!(units[1].text.ends_with("x: int = 0"));
+    assert!(units[2].text.contains("def helper()"));
+    assert!(units[2].text.ends_with("return helper()"));
+    assert!(units[3].text.starts_with("@staticmethod"));
+    assert!(units[3].text.contains("not a block"));
+    assert!(units[3].text.ends_with("return text"));
+    assert!(units[4].text.ends_with("print(argv)"));
+    assert!(units[5].text.starts_with("# trailing comment"));
+}
+
+#[test]
+fn test_python_parser_symbols() {
+    let result = PythonParser.parse("app.py", PYTHON_SOURCE.as_bytes());
+    let names: Vec<&str> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+
+    assert_eq!(names, vec!["Point", "Point.norm", "Point.load", "main"]);
+    assert_eq!(result.symbols[0].kind, SemanticKind::Class);
+    assert_eq!(result.symbols[0].start_line, 10);
+    assert_eq!(result.symbols[2].signature, "async def load(path):");
+    assert_eq!(result.symbols[3].signature, "def main(");
+}
+
+#[test]
+fn test_python_parser_no_blocks() {
+    let source = "import sys\nprint(sys.argv)\n";
+    let result = PythonParser.parse("script.py", source.as_bytes());
+
+    assert_eq!(result.semantic_units.len(), 1);
+    assert_eq!(result.semantic_units[0].kind, SemanticKind::Module);
+    assert_eq!(result.semantic_units[0].text, source.trim());
+}
+
+// ========================================================================
+// MarkdownParser Tests
+// ========================================================================
+
+const MARKDOWN_SOURCE: &str = r#"Intro paragraph
+spanning two lines.
+
+Second intro paragraph.
+
+# Title
+
+Some text.
+
+## Usage
+
+```bash
+# not a heading
+
+cargo run
+```
+
+More usage.
+
+Setext Heading
+--------------
+
+Body.
+"#;
 
-        // Should match regardless of case
-        let parser1 = registry.select("file.rs");
+#[test]
+fn test_markdown_parser_sections() {
+    let result = MarkdownParser.parse("README.md", MARKDOWN_SOURCE.as_bytes());
+    let kinds: Vec<SemanticKind> = result.semantic_units.iter().map(|u| u.kind).collect();
 
-        // All should resolve to the registered parser (not fallback)
-        // We can't directly compare trait objects, but we can test behavior
-        let result = parser1.parse("test.rs", b"");
-        assert_eq!(result.metadata.extension, "rs");
+    assert_eq!(
+        kinds,
+        vec![
+            SemanticKind::Paragraph,
+            SemanticKind::Paragraph,
+            SemanticKind::Section, // # Title
+            SemanticKind::Section, // ## Usage
+            SemanticKind::Section, // Setext Heading
+        ]
+    );
+
+    let units = &result.semantic_units;
+    assert_eq!(units[0].text, "Intro paragraph\nspanning two lines.");
+    assert_eq!(units[2].text, "# Title\n\nSome text.");
+    assert!(units[3].text.starts_with("## Usage"));
+    assert!(units[3].text.contains("# not a heading\n\ncargo run\n```"));
+    assert!(units[3].text.ends_with("More usage."));
+    assert!(units[4].text.starts_with("Setext Heading\n---"));
+    assert_eq!(result.metadata.language, "markdown");
+}
+
+#[test]
+fn test_markdown_parser_nested_sections_do_not_overlap() {
+    let source = "# Guide\n\nIntro.\n\n## Install\n\nRun it.\n\n### Notes\n\nFine print.\n\n# Appendix\n\nExtra.\n";
+    let result = MarkdownParser.parse("guide.md", source.as_bytes());
+    let units: Vec<(SemanticKind, &str)> = result
+        .semantic_units
+        .iter()
+        .map(|u| (u.kind, u.text.as_str()))
+        .collect();
+
+    // A parent section stops where its first subsection starts
+    assert_eq!(
+        units,
+        vec![
+            (SemanticKind::Section, "# Guide\n\nIntro."),
+            (SemanticKind::Section, "## Install\n\nRun it."),
+            (SemanticKind::Section, "### Notes\n\nFine print."),
+            (SemanticKind::Section, "# Appendix\n\nExtra."),
+        ]
+    );
+}
+
+#[test]
+fn test_markdown_parser_units_cover_each_byte_at_most_once() {
+    let result = MarkdownParser.parse("README.md", MARKDOWN_SOURCE.as_bytes());
+    let mut covered = vec![false; MARKDOWN_SOURCE.len()];
+
+    for unit in &result.semantic_units {
+        for seen in &mut covered[unit.start_offset..unit.end_offset] {
+            assert!(!*seen, "unit {:?} overlaps another unit", unit.text);
+            *seen = true;
+        }
     }
+}
 
-    #[test]
-    fn test_registry_multiple_extensions() {
-        let mut registry = ParserRegistry::new();
-        registry.register("rs", UnknownParser);
-        registry.register("py", UnknownParser);
-        registry.register("js", UnknownParser);
+#[test]
+fn test_markdown_parser_heading_symbols() {
+    let result = MarkdownParser.parse("README.md", MARKDOWN_SOURCE.as_bytes());
+    let names: Vec<(&str, usize)> = result
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.start_line))
+        .collect();
 
-        assert_eq!(registry.parser_count(), 3);
+    assert_eq!(
+        names,
+        vec![("Title", 6), ("Usage", 10), ("Setext Heading", 20)]
+    );
+    assert!(
+        result
+            .symbols
+            .iter()
+            .all(|s| s.kind == SemanticKind::Section)
+    );
+}
+
+#[test]
+fn test_markdown_parser_offsets_map_to_source() {
+    let result = MarkdownParser.parse("README.md", MARKDOWN_SOURCE.as_bytes());
 
-        let extensions = registry.registered_extensions();
-        assert_eq!(extensions.len(), 3);
-        assert!(extensions.contains(&"rs"));
-        assert!(extensions.contains(&"py"));
-        assert!(extensions.contains(&"js"));
+    for unit in &result.semantic_units {
+        assert_eq!(
+            &MARKDOWN_SOURCE[unit.start_offset..unit.end_offset],
+            unit.text
+        );
     }
+}
 
-    #[test]
-    fn test_registry_overwrite_extension() {
-        struct Parser1;
-        impl Parser for Parser1 {
-            fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
-                let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
-                metadata.language = "parser1".to_string();
-                metadata.set_line_count(0);
-                ParseResult {
-                    normalized_text: String::new(),
-                    metadata,
-                    semantic_units: vec![],
-                }
-            }
-        }
+#[test]
+fn test_markdown_parser_preamble_fence_not_split() {
+    let source = "~~~\nfirst\n\nsecond\n~~~\n\nafter\n";
+    let result = MarkdownParser.parse("notes.md", source.as_bytes());
 
-        struct Parser2;
-        impl Parser for Parser2 {
-            fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
-                let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
-                metadata.language = "parser2".to_string();
-                metadata.set_line_count(0);
-                ParseResult {
-                    normalized_text: String::new(),
-                    metadata,
-                    semantic_units: vec![],
-                }
-            }
-        }
+    assert_eq!(result.semantic_units.len(), 2);
+    assert_eq!(result.semantic_units[0].text, "~~~\nfirst\n\nsecond\n~~~");
+    assert_eq!(result.semantic_units[1].text, "after");
+}
 
-        let mut registry = ParserRegistry::new();
-        registry.register("test", Parser1);
-        registry.register("test", Parser2); // Overwrite
+#[test]
+fn test_markdown_parser_hash_without_space_is_not_heading() {
+    let source = "#hashtag\n\n####### seven\n";
+    let result = MarkdownParser.parse("x.md", source.as_bytes());
+
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Paragraph)
+    );
+}
 
-        let parser = registry.select("file.test");
-        let result = parser.parse("file.test", b"");
+// ========================================================================
+// JsonParser Tests
+// ========================================================================
 
-        // Should use the second parser
-        assert_eq!(result.metadata.language, "parser2");
+#[test]
+fn test_json_parser_object_members() {
+    let source = r#"{
+  "name": "doctown",
+  "tags": ["a", "b,c"],
+  "nested": {"x": {"y": "}"}},
+  "count": 3
+}"#;
+    let result = JsonParser.parse("package.json", source.as_bytes());
+    let texts: Vec<&str> = result
+        .semantic_units
+        .iter()
+        .map(|u| u.text.as_str())
+        .collect();
+
+    assert_eq!(
+        texts,
+        vec![
+            r#""name": "doctown""#,
+            r#""tags": ["a", "b,c"]"#,
+            r#""nested": {"x": {"y": "}"}}"#,
+            r#""count": 3"#,
+        ]
+    );
+    for unit in &result.semantic_units {
+        assert_eq!(unit.kind, SemanticKind::Object);
+        assert_eq!(&source[unit.start_offset..unit.end_offset], unit.text);
     }
+}
+
+#[test]
+fn test_json_parser_array_elements() {
+    let source = r#"[1, {"a": "\"quoted\""}, null]"#;
+    let result = JsonParser.parse("data.json", source.as_bytes());
+    let texts: Vec<&str> = result
+        .semantic_units
+        .iter()
+        .map(|u| u.text.as_str())
+        .collect();
+
+    assert_eq!(texts, vec!["1", r#"{"a": "\"quoted\""}"#, "null"]);
+}
+
+#[test]
+fn test_json_parser_scalar_and_empty() {
+    let result = JsonParser.parse("x.json", b"  42\n");
+    assert_eq!(result.semantic_units.len(), 1);
+    assert_eq!(result.semantic_units[0].text, "42");
+    assert_eq!(result.semantic_units[0].start_offset, 2);
+
+    let result = JsonParser.parse("x.json", b"{}");
+    assert_eq!(result.semantic_units.len(), 1);
+    assert_eq!(result.semantic_units[0].text, "{}");
+}
+
+#[test]
+fn test_unstructured_parsers_have_no_symbols() {
+    assert!(
+        UnknownParser::new()
+            .parse("a.txt", b"fn main() {}")
+            .symbols
+            .is_empty()
+    );
+    assert!(JsonParser.parse("a.json", b"[1, 2]").symbols.is_empty());
+}
+
+#[test]
+fn test_json_parser_key_symbols() {
+    let source = r#"{
+  "name": "doctown",
+  "scripts": {
+    "build": "cargo build"
+  },
+  "a\"b": 1
+}"#;
+    let result = JsonParser.parse("package.json", source.as_bytes());
+    let symbols: Vec<(&str, usize, &str)> = result
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.start_line, s.signature.as_str()))
+        .collect();
+
+    // Nested keys aren't symbols; escaped keys are decoded
+    assert_eq!(
+        symbols,
+        vec![
+            ("name", 2, r#""name": "doctown""#),
+            ("scripts", 3, r#""scripts": {"#),
+            ("a\"b", 6, r#""a\"b": 1"#),
+        ]
+    );
+    assert!(
+        result
+            .symbols
+            .iter()
+            .all(|s| s.kind == SemanticKind::Object)
+    );
+}
+
+#[test]
+fn test_json_parser_falls_back_on_malformed() {
+    let result = JsonParser.parse("bad.json", b"{\"a\": 1,\n");
+
+    assert_eq!(result.semantic_units.len(), 1);
+    assert_eq!(result.semantic_units[0].kind, SemanticKind::Unknown);
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].message.contains("invalid JSON"));
+    assert_eq!(result.errors[0].line, Some(2));
+}
 
-    #[test]
-    fn test_registry_no_extension() {
-        let registry = ParserRegistry::new();
-        let parser = registry.select("Makefile");
+// ========================================================================
+// TomlParser / YamlParser Tests
+// ========================================================================
 
-        // Should fall back to unknown parser
-        let result = parser.parse("Makefile", b"all:");
-        assert_eq!(result.metadata.extension, "");
+const TOML_SOURCE: &str = r#"name = "root"
+list = [
+    ["not", "a header"],
+]
+text = """
+[fake]
+"""
+
+[package]
+name = "doctown"
+
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+"#;
+
+#[test]
+fn test_toml_parser_tables() {
+    let result = TomlParser.parse("Cargo.toml", TOML_SOURCE.as_bytes());
+    let texts: Vec<&str> = result
+        .semantic_units
+        .iter()
+        .map(|u| u.text.as_str())
+        .collect();
+
+    assert_eq!(texts.len(), 4);
+    assert!(texts[0].starts_with("name = \"root\""));
+    assert!(texts[0].contains("[fake]"));
+    assert_eq!(texts[1], "[package]\nname = \"doctown\"");
+    assert_eq!(texts[2], "[[bin]]\nname = \"a\"");
+    assert_eq!(texts[3], "[[bin]]\nname = \"b\"");
+
+    for unit in &result.semantic_units {
+        assert_eq!(unit.kind, SemanticKind::Config);
+        assert_eq!(&TOML_SOURCE[unit.start_offset..unit.end_offset], unit.text);
     }
+}
 
-    // ========================================================================
-    // Integration Tests
-    // ========================================================================
-
-    #[test]
-    fn test_full_pipeline_with_registry() {
-        let mut registry = ParserRegistry::new();
-        registry.register("txt", UnknownParser);
-
-        // Simulate processing multiple files
-        let files = vec![
-            ("file1.txt", b"line1\nline2" as &[u8]),
-            ("file2.txt", b"single line"),
-            ("file3.unknown", b"unknown type"),
-        ];
-
-        let mut total_units = 0;
-        for (path, bytes) in files {
-            let parser = registry.select(path);
-            let result = parser.parse(path, bytes);
-            total_units += result.semantic_units.len();
-        }
+#[test]
+fn test_toml_parser_key_symbols() {
+    let result = TomlParser.parse("Cargo.toml", TOML_SOURCE.as_bytes());
+    let symbols: Vec<(&str, usize)> = result
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.start_line))
+        .collect();
+
+    // Keys inside arrays, strings and tables aren't top-level
+    assert_eq!(
+        symbols,
+        vec![
+            ("name", 1),
+            ("list", 2),
+            ("text", 5),
+            ("package", 9),
+            ("bin", 12),
+            ("bin", 15),
+        ]
+    );
+    assert_eq!(result.symbols[3].signature, "[package]");
+    assert!(
+        result
+            .symbols
+            .iter()
+            .all(|s| s.kind == SemanticKind::Config)
+    );
+}
+
+#[test]
+fn test_toml_parser_falls_back_on_malformed() {
+    let result = TomlParser.parse("bad.toml", b"[package\nname = ");
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].message.contains("invalid TOML"));
+    assert_eq!(result.errors[0].line, Some(1));
+}
+
+const YAML_SOURCE: &str = r#"# CI config
+name: CI
+
+# Triggers
+on:
+  push:
+    branches: [main]
+
+jobs:
+  build:
+    steps:
+      - run: |
+          echo hi
+"#;
+
+#[test]
+fn test_yaml_parser_top_level_keys() {
+    let result = YamlParser.parse(".github/workflows/ci.yml", YAML_SOURCE.as_bytes());
+    let texts: Vec<&str> = result
+        .semantic_units
+        .iter()
+        .map(|u| u.text.as_str())
+        .collect();
+
+    assert_eq!(texts.len(), 3);
+    assert_eq!(texts[0], "# CI config\nname: CI");
+    assert!(texts[1].starts_with("# Triggers\non:"));
+    assert!(texts[2].starts_with("jobs:"));
+    assert!(texts[2].ends_with("echo hi"));
 
-        assert!(total_units > 0);
+    for unit in &result.semantic_units {
+        assert_eq!(unit.kind, SemanticKind::Config);
+        assert_eq!(&YAML_SOURCE[unit.start_offset..unit.end_offset], unit.text);
     }
+}
+
+#[test]
+fn test_yaml_parser_key_symbols() {
+    let result = YamlParser.parse(".github/workflows/ci.yml", YAML_SOURCE.as_bytes());
+    let symbols: Vec<(&str, usize, &str)> = result
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.start_line, s.signature.as_str()))
+        .collect();
+
+    assert_eq!(
+        symbols,
+        vec![
+            ("name", 2, "name: CI"),
+            ("on", 5, "on:"),
+            ("jobs", 9, "jobs:")
+        ]
+    );
+
+    let result = YamlParser.parse("x.yaml", b"\"a: b\": 1\n---\n- x\n");
+    let names: Vec<&str> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["a: b"]);
+}
+
+#[test]
+fn test_yaml_parser_multi_document_and_sequences() {
+    let source = "---\na: 1\n---\n- x\n- y\n";
+    let result = YamlParser.parse("multi.yaml", source.as_bytes());
+    let texts: Vec<&str> = result
+        .semantic_units
+        .iter()
+        .map(|u| u.text.as_str())
+        .collect();
+
+    assert_eq!(texts, vec!["a: 1", "---\n- x\n- y"]);
+}
 
-    #[test]
-    fn test_semantic_kind_values() {
-        // Ensure all enum variants are distinct
-        assert_ne!(SemanticKind::Unknown, SemanticKind::Blob);
-        assert_ne!(SemanticKind::Function, SemanticKind::Class);
-        assert_ne!(SemanticKind::Module, SemanticKind::Comment);
+#[test]
+fn test_yaml_parser_falls_back_on_malformed() {
+    let result = YamlParser.parse("bad.yml", b"a: [1, 2\nb: }");
+    assert!(
+        result
+            .semantic_units
+            .iter()
+            .all(|u| u.kind == SemanticKind::Unknown)
+    );
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].message.contains("invalid YAML"));
+    assert!(result.errors[0].line.is_some());
+}
+
+// ========================================================================
+// Integration Tests
+// ========================================================================
+
+#[test]
+fn test_full_pipeline_with_registry() {
+    let mut registry = ParserRegistry::new();
+    registry.register("txt", UnknownParser::new());
+
+    // Simulate processing multiple files
+    let files = vec![
+        ("file1.txt", b"line1\nline2" as &[u8]),
+        ("file2.txt", b"single line"),
+        ("file3.unknown", b"unknown type"),
+    ];
+
+    let mut total_units = 0;
+    for (path, bytes) in files {
+        let parser = registry.select(path);
+        let result = parser.parse(path, bytes);
+        total_units += result.semantic_units.len();
     }
+
+    assert!(total_units > 0);
+}
+
+#[test]
+fn test_semantic_kind_values() {
+    // Ensure all enum variants are distinct
+    assert_ne!(SemanticKind::Unknown, SemanticKind::Blob);
+    assert_ne!(SemanticKind::Function, SemanticKind::Class);
+    assert_ne!(SemanticKind::Module, SemanticKind::Comment);
 }
@@ -1,18 +1,72 @@
-use super::{FileMetadata, ParseResult, Parser, SemanticKind, SemanticUnit};
+use super::{
+    DEFAULT_BINARY_THRESHOLD, FileMetadata, LineEnding, ParseResult, Parser, SemanticKind,
+    SemanticUnit, normalize_utf8,
+};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
 
 /// Fallback parser for unknown/unsupported file types
+///
+/// Uses `DEFAULT_BINARY_THRESHOLD` to tell text from binary; see
+/// `with_binary_threshold` for a parser with another threshold.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct UnknownParser;
 
+/// `UnknownParser` with a custom binary threshold
+#[derive(Debug, Clone, Copy)]
+pub struct UnknownParserWithThreshold {
+    /// Fraction of control bytes above which non-UTF-8 content is binary
+    binary_threshold: f64,
+}
+
 impl Parser for UnknownParser {
     fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        Self::parse_with_threshold(path, bytes, DEFAULT_BINARY_THRESHOLD)
+    }
+}
+
+impl Parser for UnknownParserWithThreshold {
+    fn parse(&self, path: &str, bytes: &[u8]) -> ParseResult {
+        UnknownParser::parse_with_threshold(path, bytes, self.binary_threshold)
+    }
+}
+
+impl UnknownParser {
+    /// Create a parser with the default binary threshold
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// A parser that treats non-UTF-8 content as binary when the fraction
+    /// of control bytes (0.0-1.0) in the sampled prefix exceeds `threshold`
+    pub fn with_binary_threshold(self, threshold: f64) -> UnknownParserWithThreshold {
+        UnknownParserWithThreshold {
+            binary_threshold: threshold,
+        }
+    }
+
+    fn parse_with_threshold(path: &str, bytes: &[u8], binary_threshold: f64) -> ParseResult {
         let mut metadata = FileMetadata::from_path_and_bytes(path, bytes);
 
         // Try to extract printable text
+        let utf16 = Self::decode_utf16(bytes);
+        metadata.is_binary = !metadata.is_utf8
+            && utf16.is_none()
+            && FileMetadata::detect_binary(bytes, binary_threshold);
+
+        let is_text = !metadata.is_binary;
         let normalized_text = if metadata.is_utf8 {
-            // Valid UTF-8: use as-is
-            String::from_utf8_lossy(bytes).into_owned()
+            // Valid UTF-8: strip BOM and normalize line endings
+            normalize_utf8(bytes).unwrap_or_default()
+        } else if let Some(text) = utf16 {
+            // UTF-16 with BOM: transcode, then normalize like UTF-8
+            metadata.original_line_ending = LineEnding::detect(text.as_bytes());
+            normalize_utf8(text.as_bytes()).unwrap_or_default()
+        } else if is_text {
+            // Mostly-text legacy encoding: decode as Windows-1252 (Latin-1)
+            let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+            normalize_utf8(text.as_bytes()).unwrap_or_default()
         } else {
-            // Binary or invalid UTF-8: extract printable ASCII
+            // Binary: extract printable ASCII
             Self::extract_printable(bytes)
         };
 
@@ -21,17 +75,44 @@ impl Parser for UnknownParser {
         metadata.set_line_count(line_count);
 
         // Create semantic units (simple newline-based chunking)
-        let semantic_units = Self::chunk_by_lines(&normalized_text, metadata.is_utf8);
+        let semantic_units = Self::chunk_by_lines(&normalized_text, is_text);
+
+        ParseResult::new(normalized_text, metadata, semantic_units, Vec::new())
+    }
+
+    /// Decode UTF-16LE/BE text identified by its byte order mark
+    ///
+    /// Returns None for anything that doesn't decode cleanly to text.
+    fn decode_utf16(bytes: &[u8]) -> Option<String> {
+        let (encoding, _) = Encoding::for_bom(bytes)?;
+        if encoding != UTF_16LE && encoding != UTF_16BE {
+            return None;
+        }
 
-        ParseResult {
-            normalized_text,
-            metadata,
-            semantic_units,
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[2..]);
+        if had_errors {
+            return None;
         }
+
+        // `FF FE` is also a plausible start for arbitrary binary data, so
+        // only accept output that looks like mostly-ASCII text (as Windows
+        // tools produce for .txt/.reg files) without stray control chars
+        let mut ascii = 0;
+        let mut total = 0;
+        for c in text.chars() {
+            if c.is_control() && !matches!(c, '\t' | '\n' | '\r') {
+                return None;
+            }
+            ascii += usize::from(c.is_ascii());
+            total += 1;
+        }
+        if ascii * 2 < total {
+            return None;
+        }
+
+        Some(text.into_owned())
     }
-}
 
-impl UnknownParser {
     /// Extract printable ASCII from binary data
     fn extract_printable(bytes: &[u8]) -> String {
         bytes
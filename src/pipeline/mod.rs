@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests;
+
+use crate::chunker::{Chunk, ChunkOptions, chunk_parse_results_opts};
+use crate::clusterer::{ClusterResult, KmeansConfig, kmeans_with_config};
+use crate::embedder::{Embed, EmbedError, EmbeddingModelInfo, embed_chunks};
+use crate::parser::{FileMetadata, ParseWarning, ParserRegistry};
+use crate::sandbox::Sandbox;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("Embedding failed: {0}")]
+    Embed(#[from] EmbedError),
+}
+
+/// Runs every file of a sandbox through parsing, chunking, embedding and
+/// clustering
+///
+/// Build one with `Pipeline::builder`.
+pub struct Pipeline<'a> {
+    sandbox: &'a Sandbox,
+    embedder: &'a dyn Embed,
+    registry: ParserRegistry,
+    chunk_options: ChunkOptions<'a>,
+    batch_size: usize,
+    cluster_config: Option<KmeansConfig>,
+}
+
+/// Builder for `Pipeline`; everything but the sandbox and embedder has a
+/// default
+pub struct PipelineBuilder<'a> {
+    pipeline: Pipeline<'a>,
+}
+
+/// Everything a pipeline run produces
+#[derive(Debug)]
+pub struct PipelineResult {
+    /// Metadata of every parsed file, in path order
+    pub files: Vec<FileMetadata>,
+    /// Problems hit while parsing, paired with the path of the file
+    pub parse_warnings: Vec<(String, ParseWarning)>,
+    /// Chunks with ids assigned in path order
+    pub chunks: Vec<Chunk>,
+    /// One embedding per chunk, indexed by chunk id
+    pub embeddings: Vec<Vec<f32>>,
+    pub clusters: ClusterResult,
+    pub stats: PipelineStats,
+}
+
+/// Counts and per-step timings of a pipeline run
+#[derive(Debug, Clone, Default)]
+pub struct PipelineStats {
+    pub text_files: usize,
+    pub binary_files: usize,
+    pub semantic_units: usize,
+    pub symbols: usize,
+    pub normalized_bytes: usize,
+    pub total_tokens: usize,
+    pub parse_time: Duration,
+    pub chunk_time: Duration,
+    pub embed_time: Duration,
+    pub cluster_time: Duration,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn builder(sandbox: &'a Sandbox, embedder: &'a dyn Embed) -> PipelineBuilder<'a> {
+        PipelineBuilder {
+            pipeline: Pipeline {
+                sandbox,
+                embedder,
+                registry: ParserRegistry::with_defaults(),
+                chunk_options: ChunkOptions::default(),
+                batch_size: EmbeddingModelInfo::default().max_batch,
+                cluster_config: None,
+            },
+        }
+    }
+
+    pub fn registry(&self) -> &ParserRegistry {
+        &self.registry
+    }
+
+    pub fn chunk_options(&self) -> &ChunkOptions<'a> {
+        &self.chunk_options
+    }
+
+    /// Run every step, stopping at the first error
+    pub fn run(&self) -> Result<PipelineResult, PipelineError> {
+        let mut stats = PipelineStats::default();
+
+        // Sorted so chunk order (and chunk ids) are reproducible across runs
+        let start = Instant::now();
+        let files: Vec<(&str, &[u8])> = self
+            .sandbox
+            .list_sorted()
+            .into_iter()
+            .filter_map(|entry| {
+                let path = entry.virtual_path.as_str();
+                Some((path, self.sandbox.get(path)?))
+            })
+            .collect();
+
+        let parse_results = self.registry.parse_all(&files);
+        let mut parse_warnings = Vec::new();
+        for result in &parse_results {
+            for warning in &result.errors {
+                parse_warnings.push((result.metadata.path.clone(), warning.clone()));
+            }
+            stats.semantic_units += result.semantic_units.len();
+            stats.symbols += result.symbols.len();
+            stats.normalized_bytes += result.normalized_text.len();
+            if result.metadata.is_binary {
+                stats.binary_files += 1;
+            } else {
+                stats.text_files += 1;
+            }
+        }
+        let files = parse_results.iter().map(|r| r.metadata.clone()).collect();
+        stats.parse_time = start.elapsed();
+
+        let start = Instant::now();
+        let chunks = chunk_parse_results_opts(parse_results, &self.chunk_options);
+        stats.total_tokens = chunks.iter().map(|c| c.metadata.token_count).sum();
+        stats.chunk_time = start.elapsed();
+
+        let start = Instant::now();
+        let embeddings = embed_chunks(self.embedder, &chunks, self.batch_size)?;
+        stats.embed_time = start.elapsed();
+
+        let start = Instant::now();
+        let config = self
+            .cluster_config
+            .clone()
+            .unwrap_or_else(|| KmeansConfig::new(default_cluster_count(embeddings.len())));
+        let clusters = kmeans_with_config(&embeddings, &config);
+        stats.cluster_time = start.elapsed();
+
+        Ok(PipelineResult {
+            files,
+            parse_warnings,
+            chunks,
+            embeddings,
+            clusters,
+            stats,
+        })
+    }
+}
+
+impl<'a> PipelineBuilder<'a> {
+    /// Parse with `registry` instead of `ParserRegistry::with_defaults()`
+    pub fn registry(mut self, registry: ParserRegistry) -> Self {
+        self.pipeline.registry = registry;
+        self
+    }
+
+    pub fn chunk_options(mut self, options: ChunkOptions<'a>) -> Self {
+        self.pipeline.chunk_options = options;
+        self
+    }
+
+    /// Chunks per embedding call; defaults to the default model's
+    /// `max_batch`
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.pipeline.batch_size = batch_size;
+        self
+    }
+
+    /// Cluster with `config` instead of k-means with
+    /// `default_cluster_count` clusters
+    pub fn cluster_config(mut self, config: KmeansConfig) -> Self {
+        self.pipeline.cluster_config = Some(config);
+        self
+    }
+
+    pub fn build(self) -> Pipeline<'a> {
+        self.pipeline
+    }
+}
+
+/// Number of clusters used when none is configured: `sqrt(n)`, kept
+/// within 2..=50
+pub fn default_cluster_count(embeddings: usize) -> usize {
+    ((embeddings as f64).sqrt().ceil() as usize).clamp(2, 50)
+}
@@ -0,0 +1,151 @@
+use super::*;
+use crate::sandbox::SandboxBuilder;
+
+/// Embeds each text as (length, number of lines), failing when asked to
+struct MockEmbedder {
+    fail: bool,
+}
+
+impl Embed for MockEmbedder {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, EmbedError> {
+        if self.fail {
+            return Err(EmbedError::Model("no model".to_string()));
+        }
+        Ok(texts
+            .iter()
+            .map(|t| vec![t.len() as f32, t.lines().count() as f32])
+            .collect())
+    }
+}
+
+fn sandbox() -> Sandbox {
+    let mut builder = SandboxBuilder::new();
+    for i in 0..6 {
+        let source = format!("/// Doc {i}\nfn f{i}() {{\n    step_{i}();\n}}\n");
+        builder
+            .add_file(&format!("src/f{i}.rs"), source.as_bytes())
+            .unwrap();
+    }
+    builder
+        .add_file("README.md", b"# Title\n\nSome text.\n")
+        .unwrap();
+    builder
+        .add_file("logo.png", &[0x89, b'P', b'N', b'G', 0, 0, 1, 2])
+        .unwrap();
+    builder.build()
+}
+
+#[test]
+fn test_run_produces_consistent_outputs() {
+    let sandbox = sandbox();
+    let embedder = MockEmbedder { fail: false };
+    let pipeline = Pipeline::builder(&sandbox, &embedder).build();
+
+    let result = pipeline.run().unwrap();
+
+    assert_eq!(result.files.len(), 8);
+    assert_eq!(result.stats.text_files + result.stats.binary_files, 8);
+    assert!(result.stats.binary_files >= 1);
+    assert!(!result.chunks.is_empty());
+    assert_eq!(result.embeddings.len(), result.chunks.len());
+
+    let mut clustered: Vec<u32> = result
+        .clusters
+        .clusters
+        .iter()
+        .flat_map(|c| c.chunk_ids.clone())
+        .collect();
+    clustered.sort();
+    let ids: Vec<u32> = result.chunks.iter().map(|c| c.id).collect();
+    assert_eq!(clustered, ids);
+}
+
+#[test]
+fn test_run_visits_files_in_path_order() {
+    let sandbox = sandbox();
+    let embedder = MockEmbedder { fail: false };
+
+    let result = Pipeline::builder(&sandbox, &embedder)
+        .build()
+        .run()
+        .unwrap();
+
+    let paths: Vec<&str> = result.files.iter().map(|f| f.path.as_str()).collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+}
+
+#[test]
+fn test_run_collects_parse_warnings() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("good.json", b"{\"a\": 1}").unwrap();
+    builder.add_file("bad.json", b"{\"a\": 1,\n").unwrap();
+    let sandbox = builder.build();
+    let embedder = MockEmbedder { fail: false };
+
+    let result = Pipeline::builder(&sandbox, &embedder)
+        .build()
+        .run()
+        .unwrap();
+
+    assert_eq!(result.parse_warnings.len(), 1);
+    assert_eq!(result.parse_warnings[0].0, "bad.json");
+    assert_eq!(result.parse_warnings[0].1.line, Some(2));
+}
+
+#[test]
+fn test_cluster_config_overrides_heuristic() {
+    let sandbox = sandbox();
+    let embedder = MockEmbedder { fail: false };
+
+    let result = Pipeline::builder(&sandbox, &embedder)
+        .cluster_config(KmeansConfig::new(1))
+        .build()
+        .run()
+        .unwrap();
+
+    assert_eq!(result.clusters.clusters.len(), 1);
+}
+
+#[test]
+fn test_chunk_options_are_applied() {
+    let sandbox = sandbox();
+    let embedder = MockEmbedder { fail: false };
+    let options = ChunkOptions {
+        max_tokens: 4,
+        ..ChunkOptions::default()
+    };
+
+    let small = Pipeline::builder(&sandbox, &embedder)
+        .chunk_options(options)
+        .build()
+        .run()
+        .unwrap();
+    let default = Pipeline::builder(&sandbox, &embedder)
+        .build()
+        .run()
+        .unwrap();
+
+    assert!(small.chunks.len() > default.chunks.len());
+}
+
+#[test]
+fn test_embedding_errors_are_returned() {
+    let sandbox = sandbox();
+    let embedder = MockEmbedder { fail: true };
+
+    let result = Pipeline::builder(&sandbox, &embedder).build().run();
+
+    assert!(matches!(
+        result,
+        Err(PipelineError::Embed(EmbedError::Model(_)))
+    ));
+}
+
+#[test]
+fn test_default_cluster_count() {
+    assert_eq!(default_cluster_count(0), 2);
+    assert_eq!(default_cluster_count(100), 10);
+    assert_eq!(default_cluster_count(1_000_000), 50);
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// Metadata for a single file in the sandbox arena
 #[derive(Debug, Clone)]
 pub struct FileEntry {
@@ -8,3 +10,10 @@ pub struct FileEntry {
     /// Sanitized virtual path (e.g., "src/lib.rs")
     pub virtual_path: String,
 }
+
+impl FileEntry {
+    /// Byte range of the file's contents in `Sandbox::arena`
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.length
+    }
+}
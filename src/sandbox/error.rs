@@ -5,12 +5,36 @@ pub enum SandboxError {
     #[error("Failed to download repository: {0}")]
     DownloadFailed(String),
 
-    #[error("Failed to parse ZIP archive: {0}")]
-    ZipParseFailed(String),
+    #[error("Not authorized to download repository: {0}")]
+    Unauthorized(String),
+
+    #[error("Failed to parse archive: {0}")]
+    ArchiveParseFailed(String),
 
     #[error("Invalid path in archive: {0}")]
     InvalidPath(String),
 
+    #[error("Failed to read local file: {0}")]
+    ReadFailed(String),
+
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlob(String),
+
+    #[error("Reserved device name in path: {0}")]
+    ReservedName(String),
+
+    #[error("Path too long: {len} bytes (max: {max})")]
+    PathTooLong { len: usize, max: usize },
+
+    #[error("Path too deep: {depth} components (max: {max})")]
+    PathTooDeep { depth: usize, max: usize },
+
     #[error("File too large: {size} bytes (max: {max})")]
     FileTooLarge { size: u64, max: u64 },
+
+    #[error("Too many files: {count} (max: {max})")]
+    TooManyFiles { count: usize, max: usize },
+
+    #[error("Suspicious compression ratio: {ratio:.1}:1 (max: {max}:1)")]
+    SuspiciousCompressionRatio { ratio: f64, max: f64 },
 }
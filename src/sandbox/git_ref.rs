@@ -0,0 +1,21 @@
+/// A git reference to download an archive for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitRef {
+    /// Branch name (e.g., "main")
+    Branch(String),
+    /// Tag name (e.g., "v1.0.0")
+    Tag(String),
+    /// Full or abbreviated commit SHA
+    Commit(String),
+}
+
+impl GitRef {
+    /// Path segment under `https://github.com/{owner}/{repo}/archive/`
+    pub fn archive_path(&self) -> String {
+        match self {
+            GitRef::Branch(name) => format!("refs/heads/{}", name),
+            GitRef::Tag(name) => format!("refs/tags/{}", name),
+            GitRef::Commit(sha) => sha.clone(),
+        }
+    }
+}
@@ -1,15 +1,40 @@
 mod entry;
 mod error;
+mod git_ref;
+mod plan;
+mod progress;
+mod report;
 
 #[cfg(test)]
 mod tests;
 
 pub use entry::FileEntry;
 pub use error::SandboxError;
+pub use git_ref::GitRef;
+pub use plan::IngestPlan;
+pub use progress::{IngestProgress, ProgressCallback};
+pub use report::{IngestReport, SkipReason, UpdateReport};
 
+use crate::parser::FileMetadata;
 use crate::security::PathSanitizer;
-use std::collections::HashMap;
-use std::io::{Cursor, Read};
+use flate2::read::GzDecoder;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::ACCEPT;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Read buffer size used while streaming downloads
+const DOWNLOAD_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Minimum downloaded bytes between progress reports
+const PROGRESS_REPORT_INTERVAL: u64 = 1024 * 1024;
 
 /// Immutable sandbox with arena-backed file storage
 pub struct Sandbox {
@@ -17,14 +42,42 @@ pub struct Sandbox {
     arena: Vec<u8>,
     /// Index mapping virtual paths to arena slices
     index: HashMap<String, FileEntry>,
+    /// Lowercased path -> virtual path, when lookups ignore case
+    folded: Option<HashMap<String, String>>,
 }
 
 /// Mutable builder for constructing a sandbox
 pub struct SandboxBuilder {
     arena: Vec<u8>,
     index: HashMap<String, FileEntry>,
+    /// Content hash -> (offset, length) of bytes already in the arena
+    content_index: HashMap<u64, (usize, usize)>,
     max_file_size: u64,
     max_total_size: u64,
+    max_file_count: usize,
+    max_compression_ratio: f64,
+    /// Entries inflating to at most this many bytes skip the ratio check
+    min_ratio_check_size: u64,
+    /// Only ingest paths matching one of these globs (all if unset)
+    include_globs: Option<GlobSet>,
+    /// Never ingest paths matching one of these globs
+    exclude_globs: Option<GlobSet>,
+    /// Honor .gitignore rules during local directory ingestion
+    use_gitignore: bool,
+    /// Treat paths differing only in case as the same file
+    case_insensitive: bool,
+    /// Lowercased path -> virtual path (maintained when case-insensitive)
+    folded: HashMap<String, String>,
+    /// Directory that files added from now on are placed under
+    path_prefix: Option<String>,
+    /// Skip files with rejected sizes, ratios or paths instead of failing
+    lenient: bool,
+    /// Files added and skipped so far
+    report: IngestReport,
+    /// Optional observer for download/extraction progress
+    progress_callback: Option<ProgressCallback>,
+    /// Running progress totals reported to the callback
+    progress: IngestProgress,
 }
 
 impl SandboxBuilder {
@@ -33,8 +86,22 @@ impl SandboxBuilder {
         Self {
             arena: Vec::new(),
             index: HashMap::new(),
+            content_index: HashMap::new(),
             max_file_size: 50 * 1024 * 1024,   // 50 MB per file
             max_total_size: 500 * 1024 * 1024, // 500 MB total
+            max_file_count: 100_000,
+            max_compression_ratio: 100.0,
+            min_ratio_check_size: 1024 * 1024, // 1 MB
+            include_globs: None,
+            exclude_globs: None,
+            use_gitignore: false,
+            case_insensitive: false,
+            folded: HashMap::new(),
+            path_prefix: None,
+            lenient: false,
+            report: IngestReport::default(),
+            progress_callback: None,
+            progress: IngestProgress::default(),
         }
     }
 
@@ -50,10 +117,152 @@ impl SandboxBuilder {
         self
     }
 
+    /// Set maximum number of files
+    pub fn max_file_count(mut self, count: usize) -> Self {
+        self.max_file_count = count;
+        self
+    }
+
+    /// Set maximum decompressed-to-compressed size ratio for ZIP entries
+    ///
+    /// Extraction stops as soon as an entry inflates past this ratio, so a
+    /// small zip bomb can't force enormous decompression work.
+    pub fn max_compression_ratio(mut self, ratio: f64) -> Self {
+        self.max_compression_ratio = ratio;
+        self
+    }
+
+    /// Only enforce the compression ratio on ZIP entries that inflate past
+    /// `size` bytes
+    ///
+    /// Small files can legitimately compress far better than any sane
+    /// ratio limit (e.g., a blank-padded fixture), and inflating them
+    /// costs little.
+    pub fn min_ratio_check_size(mut self, size: u64) -> Self {
+        self.min_ratio_check_size = size;
+        self
+    }
+
+    /// Only ingest files whose path matches at least one of these globs
+    ///
+    /// Patterns are matched against the virtual path (e.g., "src/**/*.rs"
+    /// or "*.toml"). An empty list removes the filter.
+    pub fn with_include_globs(mut self, patterns: Vec<String>) -> Result<Self, SandboxError> {
+        self.include_globs = build_glob_set(&patterns)?;
+        Ok(self)
+    }
+
+    /// Skip files whose path matches any of these globs
+    ///
+    /// Exclusions take precedence over inclusions. An empty list removes
+    /// the filter.
+    pub fn with_exclude_globs(mut self, patterns: Vec<String>) -> Result<Self, SandboxError> {
+        self.exclude_globs = build_glob_set(&patterns)?;
+        Ok(self)
+    }
+
+    /// Honor `.gitignore` files during `ingest_local_dir`
+    ///
+    /// Rules apply per directory like git, including nested ignore files
+    /// and `.git/info/exclude`. The `.git` directory itself is skipped.
+    pub fn with_gitignore(mut self, enabled: bool) -> Self {
+        self.use_gitignore = enabled;
+        self
+    }
+
+    /// Resolve paths regardless of case, like a case-insensitive filesystem
+    ///
+    /// `Sandbox::get` and `get_entry` then find `Readme.md` when asked for
+    /// `README.md`; entries keep the casing they were added with. Paths
+    /// differing only in case are one file: the last one added wins,
+    /// replacing the earlier file and its casing.
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// Place files added from now on under `prefix` (e.g., "serde/src/lib.rs")
+    ///
+    /// Lets one sandbox hold several repositories without their paths
+    /// colliding; `Sandbox::walk_prefix(prefix)` then yields one of them.
+    /// Include/exclude globs and `.gitignore` rules still match the path
+    /// within the repository. An empty prefix turns prefixing off.
+    pub fn with_path_prefix(mut self, prefix: &str) -> Result<Self, SandboxError> {
+        let prefix = prefix.trim_matches('/');
+        self.path_prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(PathSanitizer::sanitize(prefix)?)
+        };
+        Ok(self)
+    }
+
+    /// Skip ingested files that are too large, compress suspiciously well
+    /// or have invalid paths instead of failing the whole ingestion
+    ///
+    /// Skipped files are listed in `report()`. Limits on the total size
+    /// and file count still fail, since they guard against hostile
+    /// archives; an entry over the ratio is never inflated further, so
+    /// skipping it is safe.
+    pub fn lenient(mut self, enabled: bool) -> Self {
+        self.lenient = enabled;
+        self
+    }
+
+    /// Files added and skipped by ingestion so far
+    pub fn report(&self) -> &IngestReport {
+        &self.report
+    }
+
+    /// Report progress while downloading and extracting
+    ///
+    /// The callback fires periodically during downloads and once for every
+    /// file added to the sandbox.
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(IngestProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    fn report_progress(&self) {
+        if let Some(callback) = &self.progress_callback {
+            callback(self.progress);
+        }
+    }
+
+    /// Check a path against the include/exclude globs
+    ///
+    /// Ingestion skips unselected files before reading them, so they never
+    /// count toward the size limits.
+    fn is_selected(&self, path: &str) -> bool {
+        if let Some(exclude) = &self.exclude_globs
+            && exclude.is_match(path)
+        {
+            return false;
+        }
+
+        self.include_globs
+            .as_ref()
+            .is_none_or(|include| include.is_match(path))
+    }
+
+    /// Sanitize a path and place it under the path prefix, if any
+    fn virtual_path(&self, raw_path: &str) -> Result<String, SandboxError> {
+        let path = PathSanitizer::sanitize(raw_path)?;
+        Ok(match &self.path_prefix {
+            Some(prefix) => format!("{}/{}", prefix, path),
+            None => path,
+        })
+    }
+
     /// Add a file to the sandbox arena
+    ///
+    /// The file lands under the path prefix set with `with_path_prefix`.
     pub fn add_file(&mut self, raw_path: &str, data: &[u8]) -> Result<(), SandboxError> {
         // Sanitize the path
-        let virtual_path = PathSanitizer::sanitize(raw_path)?;
+        let virtual_path = self.virtual_path(raw_path)?;
 
         // Check file size limit
         if data.len() as u64 > self.max_file_size {
@@ -63,21 +272,62 @@ impl SandboxBuilder {
             });
         }
 
-        // Check total size limit
-        let new_total = self.arena.len() as u64 + data.len() as u64;
-        if new_total > self.max_total_size {
-            return Err(SandboxError::FileTooLarge {
-                size: new_total,
-                max: self.max_total_size,
+        // The file this one replaces under a different casing, if any
+        let replaced = if self.case_insensitive {
+            self.folded.get(&virtual_path.to_lowercase()).cloned()
+        } else {
+            None
+        };
+
+        // Check file count limit (overwriting an existing path is free)
+        if !self.index.contains_key(&virtual_path)
+            && replaced.is_none()
+            && self.index.len() >= self.max_file_count
+        {
+            return Err(SandboxError::TooManyFiles {
+                count: self.index.len() + 1,
+                max: self.max_file_count,
             });
         }
 
-        // Add to arena
-        let offset = self.arena.len();
-        self.arena.extend_from_slice(data);
-        let length = data.len();
+        // Identical content already in the arena is shared, not copied
+        let hash = xxh3_64(data);
+        let existing = self
+            .content_index
+            .get(&hash)
+            .copied()
+            .filter(|&(offset, length)| &self.arena[offset..offset + length] == data);
+
+        let (offset, length) = match existing {
+            Some(range) => range,
+            None => {
+                // Check total size limit
+                let new_total = self.arena.len() as u64 + data.len() as u64;
+                if new_total > self.max_total_size {
+                    return Err(SandboxError::FileTooLarge {
+                        size: new_total,
+                        max: self.max_total_size,
+                    });
+                }
+
+                // Add to arena
+                let offset = self.arena.len();
+                self.arena.extend_from_slice(data);
+                self.content_index
+                    .entry(hash)
+                    .or_insert((offset, data.len()));
+                (offset, data.len())
+            }
+        };
 
         // Add to index
+        if let Some(previous) = replaced {
+            self.index.remove(&previous);
+        }
+        if self.case_insensitive {
+            self.folded
+                .insert(virtual_path.to_lowercase(), virtual_path.clone());
+        }
         self.index.insert(
             virtual_path.clone(),
             FileEntry {
@@ -87,48 +337,313 @@ impl SandboxBuilder {
             },
         );
 
+        self.report.added += 1;
+        self.progress.files_extracted += 1;
+        self.report_progress();
+
+        Ok(())
+    }
+
+    /// Add an ingested file, skipping it in lenient mode if its size or
+    /// path is rejected
+    fn ingest_file(&mut self, path: &str, data: &[u8]) -> Result<(), SandboxError> {
+        if data.len() as u64 > self.max_file_size {
+            let error = SandboxError::FileTooLarge {
+                size: data.len() as u64,
+                max: self.max_file_size,
+            };
+            return self.skip_or_fail(path, error);
+        }
+
+        match self.add_file(path, data) {
+            Err(
+                e @ (SandboxError::InvalidPath(_)
+                | SandboxError::ReservedName(_)
+                | SandboxError::PathTooLong { .. }
+                | SandboxError::PathTooDeep { .. }),
+            ) => self.skip_or_fail(path, e),
+            result => result,
+        }
+    }
+
+    /// Record a file rejected by a per-file check as skipped in lenient
+    /// mode, otherwise fail with `error`
+    fn skip_or_fail(&mut self, path: &str, error: SandboxError) -> Result<(), SandboxError> {
+        if !self.lenient {
+            return Err(error);
+        }
+
+        let reason = match error {
+            SandboxError::FileTooLarge { size, max } => SkipReason::TooLarge { size, max },
+            SandboxError::SuspiciousCompressionRatio { ratio, max } => {
+                SkipReason::SuspiciousCompressionRatio { ratio, max }
+            }
+            e => SkipReason::InvalidPath(e.to_string()),
+        };
+        self.report.skipped.push((path.to_string(), reason));
         Ok(())
     }
 
+    /// Record a file left out regardless of lenient mode
+    fn skip(&mut self, path: &str, reason: SkipReason) {
+        self.report.skipped.push((path.to_string(), reason));
+    }
+
+    /// Remove a file from the sandbox, returning whether it existed
+    ///
+    /// Its bytes stay in the arena until `build()` compacts it.
+    pub fn remove_file(&mut self, virtual_path: &str) -> bool {
+        if self.case_insensitive
+            && let Some(path) = self.folded.remove(&virtual_path.to_lowercase())
+        {
+            return self.index.remove(&path).is_some();
+        }
+        self.index.remove(virtual_path).is_some()
+    }
+
     /// Ingest a GitHub repository as a ZIP archive
     pub fn ingest_github_repo(
-        mut self,
+        self,
         owner: &str,
         repo: &str,
         branch: &str,
+    ) -> Result<Self, SandboxError> {
+        self.ingest_github_ref(owner, repo, &GitRef::Branch(branch.to_string()))
+    }
+
+    /// Ingest a GitHub repository at a branch, tag or exact commit
+    pub fn ingest_github_ref(
+        mut self,
+        owner: &str,
+        repo: &str,
+        git_ref: &GitRef,
     ) -> Result<Self, SandboxError> {
         // Construct GitHub ZIP URL
         let url = format!(
-            "https://github.com/{}/{}/archive/refs/heads/{}.zip",
+            "https://github.com/{}/{}/archive/{}.zip",
+            owner,
+            repo,
+            git_ref.archive_path()
+        );
+
+        // Download ZIP
+        let file = self.download(Client::new().get(&url))?;
+
+        // Parse ZIP from the downloaded file
+        self.ingest_archive(file)?;
+
+        Ok(self)
+    }
+
+    /// Ingest a private GitHub repository as a ZIP archive
+    ///
+    /// Uses the GitHub API zipball endpoint with `token` as a bearer token.
+    /// A 401/403 response is reported as `SandboxError::Unauthorized`.
+    pub fn ingest_github_repo_authed(
+        mut self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        token: &str,
+    ) -> Result<Self, SandboxError> {
+        // The API redirects to a pre-signed codeload URL
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/zipball/{}",
             owner, repo, branch
         );
 
+        // The GitHub API rejects requests without a User-Agent
+        let client = Client::builder()
+            .user_agent(concat!("doctown/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| SandboxError::DownloadFailed(format!("HTTP client failed: {}", e)))?;
+
         // Download ZIP
-        let response = reqwest::blocking::get(&url)
-            .map_err(|e| SandboxError::DownloadFailed(format!("HTTP request failed: {}", e)))?;
+        let file = self.download(
+            client
+                .get(&url)
+                .bearer_auth(token)
+                .header(ACCEPT, "application/vnd.github+json"),
+        )?;
 
-        if !response.status().is_success() {
-            return Err(SandboxError::DownloadFailed(format!(
-                "HTTP {}: {}",
-                response.status(),
-                response.status().canonical_reason().unwrap_or("Unknown")
-            )));
+        // Parse ZIP from the downloaded file
+        self.ingest_archive(file)?;
+
+        Ok(self)
+    }
+
+    /// Ingest a GitHub repository as a gzipped tarball
+    ///
+    /// Produces the same virtual paths as `ingest_github_repo`.
+    pub fn ingest_github_tarball(
+        mut self,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Result<Self, SandboxError> {
+        // Construct GitHub tarball URL
+        let url = format!(
+            "https://github.com/{}/{}/archive/refs/heads/{}.tar.gz",
+            owner, repo, git_ref
+        );
+
+        // Download tarball
+        let file = self.download(Client::new().get(&url))?;
+
+        // Decompress and parse from the downloaded file
+        self.ingest_tarball(GzDecoder::new(file))?;
+
+        Ok(self)
+    }
+
+    /// Ingest a ZIP archive from disk
+    ///
+    /// The archive is expected to use the GitHub layout, with every entry
+    /// under a single top-level directory that gets stripped.
+    pub fn ingest_zip_file(mut self, path: &Path) -> Result<Self, SandboxError> {
+        let file = fs::File::open(path)
+            .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", path.display(), e)))?;
+
+        self.ingest_archive(file)?;
+
+        Ok(self)
+    }
+
+    /// Ingest a newer version of a GitHub repository previously ingested
+    /// into `prev`
+    ///
+    /// The archive is downloaded as usual, but files whose size and CRC-32
+    /// match their entry in `prev` are copied from it instead of being
+    /// decompressed. Every path of `prev` not in the new version is
+    /// reported as removed, so `prev` should hold only this repository.
+    pub fn update_from_github(
+        mut self,
+        prev: &Sandbox,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<(Self, UpdateReport), SandboxError> {
+        let url = format!(
+            "https://github.com/{}/{}/archive/{}.zip",
+            owner,
+            repo,
+            GitRef::Branch(branch.to_string()).archive_path()
+        );
+
+        let file = self.download(Client::new().get(&url))?;
+        let report = self.ingest_archive_from(file, Some(prev))?;
+
+        Ok((self, report))
+    }
+
+    /// Ingest a newer version of a ZIP archive previously ingested into
+    /// `prev` (see `update_from_github`)
+    pub fn update_from_zip_file(
+        mut self,
+        prev: &Sandbox,
+        path: &Path,
+    ) -> Result<(Self, UpdateReport), SandboxError> {
+        let file = fs::File::open(path)
+            .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", path.display(), e)))?;
+
+        let report = self.ingest_archive_from(file, Some(prev))?;
+
+        Ok((self, report))
+    }
+
+    /// List the files `ingest_github_repo` would add, without adding them
+    ///
+    /// GitHub builds archives on the fly and doesn't serve byte ranges, so
+    /// the archive is still downloaded (to a temp file, with progress
+    /// reports), but nothing is decompressed or stored. Include/exclude
+    /// globs apply, so the plan can be used to tune them.
+    pub fn plan_github_repo(
+        &mut self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<IngestPlan, SandboxError> {
+        let url = format!(
+            "https://github.com/{}/{}/archive/{}.zip",
+            owner,
+            repo,
+            GitRef::Branch(branch.to_string()).archive_path()
+        );
+
+        let file = self.download(Client::new().get(&url))?;
+
+        self.plan_archive(file)
+    }
+
+    /// List the files `ingest_zip_file` would add, without adding them
+    pub fn plan_zip_file(&self, path: &Path) -> Result<IngestPlan, SandboxError> {
+        let file = fs::File::open(path)
+            .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", path.display(), e)))?;
+
+        self.plan_archive(file)
+    }
+
+    /// Read a ZIP archive's central directory into a plan
+    ///
+    /// Paths are stripped, filtered and sanitized exactly as
+    /// `ingest_archive` does; sizes are the ones the entries declare. Paths
+    /// that fail sanitization are listed as rejected rather than failing
+    /// the plan, so one bad entry doesn't hide the rest of the archive.
+    fn plan_archive<R: Read + Seek>(&self, reader: R) -> Result<IngestPlan, SandboxError> {
+        let mut archive = zip::ZipArchive::new(reader)
+            .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+
+        let mut entries = Vec::new();
+        let mut rejected = Vec::new();
+        for i in 0..archive.len() {
+            let file = archive
+                .by_index_raw(i)
+                .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+            if file.is_dir() {
+                continue;
+            }
+
+            let stripped_path = strip_archive_root(file.name());
+            if stripped_path.is_empty() || !self.is_selected(stripped_path) {
+                continue;
+            }
+
+            match self.virtual_path(stripped_path) {
+                Ok(path) => entries.push((path, file.size())),
+                Err(e) => rejected.push((
+                    stripped_path.to_string(),
+                    SkipReason::InvalidPath(e.to_string()),
+                )),
+            }
         }
+        entries.sort();
+        rejected.sort_by(|a, b| a.0.cmp(&b.0));
 
-        let bytes = response.bytes().map_err(|e| {
-            SandboxError::DownloadFailed(format!("Failed to read response body: {}", e))
-        })?;
+        Ok(IngestPlan { entries, rejected })
+    }
+
+    /// Extract all files from a ZIP archive into the arena
+    fn ingest_archive<R: Read + Seek>(&mut self, reader: R) -> Result<(), SandboxError> {
+        self.ingest_archive_from(reader, None).map(|_| ())
+    }
+
+    /// Extract all files from a ZIP archive into the arena, reusing the
+    /// bytes of files unchanged since `prev`
+    fn ingest_archive_from<R: Read + Seek>(
+        &mut self,
+        reader: R,
+        prev: Option<&Sandbox>,
+    ) -> Result<UpdateReport, SandboxError> {
+        let mut archive = zip::ZipArchive::new(reader)
+            .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
 
-        // Parse ZIP in memory
-        let cursor = Cursor::new(bytes);
-        let mut archive = zip::ZipArchive::new(cursor)
-            .map_err(|e| SandboxError::ZipParseFailed(e.to_string()))?;
+        let mut report = UpdateReport::default();
+        let mut seen = HashSet::new();
 
-        // Extract all files into the arena
         for i in 0..archive.len() {
             let mut file = archive
                 .by_index(i)
-                .map_err(|e| SandboxError::ZipParseFailed(e.to_string()))?;
+                .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
 
             // Skip directories
             if file.is_dir() {
@@ -138,39 +653,412 @@ impl SandboxBuilder {
             // Get the file path from the ZIP
             let raw_path = file.name().to_string();
 
-            // GitHub ZIPs have a top-level directory like "repo-main/"
-            // Strip it to get clean paths
-            let stripped_path = raw_path
-                .split_once('/')
-                .map(|(_, rest)| rest)
-                .unwrap_or(&raw_path);
-
-            // Skip if empty after stripping
+            // Skip if empty after stripping or filtered out
+            let stripped_path = strip_archive_root(&raw_path);
             if stripped_path.is_empty() {
+                self.skip(&raw_path, SkipReason::EmptyPath);
+                continue;
+            }
+            if !self.is_selected(stripped_path) {
+                self.skip(stripped_path, SkipReason::Filtered);
                 continue;
             }
 
+            // Invalid paths fail or get skipped when the file is added
+            let virtual_path = self.virtual_path(stripped_path).ok();
+            let previous = prev
+                .zip(virtual_path.as_deref())
+                .and_then(|(prev, path)| prev.get(path));
+
+            // The header's checksum tells whether the file changed without
+            // inflating it
+            if let Some(bytes) = previous
+                && bytes.len() as u64 == file.size()
+                && crc32fast::hash(bytes) == file.crc32()
+            {
+                // A tighter limit in lenient mode may still skip it
+                let added = self.report.added;
+                self.ingest_file(stripped_path, bytes)?;
+                if let Some(path) = virtual_path
+                    && self.report.added > added
+                {
+                    report.unchanged += 1;
+                    seen.insert(path);
+                }
+                continue;
+            }
+
+            // Never inflate more than the size limit or the ratio allows,
+            // whatever the entry header claims
+            let compressed_size = file.compressed_size();
+            let ratio_limit = ((compressed_size as f64 * self.max_compression_ratio) as u64)
+                .max(self.min_ratio_check_size);
+            let read_limit = self.max_file_size.min(ratio_limit);
+
             // Read file contents
             let mut contents = Vec::new();
-            file.read_to_end(&mut contents)
-                .map_err(|e| SandboxError::ZipParseFailed(e.to_string()))?;
+            file.by_ref()
+                .take(read_limit + 1)
+                .read_to_end(&mut contents)
+                .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+
+            let read = contents.len() as u64;
+            if read > ratio_limit {
+                let error = SandboxError::SuspiciousCompressionRatio {
+                    ratio: read as f64 / compressed_size.max(1) as f64,
+                    max: self.max_compression_ratio,
+                };
+                self.skip_or_fail(stripped_path, error)?;
+                continue;
+            }
+            if read > self.max_file_size {
+                let error = SandboxError::FileTooLarge {
+                    size: file.size().max(read),
+                    max: self.max_file_size,
+                };
+                self.skip_or_fail(stripped_path, error)?;
+                continue;
+            }
+
+            // Add to sandbox (this handles sanitization)
+            let added = self.report.added;
+            self.ingest_file(stripped_path, &contents)?;
+
+            if let Some(path) = virtual_path
+                && self.report.added > added
+            {
+                if previous.is_some() {
+                    report.modified.push(path.clone());
+                } else {
+                    report.added.push(path.clone());
+                }
+                seen.insert(path);
+            }
+        }
+
+        if let Some(prev) = prev {
+            report.removed = prev
+                .list_sorted()
+                .into_iter()
+                .map(|entry| &entry.virtual_path)
+                .filter(|path| !seen.contains(*path))
+                .cloned()
+                .collect();
+        }
+
+        Ok(report)
+    }
+
+    /// Extract all regular files from an (already decompressed) tar stream
+    fn ingest_tarball<R: Read>(&mut self, reader: R) -> Result<(), SandboxError> {
+        let mut archive = tar::Archive::new(reader);
+        let entries = archive
+            .entries()
+            .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+
+        for entry in entries {
+            let mut entry = entry.map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+
+            // Skip directories, links and pax metadata entries
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            // Get the file path from the tarball
+            let path = entry
+                .path()
+                .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+            let Some(raw_path) = path.to_str().map(str::to_string) else {
+                let lossy = path.to_string_lossy().into_owned();
+                let error = SandboxError::InvalidPath("Invalid UTF-8 in archive path".to_string());
+                self.skip_or_fail(&lossy, error)?;
+                continue;
+            };
+
+            // Skip if empty after stripping or filtered out
+            let stripped_path = strip_archive_root(&raw_path);
+            if stripped_path.is_empty() {
+                self.skip(&raw_path, SkipReason::EmptyPath);
+                continue;
+            }
+            if !self.is_selected(stripped_path) {
+                self.skip(stripped_path, SkipReason::Filtered);
+                continue;
+            }
+
+            // Check the declared size before inflating anything
+            let size = entry.header().size().unwrap_or(0);
+            if size > self.max_file_size {
+                let error = SandboxError::FileTooLarge {
+                    size,
+                    max: self.max_file_size,
+                };
+                self.skip_or_fail(stripped_path, error)?;
+                continue;
+            }
+
+            // Never read more than the size limit, whatever the header says
+            let mut contents = Vec::new();
+            entry
+                .by_ref()
+                .take(self.max_file_size + 1)
+                .read_to_end(&mut contents)
+                .map_err(|e| SandboxError::ArchiveParseFailed(e.to_string()))?;
+            if contents.len() as u64 > self.max_file_size {
+                let error = SandboxError::FileTooLarge {
+                    size: size.max(contents.len() as u64),
+                    max: self.max_file_size,
+                };
+                self.skip_or_fail(stripped_path, error)?;
+                continue;
+            }
+
+            // Add to sandbox (this handles sanitization)
+            self.ingest_file(stripped_path, &contents)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ingest a local directory tree, using paths relative to `root`
+    ///
+    /// Symlinks to files inside the root are followed; symlinked directories
+    /// are skipped (their contents are reachable through the real path), as
+    /// are links that resolve outside the root. With `with_gitignore(true)`,
+    /// `.gitignore` and `.git/info/exclude` rules are honored.
+    pub fn ingest_local_dir(mut self, root: &Path) -> Result<Self, SandboxError> {
+        let root = root
+            .canonicalize()
+            .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", root.display(), e)))?;
+
+        let mut walker = WalkBuilder::new(&root);
+        walker
+            .standard_filters(false)
+            .follow_links(false)
+            // Sort for a deterministic arena layout
+            .sort_by_file_name(|a, b| a.cmp(b));
+
+        if self.use_gitignore {
+            // Per-directory rules like git, even outside a git checkout
+            walker
+                .git_ignore(true)
+                .git_exclude(true)
+                .parents(true)
+                .require_git(false)
+                .filter_entry(|entry| entry.file_name() != ".git");
+        }
+
+        for entry in walker.build() {
+            let entry = entry.map_err(|e| SandboxError::ReadFailed(e.to_string()))?;
+            let path = entry.path();
+
+            let metadata = fs::symlink_metadata(path)
+                .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", path.display(), e)))?;
+
+            let metadata = if metadata.file_type().is_symlink() {
+                // Skip dangling links and links escaping the root
+                match path.canonicalize() {
+                    Ok(target) if target.starts_with(&root) => match fs::metadata(&target) {
+                        Ok(target_meta) if target_meta.is_file() => target_meta,
+                        _ => continue,
+                    },
+                    _ => continue,
+                }
+            } else {
+                metadata
+            };
+
+            // Directories are descended into by the walker
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let Some(relative) = path.strip_prefix(&root).ok().and_then(|p| p.to_str()) else {
+                let lossy = path.to_string_lossy().into_owned();
+                let error =
+                    SandboxError::InvalidPath(format!("Invalid local path: {}", path.display()));
+                self.skip_or_fail(&lossy, error)?;
+                continue;
+            };
+
+            if !self.is_selected(relative) {
+                self.skip(relative, SkipReason::Filtered);
+                continue;
+            }
+
+            // Check the size before reading anything into memory
+            if metadata.len() > self.max_file_size {
+                let error = SandboxError::FileTooLarge {
+                    size: metadata.len(),
+                    max: self.max_file_size,
+                };
+                self.skip_or_fail(relative, error)?;
+                continue;
+            }
+
+            let contents = fs::read(path)
+                .map_err(|e| SandboxError::ReadFailed(format!("{}: {}", path.display(), e)))?;
 
             // Add to sandbox (this handles sanitization)
-            self.add_file(stripped_path, &contents)?;
+            self.ingest_file(relative, &contents)?;
         }
 
         Ok(self)
     }
 
+    /// Send a download request and stream the body into a temporary file
+    ///
+    /// Keeps the compressed archive out of memory so only the arena holds
+    /// file contents. The file is deleted when dropped.
+    fn download(&mut self, request: RequestBuilder) -> Result<NamedTempFile, SandboxError> {
+        let mut response = request
+            .send()
+            .map_err(|e| SandboxError::DownloadFailed(format!("HTTP request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(status_error(response.status()));
+        }
+
+        let mut file = NamedTempFile::new().map_err(|e| {
+            SandboxError::DownloadFailed(format!("Failed to create temp file: {}", e))
+        })?;
+
+        self.progress.bytes_downloaded = 0;
+        self.progress.total_bytes = response.content_length();
+        self.report_progress();
+
+        let mut buf = vec![0u8; DOWNLOAD_BUFFER_SIZE];
+        let mut last_report = 0;
+        loop {
+            let n = response.read(&mut buf).map_err(|e| {
+                SandboxError::DownloadFailed(format!("Failed to read response body: {}", e))
+            })?;
+            if n == 0 {
+                break;
+            }
+
+            file.write_all(&buf[..n]).map_err(|e| {
+                SandboxError::DownloadFailed(format!("Failed to write temp file: {}", e))
+            })?;
+
+            self.progress.bytes_downloaded += n as u64;
+            if self.progress.bytes_downloaded - last_report >= PROGRESS_REPORT_INTERVAL {
+                last_report = self.progress.bytes_downloaded;
+                self.report_progress();
+            }
+        }
+
+        // Always report the final byte count
+        if last_report != self.progress.bytes_downloaded {
+            self.report_progress();
+        }
+
+        file.rewind().map_err(|e| {
+            SandboxError::DownloadFailed(format!("Failed to rewind temp file: {}", e))
+        })?;
+
+        Ok(file)
+    }
+
     /// Build the immutable sandbox
+    ///
+    /// Compacts the arena so bytes left behind by overwritten or removed
+    /// paths are dropped and only data referenced by live entries remains.
+    /// Entries sharing deduplicated content keep sharing it.
     pub fn build(self) -> Sandbox {
+        let SandboxBuilder {
+            arena,
+            mut index,
+            case_insensitive,
+            folded,
+            ..
+        } = self;
+        let folded = case_insensitive.then_some(folded);
+
+        let mut live_ranges: Vec<(usize, usize)> = index
+            .values()
+            .map(|entry| (entry.offset, entry.length))
+            .collect();
+        live_ranges.sort_unstable();
+        live_ranges.dedup();
+
+        let live_size: usize = live_ranges.iter().map(|&(_, length)| length).sum();
+        if live_size == arena.len() {
+            // Nothing stale, keep the arena as-is
+            return Sandbox {
+                arena,
+                index,
+                folded,
+            };
+        }
+
+        // Copy live ranges in arena order so the layout stays stable
+        let mut compacted = Vec::with_capacity(live_size);
+        let mut relocated = HashMap::with_capacity(live_ranges.len());
+        for (offset, length) in live_ranges {
+            relocated.insert((offset, length), compacted.len());
+            compacted.extend_from_slice(&arena[offset..offset + length]);
+        }
+
+        for entry in index.values_mut() {
+            entry.offset = relocated[&(entry.offset, entry.length)];
+        }
+
         Sandbox {
-            arena: self.arena,
-            index: self.index,
+            arena: compacted,
+            index,
+            folded,
         }
     }
 }
 
+/// Map a non-success HTTP status to a sandbox error
+///
+/// 401/403 become `Unauthorized` so auth failures can be told apart from
+/// network failures.
+fn status_error(status: StatusCode) -> SandboxError {
+    let message = format!(
+        "HTTP {}: {}",
+        status,
+        status.canonical_reason().unwrap_or("Unknown")
+    );
+
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => SandboxError::Unauthorized(message),
+        _ => SandboxError::DownloadFailed(message),
+    }
+}
+
+/// Compile glob patterns into a set, or `None` for an empty list
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, SandboxError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| SandboxError::InvalidGlob(format!("{}: {}", pattern, e)))?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| SandboxError::InvalidGlob(e.to_string()))
+}
+
+/// Strip the top-level directory from an archive entry path
+///
+/// GitHub archives have a top-level directory like "repo-main/";
+/// stripping it gives clean paths.
+fn strip_archive_root(raw_path: &str) -> &str {
+    raw_path
+        .split_once('/')
+        .map(|(_, rest)| rest)
+        .unwrap_or(raw_path)
+}
+
 impl Default for SandboxBuilder {
     fn default() -> Self {
         Self::new()
@@ -180,39 +1068,84 @@ impl Default for SandboxBuilder {
 impl Sandbox {
     /// Get a file's contents as a byte slice (zero-copy)
     pub fn get(&self, virtual_path: &str) -> Option<&[u8]> {
-        self.index
-            .get(virtual_path)
-            .map(|entry| &self.arena[entry.offset..entry.offset + entry.length])
+        self.get_entry(virtual_path)
+            .map(|entry| &self.arena[entry.range()])
+    }
+
+    /// Get a file's contents as UTF-8 text (zero-copy)
+    ///
+    /// Returns `None` if the file doesn't exist, or the UTF-8 error if its
+    /// contents aren't valid text.
+    pub fn get_str(&self, virtual_path: &str) -> Option<Result<&str, std::str::Utf8Error>> {
+        self.get(virtual_path).map(std::str::from_utf8)
     }
 
     /// List all files in the sandbox
+    ///
+    /// Order is unspecified and may differ between runs; use `list_sorted`
+    /// when output must be reproducible.
     pub fn list(&self) -> impl Iterator<Item = &FileEntry> {
         self.index.values()
     }
 
+    /// List all files sorted by virtual path
+    pub fn list_sorted(&self) -> Vec<&FileEntry> {
+        let mut entries: Vec<&FileEntry> = self.index.values().collect();
+        entries.sort_by(|a, b| a.virtual_path.cmp(&b.virtual_path));
+        entries
+    }
+
     /// Walk all files under a given directory prefix
     pub fn walk_prefix(&self, dir_prefix: &str) -> Vec<&FileEntry> {
+        self.walk_prefix_iter(dir_prefix).collect()
+    }
+
+    /// Lazily walk all files under a given directory prefix
+    ///
+    /// Same matching as `walk_prefix`, without collecting into a `Vec`.
+    pub fn walk_prefix_iter<'a>(
+        &'a self,
+        dir_prefix: &str,
+    ) -> impl Iterator<Item = &'a FileEntry> + use<'a> {
         let normalized_prefix = if dir_prefix.is_empty() {
             String::new()
         } else {
             format!("{}/", dir_prefix.trim_end_matches('/'))
         };
 
-        self.index
-            .values()
-            .filter(|entry| {
-                if normalized_prefix.is_empty() {
-                    true // Match all if prefix is empty
-                } else {
-                    entry.virtual_path.starts_with(&normalized_prefix)
-                }
-            })
-            .collect()
+        self.index.values().filter(move |entry| {
+            if normalized_prefix.is_empty() {
+                true // Match all if prefix is empty
+            } else {
+                entry.virtual_path.starts_with(&normalized_prefix)
+            }
+        })
     }
 
     /// Get metadata for a file without reading contents
+    ///
+    /// Ignores case if the sandbox was built with `case_insensitive(true)`.
     pub fn get_entry(&self, virtual_path: &str) -> Option<&FileEntry> {
-        self.index.get(virtual_path)
+        self.index.get(virtual_path).or_else(|| {
+            let path = self.folded.as_ref()?.get(&virtual_path.to_lowercase())?;
+            self.index.get(path)
+        })
+    }
+
+    /// File count and total bytes per language
+    ///
+    /// Languages are detected as the parsers do (`FileMetadata::detect_language`),
+    /// so files nothing recognizes are counted under "unknown".
+    pub fn language_histogram(&self) -> HashMap<String, (usize, usize)> {
+        let mut histogram: HashMap<String, (usize, usize)> = HashMap::new();
+        for entry in self.index.values() {
+            let bytes = &self.arena[entry.range()];
+            let language = FileMetadata::detect_language(&entry.virtual_path, bytes);
+            let (files, size) = histogram.entry(language).or_default();
+            *files += 1;
+            *size += entry.length;
+        }
+        histogram
     }
 
     /// Get the total number of files
@@ -220,6 +1153,14 @@ impl Sandbox {
         self.index.len()
     }
 
+    /// All file contents as one slice, to slice with `FileEntry::range`
+    ///
+    /// Holds only bytes of live entries, each distinct content once, so
+    /// hashing it fingerprints the sandbox's contents without copying.
+    pub fn arena(&self) -> &[u8] {
+        &self.arena
+    }
+
     /// Get the total arena size in bytes
     pub fn total_size(&self) -> usize {
         self.arena.len()
@@ -0,0 +1,26 @@
+use super::SkipReason;
+
+/// Files an ingestion would add, without their contents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngestPlan {
+    /// Sanitized virtual path and uncompressed size of each selected file,
+    /// sorted by path
+    pub entries: Vec<(String, u64)>,
+    /// Archive path of each selected file whose path would be rejected,
+    /// with the reason, sorted by path
+    pub rejected: Vec<(String, SkipReason)>,
+}
+
+impl IngestPlan {
+    pub fn file_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Uncompressed bytes the selected files would take up
+    ///
+    /// An upper bound on the arena size, since identical files are stored
+    /// once.
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|&(_, size)| size).sum()
+    }
+}
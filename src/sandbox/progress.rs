@@ -0,0 +1,13 @@
+/// Snapshot of ingestion progress passed to the progress callback
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IngestProgress {
+    /// Archive bytes downloaded so far
+    pub bytes_downloaded: u64,
+    /// Expected archive size from Content-Length, when the server sends it
+    pub total_bytes: Option<u64>,
+    /// Files added to the sandbox so far
+    pub files_extracted: usize,
+}
+
+/// Callback invoked with progress updates during ingestion
+pub type ProgressCallback = Box<dyn Fn(IngestProgress) + Send + Sync>;
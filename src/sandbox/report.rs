@@ -0,0 +1,36 @@
+/// Why ingestion left a file out
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// Not selected by the include/exclude globs
+    Filtered,
+    /// Nothing was left of the path after stripping the archive root
+    EmptyPath,
+    /// Larger than the per-file size limit (lenient mode only)
+    TooLarge { size: u64, max: u64 },
+    /// Inflated past the compression ratio limit (lenient mode only)
+    SuspiciousCompressionRatio { ratio: f64, max: f64 },
+    /// Rejected by path sanitization, with the reason (lenient mode only)
+    InvalidPath(String),
+}
+
+/// What ingestion added and left out, accumulated across ingest calls
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngestReport {
+    /// Files added to the sandbox
+    pub added: usize,
+    /// Path of each file left out, with the reason
+    pub skipped: Vec<(String, SkipReason)>,
+}
+
+/// How a re-ingested repository differs from the previous sandbox
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateReport {
+    /// Paths that are new in this version
+    pub added: Vec<String>,
+    /// Paths whose contents changed
+    pub modified: Vec<String>,
+    /// Paths of the previous sandbox missing from this version, sorted
+    pub removed: Vec<String>,
+    /// Files whose bytes were reused from the previous sandbox
+    pub unchanged: usize,
+}
@@ -1,245 +1,1325 @@
-#[cfg(test)]
-mod tests {
-    use crate::SandboxBuilder;
-
-    #[test]
-    fn test_sandbox_builder_new() {
-        let builder = SandboxBuilder::new();
-        assert_eq!(builder.arena.len(), 0);
-        assert_eq!(builder.index.len(), 0);
-    }
+use crate::{SandboxBuilder, SkipReason};
 
-    #[test]
-    fn test_add_single_file() {
-        let mut builder = SandboxBuilder::new();
-        let result = builder.add_file("test.txt", b"hello world");
-        assert!(result.is_ok());
+#[test]
+fn test_sandbox_builder_new() {
+    let builder = SandboxBuilder::new();
+    assert_eq!(builder.arena.len(), 0);
+    assert_eq!(builder.index.len(), 0);
+}
 
-        let sandbox = builder.build();
-        assert_eq!(sandbox.file_count(), 1);
-        assert_eq!(sandbox.total_size(), 11);
-    }
+#[test]
+fn test_add_single_file() {
+    let mut builder = SandboxBuilder::new();
+    let result = builder.add_file("test.txt", b"hello world");
+    assert!(result.is_ok());
 
-    #[test]
-    fn test_add_multiple_files() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("file1.txt", b"content1").unwrap();
-        builder.add_file("file2.txt", b"content2").unwrap();
-        builder.add_file("file3.txt", b"content3").unwrap();
+    let sandbox = builder.build();
+    assert_eq!(sandbox.file_count(), 1);
+    assert_eq!(sandbox.total_size(), 11);
+}
 
-        let sandbox = builder.build();
-        assert_eq!(sandbox.file_count(), 3);
-        assert_eq!(sandbox.total_size(), 24); // 8 + 8 + 8
-    }
+#[test]
+fn test_add_multiple_files() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("file1.txt", b"content1").unwrap();
+    builder.add_file("file2.txt", b"content2").unwrap();
+    builder.add_file("file3.txt", b"content3").unwrap();
 
-    #[test]
-    fn test_get_file_zero_copy() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("test.txt", b"hello world").unwrap();
-        let sandbox = builder.build();
+    let sandbox = builder.build();
+    assert_eq!(sandbox.file_count(), 3);
+    assert_eq!(sandbox.total_size(), 24); // 8 + 8 + 8
+}
 
-        let content = sandbox.get("test.txt");
-        assert!(content.is_some());
-        assert_eq!(content.unwrap(), b"hello world");
-    }
+#[test]
+fn test_get_file_zero_copy() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("test.txt", b"hello world").unwrap();
+    let sandbox = builder.build();
 
-    #[test]
-    fn test_get_nonexistent_file() {
-        let sandbox = SandboxBuilder::new().build();
-        let content = sandbox.get("nonexistent.txt");
-        assert!(content.is_none());
-    }
+    let content = sandbox.get("test.txt");
+    assert!(content.is_some());
+    assert_eq!(content.unwrap(), b"hello world");
+}
 
-    #[test]
-    fn test_arena_concatenation() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("a.txt", b"AAA").unwrap();
-        builder.add_file("b.txt", b"BBB").unwrap();
-        builder.add_file("c.txt", b"CCC").unwrap();
+#[test]
+fn test_get_nonexistent_file() {
+    let sandbox = SandboxBuilder::new().build();
+    let content = sandbox.get("nonexistent.txt");
+    assert!(content.is_none());
+}
 
-        let sandbox = builder.build();
+#[test]
+fn test_arena_concatenation() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("a.txt", b"AAA").unwrap();
+    builder.add_file("b.txt", b"BBB").unwrap();
+    builder.add_file("c.txt", b"CCC").unwrap();
 
-        // Files should be concatenated in arena
-        assert_eq!(sandbox.get("a.txt").unwrap(), b"AAA");
-        assert_eq!(sandbox.get("b.txt").unwrap(), b"BBB");
-        assert_eq!(sandbox.get("c.txt").unwrap(), b"CCC");
+    let sandbox = builder.build();
 
-        // Arena should contain all data
-        assert_eq!(sandbox.total_size(), 9);
-    }
+    // Files should be concatenated in arena
+    assert_eq!(sandbox.get("a.txt").unwrap(), b"AAA");
+    assert_eq!(sandbox.get("b.txt").unwrap(), b"BBB");
+    assert_eq!(sandbox.get("c.txt").unwrap(), b"CCC");
 
-    #[test]
-    fn test_file_entry_metadata() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("test.txt", b"hello").unwrap();
-        let sandbox = builder.build();
+    // Arena should contain all data
+    assert_eq!(sandbox.total_size(), 9);
+}
 
-        let entry = sandbox.get_entry("test.txt");
-        assert!(entry.is_some());
+#[test]
+fn test_file_entry_metadata() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("test.txt", b"hello").unwrap();
+    let sandbox = builder.build();
 
-        let entry = entry.unwrap();
-        assert_eq!(entry.virtual_path, "test.txt");
-        assert_eq!(entry.length, 5);
-        assert_eq!(entry.offset, 0);
-    }
+    let entry = sandbox.get_entry("test.txt");
+    assert!(entry.is_some());
 
-    #[test]
-    fn test_file_size_limit() {
-        let mut builder = SandboxBuilder::new().max_file_size(10);
+    let entry = entry.unwrap();
+    assert_eq!(entry.virtual_path, "test.txt");
+    assert_eq!(entry.length, 5);
+    assert_eq!(entry.offset, 0);
+}
 
-        let result = builder.add_file("small.txt", b"tiny");
-        assert!(result.is_ok());
+#[test]
+fn test_file_size_limit() {
+    let mut builder = SandboxBuilder::new().max_file_size(10);
 
-        let result = builder.add_file("large.txt", b"this is way too large");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("File too large"));
-    }
+    let result = builder.add_file("small.txt", b"tiny");
+    assert!(result.is_ok());
 
-    #[test]
-    fn test_total_size_limit() {
-        let mut builder = SandboxBuilder::new().max_file_size(100).max_total_size(20);
+    let result = builder.add_file("large.txt", b"this is way too large");
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("File too large"));
+}
 
-        builder.add_file("file1.txt", b"12345").unwrap(); // 5 bytes
-        builder.add_file("file2.txt", b"67890").unwrap(); // 10 total
+#[test]
+fn test_total_size_limit() {
+    let mut builder = SandboxBuilder::new().max_file_size(100).max_total_size(20);
 
-        let result = builder.add_file("file3.txt", b"this will exceed"); // Would be 26 total
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("File too large"));
-    }
+    builder.add_file("file1.txt", b"12345").unwrap(); // 5 bytes
+    builder.add_file("file2.txt", b"67890").unwrap(); // 10 total
 
-    #[test]
-    fn test_list_all_files() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("a.txt", b"a").unwrap();
-        builder.add_file("b.txt", b"b").unwrap();
-        builder.add_file("c.txt", b"c").unwrap();
+    let result = builder.add_file("file3.txt", b"this will exceed"); // Would be 26 total
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("File too large"));
+}
 
-        let sandbox = builder.build();
-        let files: Vec<_> = sandbox.list().collect();
+#[test]
+fn test_list_all_files() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("a.txt", b"a").unwrap();
+    builder.add_file("b.txt", b"b").unwrap();
+    builder.add_file("c.txt", b"c").unwrap();
 
-        assert_eq!(files.len(), 3);
-    }
+    let sandbox = builder.build();
+    let files: Vec<_> = sandbox.list().collect();
 
-    #[test]
-    fn test_walk_prefix_basic() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("src/main.rs", b"").unwrap();
-        builder.add_file("src/lib.rs", b"").unwrap();
-        builder.add_file("tests/test.rs", b"").unwrap();
-        builder.add_file("README.md", b"").unwrap();
+    assert_eq!(files.len(), 3);
+}
 
-        let sandbox = builder.build();
+#[test]
+fn test_walk_prefix_basic() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("src/main.rs", b"").unwrap();
+    builder.add_file("src/lib.rs", b"").unwrap();
+    builder.add_file("tests/test.rs", b"").unwrap();
+    builder.add_file("README.md", b"").unwrap();
 
-        let src_files = sandbox.walk_prefix("src");
-        assert_eq!(src_files.len(), 2);
+    let sandbox = builder.build();
 
-        let test_files = sandbox.walk_prefix("tests");
-        assert_eq!(test_files.len(), 1);
+    let src_files = sandbox.walk_prefix("src");
+    assert_eq!(src_files.len(), 2);
 
-        let all_files = sandbox.walk_prefix("");
-        assert_eq!(all_files.len(), 4);
-    }
+    let test_files = sandbox.walk_prefix("tests");
+    assert_eq!(test_files.len(), 1);
 
-    #[test]
-    fn test_walk_prefix_nested() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("src/parser/mod.rs", b"").unwrap();
-        builder.add_file("src/parser/registry.rs", b"").unwrap();
-        builder.add_file("src/sandbox/mod.rs", b"").unwrap();
+    let all_files = sandbox.walk_prefix("");
+    assert_eq!(all_files.len(), 4);
+}
 
-        let sandbox = builder.build();
+#[test]
+fn test_walk_prefix_nested() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("src/parser/mod.rs", b"").unwrap();
+    builder.add_file("src/parser/registry.rs", b"").unwrap();
+    builder.add_file("src/sandbox/mod.rs", b"").unwrap();
 
-        let parser_files = sandbox.walk_prefix("src/parser");
-        assert_eq!(parser_files.len(), 2);
+    let sandbox = builder.build();
 
-        let sandbox_files = sandbox.walk_prefix("src/sandbox");
-        assert_eq!(sandbox_files.len(), 1);
+    let parser_files = sandbox.walk_prefix("src/parser");
+    assert_eq!(parser_files.len(), 2);
 
-        let all_src = sandbox.walk_prefix("src");
-        assert_eq!(all_src.len(), 3);
-    }
+    let sandbox_files = sandbox.walk_prefix("src/sandbox");
+    assert_eq!(sandbox_files.len(), 1);
 
-    #[test]
-    fn test_walk_prefix_trailing_slash() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("dir/file.txt", b"").unwrap();
+    let all_src = sandbox.walk_prefix("src");
+    assert_eq!(all_src.len(), 3);
+}
 
-        let sandbox = builder.build();
+#[test]
+fn test_walk_prefix_trailing_slash() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("dir/file.txt", b"").unwrap();
 
-        // Should work with or without trailing slash
-        assert_eq!(sandbox.walk_prefix("dir").len(), 1);
-        assert_eq!(sandbox.walk_prefix("dir/").len(), 1);
-    }
+    let sandbox = builder.build();
 
-    #[test]
-    fn test_path_sanitization_in_add_file() {
-        let mut builder = SandboxBuilder::new();
+    // Should work with or without trailing slash
+    assert_eq!(sandbox.walk_prefix("dir").len(), 1);
+    assert_eq!(sandbox.walk_prefix("dir/").len(), 1);
+}
 
-        // Should reject dangerous paths
-        let result = builder.add_file("../etc/passwd", b"bad");
-        assert!(result.is_err());
+#[test]
+fn test_path_sanitization_in_add_file() {
+    let mut builder = SandboxBuilder::new();
 
-        let result = builder.add_file("/etc/passwd", b"bad");
-        assert!(result.is_err());
-    }
+    // Should reject dangerous paths
+    let result = builder.add_file("../etc/passwd", b"bad");
+    assert!(result.is_err());
 
-    #[test]
-    fn test_empty_sandbox() {
-        let sandbox = SandboxBuilder::new().build();
-        assert_eq!(sandbox.file_count(), 0);
-        assert_eq!(sandbox.total_size(), 0);
-        assert_eq!(sandbox.list().count(), 0);
-    }
+    let result = builder.add_file("/etc/passwd", b"bad");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_empty_sandbox() {
+    let sandbox = SandboxBuilder::new().build();
+    assert_eq!(sandbox.file_count(), 0);
+    assert_eq!(sandbox.total_size(), 0);
+    assert_eq!(sandbox.list().count(), 0);
+}
+
+#[test]
+fn test_binary_data() {
+    let mut builder = SandboxBuilder::new();
+    let binary_data: Vec<u8> = (0..=255).collect();
+    builder.add_file("binary.dat", &binary_data).unwrap();
+
+    let sandbox = builder.build();
+    let retrieved = sandbox.get("binary.dat").unwrap();
+
+    assert_eq!(retrieved.len(), 256);
+    assert_eq!(retrieved, binary_data.as_slice());
+}
+
+#[test]
+fn test_large_file_offsets() {
+    let mut builder = SandboxBuilder::new();
+
+    // Add files to test offset calculation
+    builder.add_file("file1.txt", b"12345").unwrap(); // offset 0, len 5
+    builder.add_file("file2.txt", b"67890").unwrap(); // offset 5, len 5
+    builder.add_file("file3.txt", b"ABCDE").unwrap(); // offset 10, len 5
+
+    let sandbox = builder.build();
+
+    let entry1 = sandbox.get_entry("file1.txt").unwrap();
+    assert_eq!(entry1.offset, 0);
+    assert_eq!(entry1.length, 5);
+
+    let entry2 = sandbox.get_entry("file2.txt").unwrap();
+    assert_eq!(entry2.offset, 5);
+    assert_eq!(entry2.length, 5);
+
+    let entry3 = sandbox.get_entry("file3.txt").unwrap();
+    assert_eq!(entry3.offset, 10);
+    assert_eq!(entry3.length, 5);
+}
+
+#[test]
+fn test_duplicate_path_overwrites() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("test.txt", b"first").unwrap();
+    builder.add_file("test.txt", b"second").unwrap();
+
+    let sandbox = builder.build();
+
+    // Should only have one entry (overwritten)
+    assert_eq!(sandbox.file_count(), 1);
+
+    // Stale bytes from the first write are compacted away
+    assert_eq!(sandbox.total_size(), 6);
+    assert_eq!(sandbox.get("test.txt").unwrap(), b"second");
+}
+
+#[test]
+fn test_build_compacts_arena_to_live_entries() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("a.txt", b"AAA").unwrap();
+    builder.add_file("b.txt", b"BBBB").unwrap();
+    builder.add_file("a.txt", b"AAAAA").unwrap();
+    builder.add_file("c.txt", b"CC").unwrap();
+    builder.add_file("b.txt", b"B").unwrap();
+
+    let sandbox = builder.build();
+
+    let live_size: usize = sandbox.list().map(|entry| entry.length).sum();
+    assert_eq!(sandbox.total_size(), live_size);
+    assert_eq!(sandbox.total_size(), 8); // 5 + 1 + 2
+
+    assert_eq!(sandbox.get("a.txt").unwrap(), b"AAAAA");
+    assert_eq!(sandbox.get("b.txt").unwrap(), b"B");
+    assert_eq!(sandbox.get("c.txt").unwrap(), b"CC");
+}
+
+#[test]
+fn test_ingest_local_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src/parser")).unwrap();
+    std::fs::write(dir.path().join("README.md"), b"# readme").unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), b"pub mod parser;").unwrap();
+    std::fs::write(dir.path().join("src/parser/mod.rs"), b"").unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .ingest_local_dir(dir.path())
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.file_count(), 3);
+    assert_eq!(sandbox.get("README.md").unwrap(), b"# readme");
+    assert_eq!(sandbox.get("src/lib.rs").unwrap(), b"pub mod parser;");
+    assert_eq!(sandbox.walk_prefix("src/parser").len(), 1);
+}
+
+#[test]
+fn test_ingest_local_dir_file_too_large() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("big.bin"), vec![0u8; 64]).unwrap();
+
+    let result = SandboxBuilder::new()
+        .max_file_size(10)
+        .ingest_local_dir(dir.path());
+
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::FileTooLarge { size: 64, max: 10 })
+    ));
+}
+
+#[test]
+fn test_ingest_local_dir_missing_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = SandboxBuilder::new().ingest_local_dir(&dir.path().join("missing"));
+    assert!(matches!(result, Err(crate::SandboxError::ReadFailed(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ingest_local_dir_skips_escaping_symlinks() {
+    let outside = tempfile::tempdir().unwrap();
+    std::fs::write(outside.path().join("secret.txt"), b"secret").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"a").unwrap();
+    std::os::unix::fs::symlink(
+        outside.path().join("secret.txt"),
+        dir.path().join("leak.txt"),
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(dir.path().join("a.txt"), dir.path().join("alias.txt")).unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .ingest_local_dir(dir.path())
+        .unwrap()
+        .build();
 
-    #[test]
-    fn test_binary_data() {
-        let mut builder = SandboxBuilder::new();
-        let binary_data: Vec<u8> = (0..=255).collect();
-        builder.add_file("binary.dat", &binary_data).unwrap();
+    assert!(sandbox.get("leak.txt").is_none());
+    assert_eq!(sandbox.get("alias.txt").unwrap(), b"a");
+    assert_eq!(sandbox.file_count(), 2);
+}
+
+fn write_test_zip(path: &std::path::Path, entries: &[(&str, &[u8])]) {
+    use std::io::Write;
 
-        let sandbox = builder.build();
-        let retrieved = sandbox.get("binary.dat").unwrap();
+    let file = std::fs::File::create(path).unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
 
-        assert_eq!(retrieved.len(), 256);
-        assert_eq!(retrieved, binary_data.as_slice());
+    for (name, data) in entries {
+        if name.ends_with('/') {
+            zip.add_directory(*name, options).unwrap();
+        } else {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
     }
 
-    #[test]
-    fn test_large_file_offsets() {
-        let mut builder = SandboxBuilder::new();
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_ingest_zip_file_strips_top_level_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(
+        &zip_path,
+        &[
+            ("repo-main/", b""),
+            ("repo-main/src/", b""),
+            ("repo-main/src/lib.rs", b"pub fn lib() {}"),
+            ("repo-main/README.md", b"# repo"),
+        ],
+    );
 
-        // Add files to test offset calculation
-        builder.add_file("file1.txt", b"12345").unwrap(); // offset 0, len 5
-        builder.add_file("file2.txt", b"67890").unwrap(); // offset 5, len 5
-        builder.add_file("file3.txt", b"ABCDE").unwrap(); // offset 10, len 5
+    let sandbox = SandboxBuilder::new()
+        .ingest_zip_file(&zip_path)
+        .unwrap()
+        .build();
 
-        let sandbox = builder.build();
+    // Directory entries and the bare top-level dir are skipped
+    assert_eq!(sandbox.file_count(), 2);
+    assert_eq!(sandbox.get("src/lib.rs").unwrap(), b"pub fn lib() {}");
+    assert_eq!(sandbox.get("README.md").unwrap(), b"# repo");
+}
+
+#[test]
+fn test_plan_zip_file_lists_selected_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(
+        &zip_path,
+        &[
+            ("repo-main/", b""),
+            ("repo-main/src/lib.rs", b"pub fn lib() {}"),
+            ("repo-main/README.md", b"# repo"),
+            ("repo-main/assets/logo.png", b"PNG"),
+        ],
+    );
+
+    let builder = SandboxBuilder::new()
+        .with_exclude_globs(vec!["*.png".to_string()])
+        .unwrap();
+    let plan = builder.plan_zip_file(&zip_path).unwrap();
+
+    assert_eq!(
+        plan.entries,
+        vec![("README.md".to_string(), 6), ("src/lib.rs".to_string(), 15)]
+    );
+    assert_eq!(plan.file_count(), 2);
+    assert_eq!(plan.total_size(), 21);
+    // Planning leaves the builder empty
+    assert_eq!(builder.build().file_count(), 0);
+}
+
+#[test]
+fn test_plan_zip_file_rejects_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("evil.zip");
+    write_test_zip(
+        &zip_path,
+        &[
+            ("repo-main/../../etc/passwd", b"bad"),
+            ("repo-main/src/lib.rs", b"lib"),
+        ],
+    );
 
-        let entry1 = sandbox.get_entry("file1.txt").unwrap();
-        assert_eq!(entry1.offset, 0);
-        assert_eq!(entry1.length, 5);
+    let plan = SandboxBuilder::new().plan_zip_file(&zip_path).unwrap();
+
+    // The bad entry is reported and the rest of the archive still planned
+    assert_eq!(plan.entries, vec![("src/lib.rs".to_string(), 3)]);
+    assert_eq!(plan.rejected.len(), 1);
+    assert_eq!(plan.rejected[0].0, "../../etc/passwd");
+    assert!(matches!(plan.rejected[0].1, SkipReason::InvalidPath(_)));
+    assert_eq!(plan.file_count(), 1);
+}
 
-        let entry2 = sandbox.get_entry("file2.txt").unwrap();
-        assert_eq!(entry2.offset, 5);
-        assert_eq!(entry2.length, 5);
+#[test]
+fn test_ingest_zip_file_rejects_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("evil.zip");
+    write_test_zip(&zip_path, &[("repo-main/../../etc/passwd", b"bad")]);
 
-        let entry3 = sandbox.get_entry("file3.txt").unwrap();
-        assert_eq!(entry3.offset, 10);
-        assert_eq!(entry3.length, 5);
+    let result = SandboxBuilder::new().ingest_zip_file(&zip_path);
+    assert!(matches!(result, Err(crate::SandboxError::InvalidPath(_))));
+}
+
+#[test]
+fn test_ingest_zip_file_invalid_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("not-a.zip");
+    std::fs::write(&zip_path, b"definitely not a zip").unwrap();
+
+    let result = SandboxBuilder::new().ingest_zip_file(&zip_path);
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::ArchiveParseFailed(_))
+    ));
+}
+
+fn make_test_tarball(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut tar = tar::Builder::new(Vec::new());
+
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        if name.ends_with('/') {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+        } else {
+            header.set_size(data.len() as u64);
+        }
+        header.set_mode(0o644);
+        tar.append_data(&mut header, name, *data).unwrap();
     }
 
-    #[test]
-    fn test_duplicate_path_overwrites() {
-        let mut builder = SandboxBuilder::new();
-        builder.add_file("test.txt", b"first").unwrap();
-        builder.add_file("test.txt", b"second").unwrap();
+    tar.into_inner().unwrap()
+}
+
+#[test]
+fn test_ingest_tarball_strips_top_level_dir() {
+    let tarball = make_test_tarball(&[
+        ("repo-main/", b""),
+        ("repo-main/src/lib.rs", b"pub fn lib() {}"),
+        ("repo-main/README.md", b"# repo"),
+    ]);
+
+    let mut builder = SandboxBuilder::new();
+    builder.ingest_tarball(tarball.as_slice()).unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.file_count(), 2);
+    assert_eq!(sandbox.get("src/lib.rs").unwrap(), b"pub fn lib() {}");
+    assert_eq!(sandbox.get("README.md").unwrap(), b"# repo");
+}
+
+#[test]
+fn test_ingest_tarball_gzip_round_trip() {
+    use std::io::Write;
+
+    let tarball = make_test_tarball(&[("repo-v1/main.rs", b"fn main() {}")]);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&tarball).unwrap();
+    let gz = encoder.finish().unwrap();
+
+    let mut builder = SandboxBuilder::new();
+    builder
+        .ingest_tarball(flate2::read::GzDecoder::new(gz.as_slice()))
+        .unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.get("main.rs").unwrap(), b"fn main() {}");
+}
+
+#[test]
+fn test_ingest_tarball_rejects_oversized_entry_before_reading() {
+    use std::io::Write;
+
+    // 8 MB of zeros gzips to a few KB
+    let zeros = vec![0u8; 8 * 1024 * 1024];
+    let tarball =
+        make_test_tarball(&[("repo-main/zeros.bin", &zeros), ("repo-main/ok.txt", b"ok")]);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(&tarball).unwrap();
+    let gz = encoder.finish().unwrap();
+    assert!(gz.len() < 64 * 1024);
+
+    let mut builder = SandboxBuilder::new().max_file_size(1024);
+    let result = builder.ingest_tarball(flate2::read::GzDecoder::new(gz.as_slice()));
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::FileTooLarge { size, max: 1024 }) if size == zeros.len() as u64
+    ));
+
+    let mut builder = SandboxBuilder::new().max_file_size(1024).lenient(true);
+    builder
+        .ingest_tarball(flate2::read::GzDecoder::new(gz.as_slice()))
+        .unwrap();
+    assert_eq!(
+        builder.report().skipped,
+        vec![(
+            "zeros.bin".to_string(),
+            SkipReason::TooLarge {
+                size: zeros.len() as u64,
+                max: 1024
+            }
+        )]
+    );
+    assert_eq!(builder.build().get("ok.txt").unwrap(), b"ok");
+}
+
+#[test]
+fn test_ingest_tarball_invalid_archive() {
+    let mut builder = SandboxBuilder::new();
+    let result = builder.ingest_tarball(flate2::read::GzDecoder::new(&b"not gzip"[..]));
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::ArchiveParseFailed(_))
+    ));
+}
+
+#[test]
+fn test_status_error_distinguishes_auth_failures() {
+    use crate::SandboxError;
+    use reqwest::StatusCode;
+
+    assert!(matches!(
+        super::status_error(StatusCode::UNAUTHORIZED),
+        SandboxError::Unauthorized(_)
+    ));
+    assert!(matches!(
+        super::status_error(StatusCode::FORBIDDEN),
+        SandboxError::Unauthorized(_)
+    ));
+    assert!(matches!(
+        super::status_error(StatusCode::NOT_FOUND),
+        SandboxError::DownloadFailed(_)
+    ));
+    assert!(matches!(
+        super::status_error(StatusCode::INTERNAL_SERVER_ERROR),
+        SandboxError::DownloadFailed(_)
+    ));
+}
+
+#[test]
+fn test_git_ref_archive_paths() {
+    use crate::GitRef;
+
+    assert_eq!(
+        GitRef::Branch("main".to_string()).archive_path(),
+        "refs/heads/main"
+    );
+    assert_eq!(
+        GitRef::Tag("v1.0.0".to_string()).archive_path(),
+        "refs/tags/v1.0.0"
+    );
+    assert_eq!(
+        GitRef::Commit("3f2a9c1".to_string()).archive_path(),
+        "3f2a9c1"
+    );
+}
+
+#[test]
+fn test_remove_file() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("Cargo.lock", b"lockfile").unwrap();
+    builder.add_file("src/lib.rs", b"lib").unwrap();
+
+    assert!(builder.remove_file("Cargo.lock"));
+    assert!(!builder.remove_file("Cargo.lock"));
+    assert!(!builder.remove_file("missing.txt"));
+
+    let sandbox = builder.build();
+    assert_eq!(sandbox.file_count(), 1);
+    assert!(sandbox.get("Cargo.lock").is_none());
+    assert_eq!(sandbox.get("src/lib.rs").unwrap(), b"lib");
+
+    // Removed bytes are compacted out of the arena
+    assert_eq!(sandbox.total_size(), 3);
+}
+
+#[test]
+fn test_include_globs_filter_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(
+        &zip_path,
+        &[
+            ("repo-main/Cargo.toml", b"[package]"),
+            ("repo-main/src/lib.rs", b"pub fn lib() {}"),
+            ("repo-main/assets/logo.png", b"PNG"),
+        ],
+    );
+
+    let sandbox = SandboxBuilder::new()
+        .with_include_globs(vec!["*.rs".to_string(), "*.toml".to_string()])
+        .unwrap()
+        .ingest_zip_file(&zip_path)
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.file_count(), 2);
+    assert!(sandbox.get("src/lib.rs").is_some());
+    assert!(sandbox.get("Cargo.toml").is_some());
+    assert!(sandbox.get("assets/logo.png").is_none());
+}
+
+#[test]
+fn test_exclude_globs_take_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src/generated")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), b"lib").unwrap();
+    std::fs::write(dir.path().join("src/generated/api.rs"), b"gen").unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .with_include_globs(vec!["src/**/*.rs".to_string()])
+        .unwrap()
+        .with_exclude_globs(vec!["**/generated/**".to_string()])
+        .unwrap()
+        .ingest_local_dir(dir.path())
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.file_count(), 1);
+    assert!(sandbox.get("src/lib.rs").is_some());
+}
+
+#[test]
+fn test_filtered_files_skip_size_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("huge.bin"), vec![0u8; 64]).unwrap();
+    std::fs::write(dir.path().join("small.rs"), b"fn a() {}").unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .max_file_size(16)
+        .max_total_size(16)
+        .with_include_globs(vec!["*.rs".to_string()])
+        .unwrap()
+        .ingest_local_dir(dir.path())
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.file_count(), 1);
+    assert_eq!(sandbox.total_size(), 9);
+}
+
+#[test]
+fn test_invalid_glob() {
+    let result = SandboxBuilder::new().with_include_globs(vec!["src/[".to_string()]);
+    assert!(matches!(result, Err(crate::SandboxError::InvalidGlob(_))));
+}
+
+#[test]
+fn test_ingest_local_dir_with_gitignore() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("vendor/big")).unwrap();
+    std::fs::create_dir_all(root.join(".git/info")).unwrap();
+
+    std::fs::write(root.join(".gitignore"), b"target/\n*.log\n").unwrap();
+    std::fs::write(root.join("vendor/.gitignore"), b"big/\n").unwrap();
+    std::fs::write(root.join(".git/info/exclude"), b"scratch.txt\n").unwrap();
+    std::fs::write(root.join(".git/HEAD"), b"ref: refs/heads/main").unwrap();
+
+    std::fs::write(root.join("src/lib.rs"), b"lib").unwrap();
+    std::fs::write(root.join("debug.log"), b"log").unwrap();
+    std::fs::write(root.join("scratch.txt"), b"tmp").unwrap();
+    std::fs::write(root.join("vendor/keep.rs"), b"keep").unwrap();
+    std::fs::write(root.join("vendor/big/blob.bin"), b"blob").unwrap();
+
+    // Named like an ignored dir but only as a substring
+    std::fs::create_dir_all(root.join("src/my_target_helper")).unwrap();
+    std::fs::write(root.join("src/my_target_helper/mod.rs"), b"helper").unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .with_gitignore(true)
+        .ingest_local_dir(root)
+        .unwrap()
+        .build();
+
+    let mut paths: Vec<_> = sandbox.list().map(|e| e.virtual_path.as_str()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            ".gitignore",
+            "src/lib.rs",
+            "src/my_target_helper/mod.rs",
+            "vendor/.gitignore",
+            "vendor/keep.rs",
+        ]
+    );
+}
+
+#[test]
+fn test_ingest_local_dir_ignores_gitignore_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(".gitignore"), b"*.log\n").unwrap();
+    std::fs::write(dir.path().join("debug.log"), b"log").unwrap();
+
+    let sandbox = SandboxBuilder::new()
+        .ingest_local_dir(dir.path())
+        .unwrap()
+        .build();
+
+    assert!(sandbox.get("debug.log").is_some());
+}
+
+#[test]
+fn test_progress_reported_per_file() {
+    use crate::IngestProgress;
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"a").unwrap();
+    std::fs::write(dir.path().join("b.txt"), b"b").unwrap();
+    std::fs::write(dir.path().join("c.txt"), b"c").unwrap();
+
+    let events: Arc<Mutex<Vec<IngestProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let events_clone = Arc::clone(&events);
+
+    SandboxBuilder::new()
+        .with_progress(move |progress| events_clone.lock().unwrap().push(progress))
+        .ingest_local_dir(dir.path())
+        .unwrap();
+
+    let events = events.lock().unwrap();
+    let counts: Vec<usize> = events.iter().map(|p| p.files_extracted).collect();
+    assert_eq!(counts, vec![1, 2, 3]);
+    assert!(events.iter().all(|p| p.bytes_downloaded == 0));
+}
+
+#[test]
+fn test_walk_prefix_iter_matches_walk_prefix() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("src/main.rs", b"").unwrap();
+    builder.add_file("src/parser/mod.rs", b"").unwrap();
+    builder.add_file("srcs/other.rs", b"").unwrap();
+    builder.add_file("README.md", b"").unwrap();
+
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.walk_prefix_iter("src").count(), 2);
+    assert_eq!(sandbox.walk_prefix_iter("src/").count(), 2);
+    assert_eq!(sandbox.walk_prefix_iter("").count(), 4);
+
+    // The prefix only needs to live for the call
+    let prefix = String::from("src/parser");
+    let iter = sandbox.walk_prefix_iter(&prefix);
+    drop(prefix);
+    assert_eq!(iter.count(), 1);
+}
+
+#[test]
+fn test_list_sorted() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("src/main.rs", b"").unwrap();
+    builder.add_file("Cargo.toml", b"").unwrap();
+    builder.add_file("src/lib.rs", b"").unwrap();
+    builder.add_file("README.md", b"").unwrap();
+
+    let sandbox = builder.build();
+    let paths: Vec<&str> = sandbox
+        .list_sorted()
+        .iter()
+        .map(|entry| entry.virtual_path.as_str())
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec!["Cargo.toml", "README.md", "src/lib.rs", "src/main.rs"]
+    );
+}
+
+#[test]
+fn test_get_str() {
+    let mut builder = SandboxBuilder::new();
+    builder
+        .add_file("hello.txt", "Hello, 世界".as_bytes())
+        .unwrap();
+    builder.add_file("binary.dat", &[0xFF, 0xFE, 0x00]).unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(
+        sandbox.get_str("hello.txt").unwrap().unwrap(),
+        "Hello, 世界"
+    );
+    assert!(sandbox.get_str("binary.dat").unwrap().is_err());
+    assert!(sandbox.get_str("missing.txt").is_none());
+}
+
+#[test]
+fn test_file_count_limit() {
+    let mut builder = SandboxBuilder::new().max_file_count(2);
+
+    builder.add_file("a.txt", b"a").unwrap();
+    builder.add_file("b.txt", b"b").unwrap();
+
+    // Overwriting an existing path doesn't count as a new file
+    builder.add_file("a.txt", b"aa").unwrap();
+
+    let result = builder.add_file("c.txt", b"c");
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::TooManyFiles { count: 3, max: 2 })
+    ));
+}
+
+#[test]
+fn test_zip_bomb_ratio_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("bomb.zip");
+    let zeros = vec![0u8; 2 * 1024 * 1024];
+    write_test_zip(&zip_path, &[("repo-main/zeros.bin", &zeros)]);
+
+    let result = SandboxBuilder::new().ingest_zip_file(&zip_path);
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::SuspiciousCompressionRatio { .. })
+    ));
+}
+
+#[test]
+fn test_small_compressible_file_skips_ratio_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("padded.zip");
+    let blanks = vec![b' '; 256 * 1024];
+    write_test_zip(&zip_path, &[("repo-main/padded.txt", &blanks)]);
+
+    let sandbox = SandboxBuilder::new()
+        .ingest_zip_file(&zip_path)
+        .unwrap()
+        .build();
+    assert_eq!(sandbox.get("padded.txt").unwrap().len(), blanks.len());
+
+    // Lowering the floor puts the same file back under the ratio check
+    let result = SandboxBuilder::new()
+        .min_ratio_check_size(0)
+        .ingest_zip_file(&zip_path);
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::SuspiciousCompressionRatio { .. })
+    ));
+}
+
+#[test]
+fn test_lenient_skips_suspicious_compression_ratio() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("bomb.zip");
+    let zeros = vec![0u8; 2 * 1024 * 1024];
+    write_test_zip(
+        &zip_path,
+        &[("repo-main/zeros.bin", &zeros), ("repo-main/ok.txt", b"ok")],
+    );
+
+    let builder = SandboxBuilder::new()
+        .lenient(true)
+        .ingest_zip_file(&zip_path)
+        .unwrap();
+
+    let report = builder.report().clone();
+    assert_eq!(report.added, 1);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].0, "zeros.bin");
+    assert!(matches!(
+        report.skipped[0].1,
+        SkipReason::SuspiciousCompressionRatio { max: 100.0, .. }
+    ));
+
+    let sandbox = builder.build();
+    assert!(sandbox.get("zeros.bin").is_none());
+    assert_eq!(sandbox.get("ok.txt"), Some(&b"ok"[..]));
+}
+
+#[test]
+fn test_compression_ratio_configurable() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("zeros.zip");
+    let zeros = vec![0u8; 2 * 1024 * 1024];
+    write_test_zip(&zip_path, &[("repo-main/zeros.bin", &zeros)]);
+
+    let sandbox = SandboxBuilder::new()
+        .max_compression_ratio(10_000.0)
+        .ingest_zip_file(&zip_path)
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.get("zeros.bin").unwrap().len(), zeros.len());
+}
+
+#[test]
+fn test_zip_entry_over_size_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("big.zip");
+    let text: Vec<u8> = (0..4096u32).flat_map(|i| i.to_le_bytes()).collect();
+    write_test_zip(&zip_path, &[("repo-main/big.bin", &text)]);
+
+    let result = SandboxBuilder::new()
+        .max_file_size(1024)
+        .ingest_zip_file(&zip_path);
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::FileTooLarge { max: 1024, .. })
+    ));
+}
+
+#[test]
+fn test_identical_content_deduplicated() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("LICENSE", b"MIT License").unwrap();
+    builder
+        .add_file("vendor/dep/LICENSE", b"MIT License")
+        .unwrap();
+    builder.add_file("README.md", b"readme").unwrap();
+
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.file_count(), 3);
+    assert_eq!(sandbox.total_size(), 11 + 6); // payload counted once
+    assert_eq!(sandbox.get("LICENSE").unwrap(), b"MIT License");
+    assert_eq!(sandbox.get("vendor/dep/LICENSE").unwrap(), b"MIT License");
+
+    let a = sandbox.get_entry("LICENSE").unwrap();
+    let b = sandbox.get_entry("vendor/dep/LICENSE").unwrap();
+    assert_eq!(a.offset, b.offset);
+}
 
-        let sandbox = builder.build();
+#[test]
+fn test_dedup_does_not_count_toward_total_size() {
+    let mut builder = SandboxBuilder::new().max_total_size(10);
+    builder.add_file("a.txt", b"12345678").unwrap();
 
-        // Should only have one entry (overwritten)
-        assert_eq!(sandbox.file_count(), 1);
+    // Identical bytes add nothing to the arena, so this still fits
+    builder.add_file("b.txt", b"12345678").unwrap();
 
-        // But arena contains both (not ideal, but documents current behavior)
-        assert_eq!(sandbox.total_size(), 11); // 5 + 6
+    let result = builder.add_file("c.txt", b"abc");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_compaction_preserves_shared_content() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("stale.txt", b"stale").unwrap();
+    builder.add_file("a.txt", b"shared").unwrap();
+    builder.add_file("b.txt", b"shared").unwrap();
+    builder.add_file("stale.txt", b"fresh").unwrap();
+
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.total_size(), 6 + 5);
+    assert_eq!(sandbox.get("a.txt").unwrap(), b"shared");
+    assert_eq!(sandbox.get("b.txt").unwrap(), b"shared");
+    assert_eq!(sandbox.get("stale.txt").unwrap(), b"fresh");
+    assert_eq!(
+        sandbox.get_entry("a.txt").unwrap().offset,
+        sandbox.get_entry("b.txt").unwrap().offset
+    );
+}
+
+#[test]
+fn test_lookup_is_case_sensitive_by_default() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("README.md", b"upper").unwrap();
+    builder.add_file("Readme.md", b"mixed").unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.file_count(), 2);
+    assert_eq!(sandbox.get("README.md").unwrap(), b"upper");
+    assert!(sandbox.get("readme.md").is_none());
+}
+
+#[test]
+fn test_case_insensitive_lookup_preserves_casing() {
+    let mut builder = SandboxBuilder::new().case_insensitive(true);
+    builder.add_file("docs/Readme.md", b"readme").unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.get("docs/README.md").unwrap(), b"readme");
+    assert_eq!(
+        sandbox.get_str("DOCS/readme.MD").unwrap().unwrap(),
+        "readme"
+    );
+    assert_eq!(
+        sandbox.get_entry("docs/README.md").unwrap().virtual_path,
+        "docs/Readme.md"
+    );
+    assert!(sandbox.get("docs/other.md").is_none());
+}
+
+#[test]
+fn test_case_insensitive_last_writer_wins() {
+    let mut builder = SandboxBuilder::new()
+        .case_insensitive(true)
+        .max_file_count(1);
+    builder.add_file("README.md", b"first").unwrap();
+    // Replaces the first file, so it doesn't count toward the limit
+    builder.add_file("Readme.md", b"second").unwrap();
+    let sandbox = builder.build();
+
+    assert_eq!(sandbox.file_count(), 1);
+    assert_eq!(sandbox.get("README.md").unwrap(), b"second");
+    assert_eq!(
+        sandbox.get_entry("readme.md").unwrap().virtual_path,
+        "Readme.md"
+    );
+    // The replaced file's bytes are compacted away
+    assert_eq!(sandbox.total_size(), b"second".len());
+}
+
+#[test]
+fn test_case_insensitive_remove_file() {
+    let mut builder = SandboxBuilder::new().case_insensitive(true);
+    builder.add_file("Makefile", b"all:").unwrap();
+
+    assert!(builder.remove_file("MAKEFILE"));
+    assert!(!builder.remove_file("Makefile"));
+    assert_eq!(builder.build().file_count(), 0);
+}
+
+#[test]
+fn test_language_histogram() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("src/lib.rs", b"pub fn a() {}").unwrap();
+    builder.add_file("src/main.rs", b"fn main() {}").unwrap();
+    builder.add_file("Makefile", b"all:").unwrap();
+    builder
+        .add_file("scripts/run", b"#!/usr/bin/env python3\n")
+        .unwrap();
+    builder.add_file("data.bin", &[0, 1, 2]).unwrap();
+    let sandbox = builder.build();
+
+    let histogram = sandbox.language_histogram();
+
+    assert_eq!(histogram.len(), 4);
+    assert_eq!(histogram["rust"], (2, 25));
+    assert_eq!(histogram["makefile"], (1, 4));
+    assert_eq!(histogram["python"], (1, 23));
+    assert_eq!(histogram["unknown"], (1, 3));
+}
+
+#[test]
+fn test_path_prefix_namespaces_repositories() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("first.zip");
+    let second = dir.path().join("second.zip");
+    write_test_zip(
+        &first,
+        &[
+            ("first-main/README.md", b"first"),
+            ("first-main/src/lib.rs", b"pub fn first() {}"),
+        ],
+    );
+    write_test_zip(&second, &[("second-main/README.md", b"second")]);
+
+    let sandbox = SandboxBuilder::new()
+        .with_path_prefix("first")
+        .unwrap()
+        .ingest_zip_file(&first)
+        .unwrap()
+        .with_path_prefix("/second/")
+        .unwrap()
+        .ingest_zip_file(&second)
+        .unwrap()
+        .build();
+
+    assert_eq!(sandbox.file_count(), 3);
+    assert_eq!(sandbox.get("first/README.md").unwrap(), b"first");
+    assert_eq!(sandbox.get("second/README.md").unwrap(), b"second");
+    assert!(sandbox.get("README.md").is_none());
+
+    let mut first_paths: Vec<&str> = sandbox
+        .walk_prefix("first")
+        .iter()
+        .map(|e| e.virtual_path.as_str())
+        .collect();
+    first_paths.sort();
+    assert_eq!(first_paths, vec!["first/README.md", "first/src/lib.rs"]);
+}
+
+#[test]
+fn test_path_prefix_applies_to_plans_and_rejects_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(&zip_path, &[("repo-main/src/lib.rs", b"lib")]);
+
+    let plan = SandboxBuilder::new()
+        .with_path_prefix("repo")
+        .unwrap()
+        .plan_zip_file(&zip_path)
+        .unwrap();
+    assert_eq!(plan.entries, vec![("repo/src/lib.rs".to_string(), 3)]);
+
+    assert!(matches!(
+        SandboxBuilder::new().with_path_prefix("../escape"),
+        Err(crate::SandboxError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn test_arena_slices_match_entries() {
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("a.txt", b"alpha").unwrap();
+    builder.add_file("b.txt", b"beta").unwrap();
+    builder.add_file("c.txt", b"alpha").unwrap();
+    let sandbox = builder.build();
+
+    // Shared content is stored once
+    assert_eq!(sandbox.arena().len(), sandbox.total_size());
+    assert_eq!(sandbox.arena().len(), b"alphabeta".len());
+
+    for entry in sandbox.list() {
+        assert_eq!(
+            &sandbox.arena()[entry.range()],
+            sandbox.get(&entry.virtual_path).unwrap()
+        );
     }
 }
+
+#[test]
+fn test_lenient_ingest_skips_rejected_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(
+        &zip_path,
+        &[
+            ("repo-main/src/lib.rs", b"pub fn lib() {}"),
+            ("repo-main/big.bin", &[7u8; 64]),
+            ("repo-main/../../etc/passwd", b"bad"),
+            ("repo-main/logo.png", b"PNG"),
+        ],
+    );
+
+    let builder = SandboxBuilder::new()
+        .max_file_size(32)
+        .with_exclude_globs(vec!["*.png".to_string()])
+        .unwrap()
+        .lenient(true)
+        .ingest_zip_file(&zip_path)
+        .unwrap();
+
+    let report = builder.report();
+    assert_eq!(report.added, 1);
+    assert_eq!(report.skipped.len(), 3);
+    assert_eq!(
+        report.skipped[0],
+        (
+            "big.bin".to_string(),
+            SkipReason::TooLarge { size: 64, max: 32 }
+        )
+    );
+    assert_eq!(report.skipped[1].0, "../../etc/passwd");
+    assert!(matches!(report.skipped[1].1, SkipReason::InvalidPath(_)));
+    assert_eq!(
+        report.skipped[2],
+        ("logo.png".to_string(), SkipReason::Filtered)
+    );
+
+    let sandbox = builder.build();
+    assert_eq!(sandbox.file_count(), 1);
+    assert!(sandbox.get("src/lib.rs").is_some());
+}
+
+#[test]
+fn test_strict_ingest_fails_on_oversized_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(&zip_path, &[("repo-main/big.bin", &[7u8; 64])]);
+
+    let result = SandboxBuilder::new()
+        .max_file_size(32)
+        .ingest_zip_file(&zip_path);
+
+    assert!(matches!(
+        result,
+        Err(crate::SandboxError::FileTooLarge { max: 32, .. })
+    ));
+}
+
+#[test]
+fn test_lenient_local_dir_skips_oversized_file() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("small.txt"), b"ok").unwrap();
+    std::fs::write(dir.path().join("large.txt"), vec![b'x'; 100]).unwrap();
+
+    let builder = SandboxBuilder::new()
+        .max_file_size(10)
+        .lenient(true)
+        .ingest_local_dir(dir.path())
+        .unwrap();
+
+    assert_eq!(builder.report().added, 1);
+    assert_eq!(
+        builder.report().skipped,
+        vec![(
+            "large.txt".to_string(),
+            SkipReason::TooLarge { size: 100, max: 10 }
+        )]
+    );
+}
+
+#[test]
+fn test_update_from_zip_file_reports_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_zip = dir.path().join("old.zip");
+    let new_zip = dir.path().join("new.zip");
+    write_test_zip(
+        &old_zip,
+        &[
+            ("repo-main/README.md", b"# repo"),
+            ("repo-main/src/lib.rs", b"pub fn a() {}"),
+            ("repo-main/src/old.rs", b"fn old() {}"),
+        ],
+    );
+    write_test_zip(
+        &new_zip,
+        &[
+            ("repo-main/README.md", b"# repo"),
+            ("repo-main/src/lib.rs", b"pub fn b() {}"),
+            ("repo-main/src/new.rs", b"fn new() {}"),
+        ],
+    );
+
+    let prev = SandboxBuilder::new()
+        .ingest_zip_file(&old_zip)
+        .unwrap()
+        .build();
+    let (builder, report) = SandboxBuilder::new()
+        .update_from_zip_file(&prev, &new_zip)
+        .unwrap();
+
+    assert_eq!(report.added, vec!["src/new.rs"]);
+    assert_eq!(report.modified, vec!["src/lib.rs"]);
+    assert_eq!(report.removed, vec!["src/old.rs"]);
+    assert_eq!(report.unchanged, 1);
+
+    let sandbox = builder.build();
+    assert_eq!(sandbox.file_count(), 3);
+    assert_eq!(sandbox.get("README.md").unwrap(), b"# repo");
+    assert_eq!(sandbox.get("src/lib.rs").unwrap(), b"pub fn b() {}");
+    assert!(sandbox.get("src/old.rs").is_none());
+}
+
+#[test]
+fn test_update_with_tighter_limit_skips_unchanged_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    let big = vec![b'x'; 100];
+    write_test_zip(
+        &zip_path,
+        &[("repo-main/big.txt", &big), ("repo-main/small.txt", b"ok")],
+    );
+
+    let prev = SandboxBuilder::new()
+        .ingest_zip_file(&zip_path)
+        .unwrap()
+        .build();
+    let (builder, report) = SandboxBuilder::new()
+        .lenient(true)
+        .max_file_size(10)
+        .update_from_zip_file(&prev, &zip_path)
+        .unwrap();
+
+    // The skipped file isn't unchanged: it's gone from the new sandbox
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(report.removed, vec!["big.txt"]);
+    assert_eq!(
+        builder.report().skipped,
+        vec![(
+            "big.txt".to_string(),
+            SkipReason::TooLarge { size: 100, max: 10 }
+        )]
+    );
+
+    let sandbox = builder.build();
+    assert!(sandbox.get("big.txt").is_none());
+    assert_eq!(sandbox.get("small.txt").unwrap(), b"ok");
+}
+
+#[test]
+fn test_update_detects_same_size_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("repo.zip");
+    write_test_zip(&zip_path, &[("repo-main/a.txt", b"same")]);
+
+    // Same length, different bytes: only the checksum tells them apart
+    let mut builder = SandboxBuilder::new();
+    builder.add_file("a.txt", b"diff").unwrap();
+    let prev = builder.build();
+
+    let (builder, report) = SandboxBuilder::new()
+        .update_from_zip_file(&prev, &zip_path)
+        .unwrap();
+
+    assert_eq!(report.modified, vec!["a.txt"]);
+    assert_eq!(report.unchanged, 0);
+    assert_eq!(builder.build().get("a.txt").unwrap(), b"same");
+}
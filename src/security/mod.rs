@@ -4,4 +4,4 @@ mod path;
 #[path = "path_tests.rs"]
 mod path_tests;
 
-pub use path::PathSanitizer;
+pub use path::{DEFAULT_MAX_COMPONENTS, DEFAULT_MAX_PATH_LEN, PathSanitizer, SanitizeOptions};
@@ -1,12 +1,48 @@
 use crate::sandbox::SandboxError;
 use std::path::{Component, Path};
 
+/// Default maximum number of components in a sanitized path
+pub const DEFAULT_MAX_COMPONENTS: usize = 32;
+
+/// Default maximum byte length of a raw path
+pub const DEFAULT_MAX_PATH_LEN: usize = 4096;
+
+/// Windows device names that cannot be used as a file stem
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Options controlling how strictly a path is sanitized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Allow components starting with `.`
+    pub allow_hidden: bool,
+    /// Reject Windows device names (CON, NUL, COM1, ...) for portability
+    pub reject_reserved_names: bool,
+    /// Maximum number of path components
+    pub max_components: usize,
+    /// Maximum byte length of the raw path
+    pub max_byte_len: usize,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            allow_hidden: true,
+            reject_reserved_names: false,
+            max_components: DEFAULT_MAX_COMPONENTS,
+            max_byte_len: DEFAULT_MAX_PATH_LEN,
+        }
+    }
+}
+
 pub struct PathSanitizer;
 
 impl PathSanitizer {
     /// Sanitize a path from a ZIP archive to prevent:
     /// - Directory traversal (../)
-    /// - Absolute paths (/etc/passwd)
+    /// - Absolute paths (/etc/passwd, C:\foo, \\server\share)
     /// - Zip slip attacks
     ///
     /// Hidden files (starting with .) are allowed for analysis purposes.
@@ -15,17 +51,86 @@ impl PathSanitizer {
         Self::sanitize_with_options(raw_path, true)
     }
 
-    /// Sanitize with custom options
+    /// Sanitize many paths at once, returning one result per input path
+    ///
+    /// Unlike bailing on the first error, this lets callers ingest the valid
+    /// paths while reporting why each rejected path was skipped.
+    pub fn sanitize_batch(paths: &[&str], allow_hidden: bool) -> Vec<Result<String, SandboxError>> {
+        paths
+            .iter()
+            .map(|path| Self::sanitize_with_options(path, allow_hidden))
+            .collect()
+    }
+
+    /// Sanitize with custom options, using the default depth and length limits
     pub fn sanitize_with_options(
         raw_path: &str,
         allow_hidden: bool,
     ) -> Result<String, SandboxError> {
+        Self::sanitize_with_limits(
+            raw_path,
+            allow_hidden,
+            DEFAULT_MAX_COMPONENTS,
+            DEFAULT_MAX_PATH_LEN,
+        )
+    }
+
+    /// Sanitize with custom options and explicit limits on path depth
+    /// (number of components) and raw byte length
+    pub fn sanitize_with_limits(
+        raw_path: &str,
+        allow_hidden: bool,
+        max_components: usize,
+        max_byte_len: usize,
+    ) -> Result<String, SandboxError> {
+        Self::sanitize_with(
+            raw_path,
+            &SanitizeOptions {
+                allow_hidden,
+                max_components,
+                max_byte_len,
+                ..SanitizeOptions::default()
+            },
+        )
+    }
+
+    /// Sanitize with the full set of options
+    pub fn sanitize_with(
+        raw_path: &str,
+        options: &SanitizeOptions,
+    ) -> Result<String, SandboxError> {
+        let SanitizeOptions {
+            allow_hidden,
+            reject_reserved_names,
+            max_components,
+            max_byte_len,
+        } = *options;
+
         // Reject empty paths
         if raw_path.is_empty() {
             return Err(SandboxError::InvalidPath("Empty path".to_string()));
         }
 
-        let path = Path::new(raw_path);
+        // Reject overly long paths before doing any parsing work
+        if raw_path.len() > max_byte_len {
+            return Err(SandboxError::PathTooLong {
+                len: raw_path.len(),
+                max: max_byte_len,
+            });
+        }
+
+        // Reject UNC paths (\\server\share) regardless of host OS
+        if raw_path.starts_with("\\\\") {
+            return Err(SandboxError::InvalidPath(format!(
+                "UNC path not allowed: {}",
+                raw_path
+            )));
+        }
+
+        // Canonicalize separators so archives built on Windows map to the
+        // same virtual paths on every host
+        let normalized = raw_path.replace('\\', "/");
+        let path = Path::new(&normalized);
         let mut components = Vec::new();
 
         for component in path.components() {
@@ -52,6 +157,24 @@ impl PathSanitizer {
                         SandboxError::InvalidPath(format!("Invalid UTF-8 in path: {:?}", part))
                     })?;
 
+                    // Reject NUL bytes and C0 control characters, a common
+                    // smuggling vector that many filesystems refuse anyway
+                    if part_str.chars().any(|c| c <= '\u{1f}') {
+                        return Err(SandboxError::InvalidPath(format!(
+                            "Control character not allowed: {:?}",
+                            raw_path
+                        )));
+                    }
+
+                    // Reject drive designators (C:) and NTFS stream names
+                    // (file:stream), which Unix hosts parse as plain names
+                    if part_str.contains(':') {
+                        return Err(SandboxError::InvalidPath(format!(
+                            "Drive letter or stream separator not allowed: {}",
+                            raw_path
+                        )));
+                    }
+
                     // Optionally reject hidden files/directories
                     if !allow_hidden && part_str.starts_with('.') {
                         return Err(SandboxError::InvalidPath(format!(
@@ -60,6 +183,11 @@ impl PathSanitizer {
                         )));
                     }
 
+                    // Optionally reject Windows device names (CON, con.txt, ...)
+                    if reject_reserved_names && Self::is_reserved_name(part_str) {
+                        return Err(SandboxError::ReservedName(raw_path.to_string()));
+                    }
+
                     components.push(part_str);
                 }
             }
@@ -73,7 +201,23 @@ impl PathSanitizer {
             )));
         }
 
+        // Reject deeply nested paths
+        if components.len() > max_components {
+            return Err(SandboxError::PathTooDeep {
+                depth: components.len(),
+                max: max_components,
+            });
+        }
+
         // Build normalized path with forward slashes
         Ok(components.join("/"))
     }
+
+    /// Check whether a component's stem is a reserved Windows device name
+    fn is_reserved_name(component: &str) -> bool {
+        let stem = component.split('.').next().unwrap_or(component).trim_end();
+        RESERVED_NAMES
+            .iter()
+            .any(|name| stem.eq_ignore_ascii_case(name))
+    }
 }
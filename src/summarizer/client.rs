@@ -1,4 +1,10 @@
-use reqwest::Client;
+use crate::summarizer::sse::SseDecoder;
+use crate::summarizer::types::*;
+use futures::stream::{self, Stream};
+use reqwest::blocking::Client as BlockingClient;
+use reqwest::{Client, Response};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -16,8 +22,10 @@ pub enum SummarizerError {
 
 pub struct DocumenterClient {
     http: Client,
+    /// Built on first blocking call; reqwest's blocking client owns a runtime
+    /// that must not be created or dropped inside an async context.
+    blocking_http: OnceLock<BlockingClient>,
     endpoint: String,
-    #[allow(dead_code)]
     timeout: Duration,
 }
 
@@ -34,18 +42,39 @@ impl DocumenterClient {
 
         Self {
             http,
+            blocking_http: OnceLock::new(),
             endpoint: endpoint.into(),
             timeout,
         }
     }
 
+    /// Report timeouts as `Timeout` so they can be told apart from an
+    /// unreachable server
+    fn request_error(&self, e: reqwest::Error) -> SummarizerError {
+        if e.is_timeout() {
+            SummarizerError::Timeout(self.timeout)
+        } else {
+            SummarizerError::RequestFailed(e)
+        }
+    }
+
+    fn blocking_http(&self) -> &BlockingClient {
+        self.blocking_http.get_or_init(|| {
+            BlockingClient::builder()
+                .timeout(self.timeout)
+                .build()
+                .expect("Failed to build blocking HTTP client")
+        })
+    }
+
     /// Check if the summarizer server is healthy
     pub async fn health_check(&self) -> Result<HealthResponse, SummarizerError> {
         let response = self
             .http
             .get(format!("{}/health", self.endpoint))
             .send()
-            .await?;
+            .await
+            .map_err(|e| self.request_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -59,7 +88,7 @@ impl DocumenterClient {
             });
         }
 
-        Ok(response.json().await?)
+        response.json().await.map_err(|e| self.request_error(e))
     }
 
     /// Summarize a text/code chunk
@@ -78,18 +107,40 @@ impl DocumenterClient {
         instructions: Option<String>,
         system_prompt: Option<String>,
     ) -> Result<String, SummarizerError> {
-        let req = SummarizeRequest {
+        self.send_summarize(&SummarizeRequest {
             text,
             instructions,
             system_prompt,
-        };
+            model: None,
+        })
+        .await
+    }
 
+    /// Summarize with a specific model from the server's
+    /// `available_models` instead of its default
+    pub async fn summarize_with_model(
+        &self,
+        text: String,
+        instructions: Option<String>,
+        model: Option<String>,
+    ) -> Result<String, SummarizerError> {
+        self.send_summarize(&SummarizeRequest {
+            text,
+            instructions,
+            system_prompt: None,
+            model,
+        })
+        .await
+    }
+
+    async fn send_summarize(&self, req: &SummarizeRequest) -> Result<String, SummarizerError> {
         let response = self
             .http
             .post(format!("{}/summarize", self.endpoint))
-            .json(&req)
+            .json(req)
             .send()
-            .await?;
+            .await
+            .map_err(|e| self.request_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -103,9 +154,183 @@ impl DocumenterClient {
             });
         }
 
-        let res: SummarizeResponse = response.json().await?;
+        let res: SummarizeResponse = response.json().await.map_err(|e| self.request_error(e))?;
         Ok(res.summary)
     }
+
+    /// Summarize a text/code chunk, yielding the summary piece by piece as
+    /// the model generates it
+    ///
+    /// Reads server-sent events from `/summarize/stream`; each event's data
+    /// is one delta, and a `[DONE]` event ends the stream. The client's
+    /// timeout covers the whole generation, not each delta.
+    pub fn summarize_stream(
+        &self,
+        text: String,
+        instructions: Option<String>,
+    ) -> impl Stream<Item = Result<String, SummarizerError>> + '_ {
+        let req = SummarizeRequest {
+            text,
+            instructions,
+            system_prompt: None,
+            model: None,
+        };
+
+        stream::unfold(StreamState::Pending(req), move |state| async move {
+            let (mut response, mut decoder, mut ready) = match state {
+                StreamState::Pending(req) => match self.open_stream(&req).await {
+                    Ok(response) => (response, SseDecoder::default(), VecDeque::new()),
+                    Err(e) => return Some((Err(e), StreamState::Done)),
+                },
+                StreamState::Open {
+                    response,
+                    decoder,
+                    ready,
+                } => (response, decoder, ready),
+                StreamState::Done => return None,
+            };
+
+            loop {
+                if let Some(delta) = ready.pop_front() {
+                    if delta == "[DONE]" {
+                        return None;
+                    }
+                    let state = StreamState::Open {
+                        response,
+                        decoder,
+                        ready,
+                    };
+                    return Some((Ok(delta), state));
+                }
+
+                match response.chunk().await {
+                    Ok(Some(bytes)) => ready.extend(decoder.push(&bytes)),
+                    Ok(None) => return None,
+                    Err(e) => return Some((Err(self.request_error(e)), StreamState::Done)),
+                }
+            }
+        })
+    }
+
+    async fn open_stream(&self, req: &SummarizeRequest) -> Result<Response, SummarizerError> {
+        let response = self
+            .http
+            .post(format!("{}/summarize/stream", self.endpoint))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(req)
+            .send()
+            .await
+            .map_err(|e| self.request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(SummarizerError::ServerError {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        Ok(response)
+    }
+
+    /// Blocking version of health_check for synchronous contexts
+    pub fn health_check_blocking(&self) -> Result<HealthResponse, SummarizerError> {
+        let response = self
+            .blocking_http()
+            .get(format!("{}/health", self.endpoint))
+            .send()
+            .map_err(|e| self.request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(SummarizerError::ServerError {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        response.json().map_err(|e| self.request_error(e))
+    }
+
+    /// Blocking version of summarize for synchronous contexts
+    pub fn summarize_blocking(
+        &self,
+        text: String,
+        instructions: Option<String>,
+    ) -> Result<String, SummarizerError> {
+        self.summarize_with_system_blocking(text, instructions, None)
+    }
+
+    /// Blocking version of summarize_with_system for synchronous contexts
+    pub fn summarize_with_system_blocking(
+        &self,
+        text: String,
+        instructions: Option<String>,
+        system_prompt: Option<String>,
+    ) -> Result<String, SummarizerError> {
+        self.send_summarize_blocking(&SummarizeRequest {
+            text,
+            instructions,
+            system_prompt,
+            model: None,
+        })
+    }
+
+    /// Blocking version of summarize_with_model for synchronous contexts
+    pub fn summarize_with_model_blocking(
+        &self,
+        text: String,
+        instructions: Option<String>,
+        model: Option<String>,
+    ) -> Result<String, SummarizerError> {
+        self.send_summarize_blocking(&SummarizeRequest {
+            text,
+            instructions,
+            system_prompt: None,
+            model,
+        })
+    }
+
+    fn send_summarize_blocking(&self, req: &SummarizeRequest) -> Result<String, SummarizerError> {
+        let response = self
+            .blocking_http()
+            .post(format!("{}/summarize", self.endpoint))
+            .json(req)
+            .send()
+            .map_err(|e| self.request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(SummarizerError::ServerError {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let res: SummarizeResponse = response.json().map_err(|e| self.request_error(e))?;
+        Ok(res.summary)
+    }
+}
+
+/// Progress of a `summarize_stream` call
+enum StreamState {
+    Pending(SummarizeRequest),
+    Open {
+        response: Response,
+        decoder: SseDecoder,
+        ready: VecDeque<String>,
+    },
+    Done,
 }
 
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,10 @@
+pub mod client;
+pub mod sse;
+pub mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::{DocumenterClient, HealthResponse, SummarizerError};
+pub use sse::SseDecoder;
+pub use types::{SummarizeRequest, SummarizeResponse};
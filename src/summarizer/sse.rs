@@ -0,0 +1,58 @@
+// sse.rs - incremental parsing of server-sent event streams
+
+/// Splits a `text/event-stream` body into the `data` of each event as
+/// bytes arrive
+///
+/// Only `data:` fields are kept; event names, ids and comments are
+/// ignored. Multi-line data is joined with "\n".
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// Bytes of the event in progress; decoded only once the event is
+    /// complete, so a character split across chunks survives
+    buf: Vec<u8>,
+}
+
+impl SseDecoder {
+    /// Feed the next piece of the body; returns the data of every event
+    /// completed by it
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(end) = event_end(&self.buf) {
+            let block: Vec<u8> = self.buf.drain(..end).collect();
+            if let Some(data) = event_data(&String::from_utf8_lossy(&block)) {
+                events.push(data);
+            }
+        }
+        events
+    }
+}
+
+/// Index just past the blank line ending the first complete event
+///
+/// Works on bytes, since a chunk may end inside a UTF-8 character; lines
+/// end in "\n" or "\r\n".
+fn event_end(buf: &[u8]) -> Option<usize> {
+    let mut line_start = 0;
+    for (i, &byte) in buf.iter().enumerate() {
+        if byte != b'\n' {
+            continue;
+        }
+        if matches!(&buf[line_start..i], [] | [b'\r']) {
+            return Some(i + 1);
+        }
+        line_start = i + 1;
+    }
+    None
+}
+
+fn event_data(block: &str) -> Option<String> {
+    let lines: Vec<&str> = block
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
@@ -0,0 +1,171 @@
+use super::*;
+use crate::testutil::{StubResponse, StubServer};
+use std::net::TcpListener;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_summarize_times_out() {
+    // Connections queue in the backlog but are never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let timeout = Duration::from_millis(100);
+    let client = DocumenterClient::with_timeout(endpoint, timeout);
+
+    let result = client.summarize("fn main() {}".to_string(), None).await;
+
+    assert!(matches!(result, Err(SummarizerError::Timeout(t)) if t == timeout));
+}
+
+#[tokio::test]
+async fn test_unreachable_server_is_not_timeout() {
+    // Bind and drop to get a port with nothing listening
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let client = DocumenterClient::new(format!("http://127.0.0.1:{port}"));
+
+    let result = client.health_check().await;
+
+    assert!(matches!(result, Err(SummarizerError::RequestFailed(_))));
+}
+
+#[test]
+fn test_request_omits_unset_fields() {
+    let req = SummarizeRequest {
+        text: "code".to_string(),
+        instructions: None,
+        system_prompt: None,
+        model: None,
+    };
+
+    assert_eq!(serde_json::to_string(&req).unwrap(), r#"{"text":"code"}"#);
+}
+
+#[tokio::test]
+async fn test_summarize_stream_yields_deltas() {
+    use futures::StreamExt;
+
+    let body = "data: Parses\n\ndata:  the\n\n: keep-alive\n\ndata:  config\n\ndata: [DONE]\n\n";
+    let server = StubServer::with_responses(vec![
+        StubResponse::new(200, "text/event-stream", body).in_pieces(7),
+    ]);
+    let client = DocumenterClient::new(server.endpoint());
+
+    let deltas: Vec<String> = client
+        .summarize_stream("fn parse() {}".to_string(), None)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(deltas, vec!["Parses", " the", " config"]);
+}
+
+#[tokio::test]
+async fn test_summarize_stream_reports_server_error() {
+    use futures::StreamExt;
+
+    let server = StubServer::with_responses(vec![StubResponse::json(503, "loading")]);
+    let client = DocumenterClient::new(server.endpoint());
+
+    let results: Vec<_> = client
+        .summarize_stream("fn parse() {}".to_string(), None)
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        &results[0],
+        Err(SummarizerError::ServerError { status: 503, body }) if body == "loading"
+    ));
+}
+
+#[test]
+fn test_sse_decoder_handles_split_events() {
+    let mut decoder = SseDecoder::default();
+
+    assert!(decoder.push(b"data: hel").is_empty());
+    assert_eq!(decoder.push(b"lo\r\n\r\ndata: a\r\n"), vec!["hello"]);
+    assert_eq!(decoder.push(b"data: b\r\n\r\n"), vec!["a\nb"]);
+}
+
+#[test]
+fn test_sse_decoder_keeps_split_multibyte_characters() {
+    let mut decoder = SseDecoder::default();
+    let event = "data: café\n\n".as_bytes();
+    let split = event.iter().position(|&b| b == 0xC3).unwrap() + 1;
+
+    assert!(decoder.push(&event[..split]).is_empty());
+    assert_eq!(decoder.push(&event[split..]), vec!["café"]);
+}
+
+#[test]
+fn test_sse_decoder_skips_events_without_data() {
+    let mut decoder = SseDecoder::default();
+
+    let events = decoder.push(b"event: ping\nid: 1\n\n: comment\n\ndata:x\n\n");
+
+    assert_eq!(events, vec!["x"]);
+}
+
+#[test]
+fn test_summarize_blocking() {
+    let server = StubServer::with_responses(vec![StubResponse::json(
+        200,
+        r#"{"summary":"Parses the config"}"#,
+    )]);
+    let client = DocumenterClient::new(server.endpoint());
+
+    let summary = client.summarize_blocking("fn parse() {}".to_string(), None);
+
+    assert_eq!(summary.unwrap(), "Parses the config");
+}
+
+#[test]
+fn test_health_check_blocking() {
+    let body = r#"{"status":"healthy","model":"qwen3-1.7b","available_models":["qwen3-1.7b"]}"#;
+    let server = StubServer::with_responses(vec![StubResponse::json(200, body)]);
+    let client = DocumenterClient::new(server.endpoint());
+
+    let health = client.health_check_blocking().unwrap();
+
+    assert_eq!(health.status, "healthy");
+    assert_eq!(health.model, "qwen3-1.7b");
+}
+
+#[test]
+fn test_summarize_blocking_reports_server_error() {
+    let server = StubServer::with_responses(vec![StubResponse::json(503, "loading")]);
+    let client = DocumenterClient::new(server.endpoint());
+
+    let result = client.summarize_blocking("fn parse() {}".to_string(), None);
+
+    assert!(matches!(
+        result,
+        Err(SummarizerError::ServerError { status: 503, .. })
+    ));
+}
+
+#[test]
+fn test_request_round_trips_model() {
+    let req = SummarizeRequest {
+        text: "code".to_string(),
+        instructions: None,
+        system_prompt: None,
+        model: Some("qwen3-7b".to_string()),
+    };
+
+    let json = serde_json::to_string(&req).unwrap();
+    assert_eq!(json, r#"{"text":"code","model":"qwen3-7b"}"#);
+
+    let parsed: SummarizeRequest = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.model.as_deref(), Some("qwen3-7b"));
+}
+
+#[test]
+fn test_request_without_model_parses() {
+    let parsed: SummarizeRequest = serde_json::from_str(r#"{"text":"code"}"#).unwrap();
+
+    assert_eq!(parsed.model, None);
+}
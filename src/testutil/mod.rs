@@ -0,0 +1,208 @@
+//! Helpers for testing code that talks to HTTP servers
+//!
+//! Enabled with the `testutil` feature (always on in this crate's tests).
+
+use crate::embedder::embed::l2_normalize;
+use crate::embedder::{EmbeddingRequest, EmbeddingResponse};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+/// What a `StubServer` answers a request with
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+    /// Write the body in pieces of this many bytes (all at once if unset)
+    pub piece_size: Option<usize>,
+}
+
+impl StubResponse {
+    pub fn new(status: u16, content_type: &str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: content_type.to_string(),
+            body: body.into(),
+            piece_size: None,
+        }
+    }
+
+    /// An `application/json` response
+    pub fn json(status: u16, body: impl Into<String>) -> Self {
+        Self::new(status, "application/json", body)
+    }
+
+    /// Flush the body `size` bytes at a time, so clients see it arrive
+    /// incrementally
+    pub fn in_pieces(mut self, size: usize) -> Self {
+        self.piece_size = Some(size.max(1));
+        self
+    }
+}
+
+/// In-process HTTP server answering each request with a handler
+///
+/// The handler gets the request path and body. Every response closes its
+/// connection, so each request is served on a connection of its own. The
+/// server stops when dropped.
+pub struct StubServer {
+    addr: SocketAddr,
+    endpoint: String,
+    requests: Arc<AtomicUsize>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl StubServer {
+    /// Answer each request with `handler(path, body)`
+    pub fn start(handler: impl Fn(&str, &str) -> StubResponse + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+        let addr = listener.local_addr().expect("Stub server has no address");
+        let requests = Arc::new(AtomicUsize::new(0));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let counter = Arc::clone(&requests);
+        let stop = Arc::clone(&shutdown);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                if let Ok(stream) = stream {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    handle(stream, &handler);
+                }
+            }
+        });
+
+        Self {
+            addr,
+            endpoint: format!("http://{}", addr),
+            requests,
+            shutdown,
+        }
+    }
+
+    /// Answer requests with `responses` in order, then with 500s
+    pub fn with_responses(responses: Vec<StubResponse>) -> Self {
+        let responses = Mutex::new(responses.into_iter());
+        Self::start(move |_, _| {
+            responses
+                .lock()
+                .unwrap()
+                .next()
+                .unwrap_or_else(|| StubResponse::json(500, ""))
+        })
+    }
+
+    /// Base URL of the server, without a trailing slash
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Number of requests served so far
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// Read a single request and write the handler's response
+fn handle(stream: TcpStream, handler: &impl Fn(&str, &str) -> StubResponse) {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    let _ = reader.read_line(&mut request_line);
+    let path = request_line.split(' ').nth(1).unwrap_or("").to_string();
+
+    let mut content_length = 0;
+    let mut line = String::new();
+    while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+        if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+        line.clear();
+    }
+    let mut body = vec![0; content_length];
+    let _ = reader.read_exact(&mut body);
+
+    let response = handler(&path, &String::from_utf8_lossy(&body));
+    let head = format!(
+        "HTTP/1.1 {} X\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+
+    let stream = reader.get_mut();
+    let _ = stream.write_all(head.as_bytes());
+    let body = response.body.as_bytes();
+    for piece in body.chunks(response.piece_size.unwrap_or(body.len().max(1))) {
+        let _ = stream.write_all(piece);
+        let _ = stream.flush();
+    }
+}
+
+/// In-process stand-in for the Python embedding server
+///
+/// Answers every POST carrying `{"texts": [...]}` with
+/// `{"embeddings": [...]}`, where each vector is `MockEmbedServer::embedding`
+/// of its text: deterministic, unit length and different for different
+/// texts. Malformed requests get a 400. The server stops when dropped.
+pub struct MockEmbedServer {
+    server: StubServer,
+}
+
+impl MockEmbedServer {
+    /// Serve 768-dimensional vectors, like the default model
+    pub fn start() -> Self {
+        Self::with_dim(768)
+    }
+
+    /// Serve `dim`-dimensional vectors
+    pub fn with_dim(dim: usize) -> Self {
+        let server = StubServer::start(move |_, body| {
+            match serde_json::from_str::<EmbeddingRequest>(body) {
+                Ok(request) => {
+                    let embeddings = request
+                        .texts
+                        .iter()
+                        .map(|text| Self::embedding(text, dim))
+                        .collect();
+                    StubResponse::json(200, json!(EmbeddingResponse { embeddings }).to_string())
+                }
+                Err(e) => StubResponse::json(400, json!({ "detail": e.to_string() }).to_string()),
+            }
+        });
+        Self { server }
+    }
+
+    /// Base URL to hand to `EmbeddingClient::new`
+    pub fn endpoint(&self) -> &str {
+        self.server.endpoint()
+    }
+
+    /// Number of requests served so far
+    pub fn request_count(&self) -> usize {
+        self.server.request_count()
+    }
+
+    /// The vector the server returns for `text`
+    pub fn embedding(text: &str, dim: usize) -> Vec<f32> {
+        let mut v: Vec<f32> = (0..dim as u64)
+            .map(|i| (xxh3_64_with_seed(text.as_bytes(), i) % 2001) as f32 / 1000.0 - 1.0)
+            .collect();
+        l2_normalize(&mut v);
+        v
+    }
+}